@@ -7,6 +7,8 @@ mod scene1;
 use scene1::Scene1;
 mod scene2;
 use scene2::Scene2;
+mod scene3;
+use scene3::Scene3;
 
 fn main() {
   launch(WinitConfig {
@@ -15,5 +17,5 @@ fn main() {
     title: "Re:Blueprint".to_owned(),
     icon: Some("icon.ico".to_owned()),
     ..Default::default()
-  }, vec![Box::new(Scene1::new()), Box::new(Scene2::new())]);
+  }, vec![Box::new(Scene1::new()), Box::new(Scene2::new()), Box::new(Scene3::new())]);
 }
\ No newline at end of file
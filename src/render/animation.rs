@@ -0,0 +1,167 @@
+use crate::utils::{Mat4, Vec3, Vec4};
+
+/// keyframed local transform of one joint, empty channels hold the rest pose
+#[derive(Debug, Clone)]
+pub struct AnimJoint {
+  /// parent joint index, None for roots
+  pub parent: Option<usize>,
+  /// local (translation, rotation quaternion, scale) when a channel has no keys
+  pub rest: (Vec3, Vec4, Vec3),
+  /// model space -> joint space at bind time, identity if vertices are already joint relative
+  pub inverse_bind: [f32; 16],
+  /// (time in seconds, value) sorted by time
+  pub translations: Vec<(f32, Vec3)>,
+  pub rotations: Vec<(f32, Vec4)>,
+  pub scales: Vec<(f32, Vec3)>,
+}
+impl AnimJoint {
+  pub fn new(parent: Option<usize>) -> Self {
+    Self {
+      parent,
+      rest: (Vec3::zero(), Vec4::unit_w(), Vec3::one()),
+      inverse_bind: Mat4::identity().as_col_major_array(),
+      translations: Vec::new(),
+      rotations: Vec::new(),
+      scales: Vec::new(),
+    }
+  }
+  pub fn with_rest(mut self, translation: Vec3, rotation: Vec4, scale: Vec3) -> Self {
+    self.rest = (translation, rotation, scale);
+    self
+  }
+  pub fn with_inverse_bind(mut self, inverse_bind: [f32; 16]) -> Self {
+    self.inverse_bind = inverse_bind;
+    self
+  }
+  pub fn with_translations(mut self, keys: Vec<(f32, Vec3)>) -> Self {
+    self.translations = keys;
+    self
+  }
+  pub fn with_rotations(mut self, keys: Vec<(f32, Vec4)>) -> Self {
+    self.rotations = keys;
+    self
+  }
+  pub fn with_scales(mut self, keys: Vec<(f32, Vec3)>) -> Self {
+    self.scales = keys;
+    self
+  }
+  // local translate * rotate * scale at time t
+  fn local_matrix(&self, t: f32) -> [f32; 16] {
+    let tr = sample_keys(&self.translations, t, self.rest.0, Vec3::lerp);
+    let rot = sample_keys(&self.rotations, t, self.rest.1, Vec4::quat_slerp);
+    let sc = sample_keys(&self.scales, t, self.rest.2, Vec3::lerp);
+    let ts = Mat4::multiply(&Mat4::translate(tr.x, tr.y, tr.z), &Mat4::rotate_quat(&rot));
+    Mat4::multiply(&ts, &Mat4::scale(sc.x, sc.y, sc.z))
+  }
+}
+
+/// joint hierarchy + keyframes, sampled into matrices for RenderObjectUpdate::with_anim
+#[derive(Debug, Clone)]
+pub struct Animation {
+  pub joints: Vec<AnimJoint>,
+  /// length in seconds, defaults to the last keyframe time
+  pub duration: f32,
+  pub looping: bool,
+}
+impl Animation {
+  pub fn new(joints: Vec<AnimJoint>) -> Self {
+    let duration = joints.iter()
+      .flat_map(|j| {
+        let t = j.translations.last().map(|k| k.0);
+        let r = j.rotations.last().map(|k| k.0);
+        let s = j.scales.last().map(|k| k.0);
+        [t, r, s].into_iter().flatten()
+      })
+      .fold(0.0, f32::max);
+    Self { joints, duration, looping: true }
+  }
+  pub fn with_looping(mut self, looping: bool) -> Self {
+    self.looping = looping;
+    self
+  }
+  /// per-joint model space skinning matrices (global * inverse bind) at time in seconds
+  pub fn sample(&self, time: f32) -> Vec<[f32; 16]> {
+    let t = if self.looping && self.duration > 0.0 { time.rem_euclid(self.duration) }
+      else { time.clamp(0.0, self.duration) };
+    let locals: Vec<[f32; 16]> = self.joints.iter().map(|j| j.local_matrix(t)).collect();
+    let mut globals: Vec<Option<[f32; 16]>> = vec![None; self.joints.len()];
+    for i in 0..self.joints.len() {
+      self.resolve_global(i, &locals, &mut globals, 0);
+    }
+    globals.iter().zip(&self.joints)
+      .map(|(g, j)| Mat4::multiply(&g.unwrap(), &j.inverse_bind))
+      .collect()
+  }
+  // parent global * local, joints can be listed in any order
+  fn resolve_global(&self, i: usize, locals: &[[f32; 16]], globals: &mut [Option<[f32; 16]>], depth: usize) -> [f32; 16] {
+    if let Some(m) = globals[i] { return m; }
+    let m = match self.joints[i].parent {
+      // invalid parents + cycles are treated as roots
+      Some(p) if p < self.joints.len() && depth < self.joints.len() => {
+        Mat4::multiply(&self.resolve_global(p, locals, globals, depth + 1), &locals[i])
+      }
+      _ => locals[i],
+    };
+    globals[i] = Some(m);
+    m
+  }
+}
+
+// interpolated value between the surrounding keys, clamped to the first/last key
+fn sample_keys<T: Copy>(keys: &[(f32, T)], t: f32, rest: T, mix: fn(T, T, f32) -> T) -> T {
+  if keys.is_empty() { return rest; }
+  let next = keys.partition_point(|k| k.0 <= t);
+  if next == 0 { return keys[0].1; }
+  if next == keys.len() { return keys[next - 1].1; }
+  let (t0, v0) = keys[next - 1];
+  let (t1, v1) = keys[next];
+  let span = t1 - t0;
+  if span <= 0.0 { return v1; }
+  mix(v0, v1, (t - t0) / span)
+}
+
+#[cfg(test)]
+mod animation_tests {
+  use super::*;
+
+  fn translation(m: &[f32; 16]) -> Vec3 {
+    Vec3::new(m[12], m[13], m[14])
+  }
+
+  #[test]
+  fn anim_interpolates_keys() {
+    let joint = AnimJoint::new(None)
+      .with_translations(vec![(0.0, Vec3::zero()), (2.0, Vec3::new(4.0, 0.0, 0.0))]);
+    let anim = Animation::new(vec![joint]).with_looping(false);
+    assert_eq!(anim.duration, 2.0);
+    assert_eq!(translation(&anim.sample(1.0)[0]), Vec3::new(2.0, 0.0, 0.0));
+    // clamped past the end
+    assert_eq!(translation(&anim.sample(5.0)[0]), Vec3::new(4.0, 0.0, 0.0));
+    // looping wraps back to the start
+    let anim = anim.with_looping(true);
+    assert_eq!(translation(&anim.sample(2.5)[0]), Vec3::new(1.0, 0.0, 0.0));
+  }
+
+  #[test]
+  fn anim_composes_hierarchy() {
+    // child listed before its parent
+    let child = AnimJoint::new(Some(1)).with_rest(Vec3::unit_y(), Vec4::unit_w(), Vec3::one());
+    let quarter_z = Vec4::quat_from_axis_angle(Vec3::unit_z(), std::f32::consts::FRAC_PI_2);
+    let root = AnimJoint::new(None).with_rotations(vec![(0.0, quarter_z)])
+      .with_translations(vec![(0.0, Vec3::new(1.0, 0.0, 0.0))]);
+    let mats = Animation::new(vec![child, root]).sample(0.0);
+    // parent rotation turns the child's +y offset into -x, cancelling the parent's +x
+    assert!(translation(&mats[0]).magnitude() < 1e-5);
+    assert_eq!(translation(&mats[1]), Vec3::new(1.0, 0.0, 0.0));
+  }
+
+  #[test]
+  fn anim_applies_inverse_bind() {
+    let joint = AnimJoint::new(None)
+      .with_rest(Vec3::new(0.0, 3.0, 0.0), Vec4::unit_w(), Vec3::one())
+      .with_inverse_bind(Mat4::translate(0.0, -3.0, 0.0));
+    // rest pose cancels out with its own inverse bind
+    let m = Animation::new(vec![joint]).sample(0.0);
+    assert_eq!(m[0], Mat4::identity().as_col_major_array());
+  }
+}
@@ -0,0 +1,91 @@
+use crate::utils::{Vec2, Vec3};
+use crate::vec3f;
+
+use super::RenderCamera;
+
+/// ortho RenderCamera with 2d panning + zooming, world y points up.
+/// screen coordinates are pixels with a top left origin (same as the mouse position)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Camera2D {
+  camera: RenderCamera,
+}
+impl Camera2D {
+  pub fn new(target_size: Vec2) -> Self {
+    Self { camera: RenderCamera::new_ortho(1.0, 1000.0, target_size) }
+  }
+  /// pass to RenderObjectUpdate::with_camera
+  pub fn camera(&self) -> &RenderCamera {
+    &self.camera
+  }
+  pub fn set_target_size(&mut self, target_size: Vec2) {
+    self.camera.target_size = target_size;
+  }
+  /// world position at the center of the view
+  pub fn position(&self) -> Vec2 {
+    Vec2::new(self.camera.position.x, self.camera.position.y)
+  }
+  pub fn set_position(&mut self, position: Vec2) {
+    let z = self.camera.position.z;
+    self.camera.position = vec3f!(position.x, position.y, z);
+    self.camera.look_at = vec3f!(position.x, position.y, 0.0);
+  }
+  pub fn zoom(&self) -> f32 {
+    self.camera.zoom
+  }
+  pub fn set_zoom(&mut self, zoom: f32) {
+    self.camera.set_zoom(zoom);
+  }
+  /// moves the view with a screen space drag, content follows the cursor
+  pub fn pan(&mut self, screen_delta: Vec2) {
+    let d = Vec2::new(-screen_delta.x, screen_delta.y) / self.camera.zoom;
+    self.set_position(self.position() + d);
+  }
+  /// multiplies zoom while keeping the world point under cursor (screen space) in place
+  pub fn zoom_at(&mut self, cursor: Vec2, factor: f32) {
+    let before = self.screen_to_world(cursor);
+    self.set_zoom(self.camera.zoom * factor);
+    let after = self.screen_to_world(cursor);
+    self.set_position(self.position() + before - after);
+  }
+  pub fn screen_to_world(&self, screen: Vec2) -> Vec2 {
+    let half = self.camera.target_size * 0.5;
+    let offset = Vec2::new(screen.x - half.x, half.y - screen.y) / self.camera.zoom;
+    self.position() + offset
+  }
+  pub fn world_to_screen(&self, world: Vec2) -> Vec2 {
+    let half = self.camera.target_size * 0.5;
+    let offset = (world - self.position()) * self.camera.zoom;
+    Vec2::new(half.x + offset.x, half.y - offset.y)
+  }
+}
+
+#[cfg(test)]
+mod camera2d_tests {
+  use super::*;
+  use crate::vec2f;
+
+  #[test]
+  fn camera2d_matches_projection() {
+    let size = vec2f!(200.0, 100.0);
+    let mut cam = Camera2D::new(size);
+    cam.set_position(vec2f!(30.0, -10.0));
+    cam.set_zoom(2.0);
+    let world = vec2f!(50.0, 5.0);
+    let screen = cam.world_to_screen(world);
+    let projected = cam.camera().world_to_screen(vec3f!(world.x, world.y, 0.0), size).unwrap();
+    assert!(Vec2::distance(screen, projected) < 1e-3);
+    assert!(Vec2::distance(cam.screen_to_world(screen), world) < 1e-3);
+  }
+
+  #[test]
+  fn camera2d_pan_and_zoom_at() {
+    let mut cam = Camera2D::new(vec2f!(200.0, 100.0));
+    let cursor = vec2f!(150.0, 20.0);
+    let under = cam.screen_to_world(cursor);
+    cam.zoom_at(cursor, 3.0);
+    assert!(Vec2::distance(cam.screen_to_world(cursor), under) < 1e-3);
+    // dragging moves the grabbed point along with the cursor
+    cam.pan(vec2f!(10.0, -5.0));
+    assert!(Vec2::distance(cam.world_to_screen(under), cursor + vec2f!(10.0, -5.0)) < 1e-3);
+  }
+}
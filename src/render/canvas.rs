@@ -0,0 +1,168 @@
+use wgpu::{Device, Queue, Texture};
+
+use super::{build_texture, downscale_rgba, update_texture, RenderColor};
+
+/// cpu side rgba8 drawing surface (top left origin, pixel coords),
+/// uploaded to a single texture on commit
+#[derive(Debug)]
+pub struct Canvas2D {
+  width: u32,
+  height: u32,
+  pixels: Vec<u8>,
+  texture: Option<Texture>,
+}
+impl Canvas2D {
+  pub fn new(width: u32, height: u32) -> Self {
+    Self {
+      width,
+      height,
+      pixels: vec![0; (width * height * 4) as usize],
+      texture: None,
+    }
+  }
+  pub fn width(&self) -> u32 {
+    self.width
+  }
+  pub fn height(&self) -> u32 {
+    self.height
+  }
+  /// raw rgba8 pixels, row by row
+  pub fn pixels(&self) -> &[u8] {
+    &self.pixels
+  }
+  pub fn get_pixel(&self, x: i32, y: i32) -> Option<[u8; 4]> {
+    let i = self.index(x, y)?;
+    Some([self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]])
+  }
+  /// overwrites every pixel (no blending)
+  pub fn clear(&mut self, color: RenderColor) {
+    let c: [u8; 4] = color.into();
+    for px in self.pixels.chunks_exact_mut(4) {
+      px.copy_from_slice(&c);
+    }
+  }
+  /// alpha blends color over the pixel, out of bounds is ignored
+  pub fn blend_pixel(&mut self, x: i32, y: i32, color: [u8; 4]) {
+    let Some(i) = self.index(x, y) else { return; };
+    let a = color[3] as u32;
+    if a == 255 {
+      self.pixels[i..i + 4].copy_from_slice(&color);
+      return;
+    }
+    // src over dst
+    for (dst, src) in self.pixels[i..i + 3].iter_mut().zip(&color[0..3]) {
+      *dst = ((*src as u32 * a + *dst as u32 * (255 - a) + 127) / 255) as u8;
+    }
+    let dst_a = self.pixels[i + 3] as u32;
+    self.pixels[i + 3] = (a + (dst_a * (255 - a) + 127) / 255) as u8;
+  }
+  pub fn fill_rect(&mut self, x: i32, y: i32, width: u32, height: u32, color: RenderColor) {
+    let c: [u8; 4] = color.into();
+    // clip to canvas before iterating
+    let x0 = x.max(0);
+    let y0 = y.max(0);
+    let x1 = (x + width as i32).min(self.width as i32);
+    let y1 = (y + height as i32).min(self.height as i32);
+    for py in y0..y1 {
+      for px in x0..x1 {
+        self.blend_pixel(px, py, c);
+      }
+    }
+  }
+  /// 1px line between both endpoints (inclusive)
+  pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: RenderColor) {
+    let c: [u8; 4] = color.into();
+    // bresenham
+    let dx = (x1 - x0).abs();
+    let dy = -(y1 - y0).abs();
+    let sx = if x0 < x1 { 1 } else { -1 };
+    let sy = if y0 < y1 { 1 } else { -1 };
+    let (mut x, mut y) = (x0, y0);
+    let mut err = dx + dy;
+    loop {
+      self.blend_pixel(x, y, c);
+      if x == x1 && y == y1 { break; }
+      let e2 = 2 * err;
+      if e2 >= dy { err += dy; x += sx; }
+      if e2 <= dx { err += dx; y += sy; }
+    }
+  }
+  /// filled circle of pixels whose centers lie within radius
+  pub fn draw_circle(&mut self, cx: i32, cy: i32, radius: f32, color: RenderColor) {
+    let c: [u8; 4] = color.into();
+    let r = radius.ceil() as i32;
+    for py in (cy - r)..=(cy + r) {
+      for px in (cx - r)..=(cx + r) {
+        let (dx, dy) = ((px - cx) as f32, (py - cy) as f32);
+        if dx * dx + dy * dy <= radius * radius {
+          self.blend_pixel(px, py, c);
+        }
+      }
+    }
+  }
+  /// alpha blends rgba8 pixel data (width x height) with its top left at (x, y)
+  pub fn blit_image(&mut self, x: i32, y: i32, width: u32, height: u32, data: &[u8]) {
+    if data.len() < (width * height * 4) as usize {
+      println!("ERR: Image data is smaller than {}x{}", width, height);
+      return;
+    }
+    for iy in 0..height {
+      for ix in 0..width {
+        let i = ((iy * width + ix) * 4) as usize;
+        let px = [data[i], data[i + 1], data[i + 2], data[i + 3]];
+        self.blend_pixel(x + ix as i32, y + iy as i32, px);
+      }
+    }
+  }
+  /// uploads the pixels, creating the texture on the first call.
+  /// the returned handle can be given to an ObjPipeline, later commits update it in place
+  pub fn commit(&mut self, device: &Device, queue: &Queue) -> Texture {
+    match &self.texture {
+      Some(tx) if tx.width() == self.width && tx.height() == self.height => update_texture(queue, tx, &self.pixels),
+      // build_texture downscaled an oversized canvas, scale each commit down to the same size
+      Some(tx) => {
+        let (_, _, scaled) = downscale_rgba(self.width, self.height, &self.pixels, tx.width().max(tx.height()));
+        update_texture(queue, tx, &scaled);
+      }
+      None => self.texture = Some(build_texture(device, queue, self.width, self.height, &self.pixels, false)),
+    }
+    self.texture.clone().unwrap()
+  }
+  fn index(&self, x: i32, y: i32) -> Option<usize> {
+    if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 { return None; }
+    Some(((y as u32 * self.width + x as u32) * 4) as usize)
+  }
+}
+
+#[cfg(test)]
+mod canvas_tests {
+  use super::*;
+
+  #[test]
+  fn canvas_fill_rect_clips() {
+    let mut cv = Canvas2D::new(4, 4);
+    cv.fill_rect(-2, 2, 10, 10, RenderColor::RED);
+    assert_eq!(cv.get_pixel(0, 1), Some([0, 0, 0, 0]));
+    assert_eq!(cv.get_pixel(3, 3), Some([255, 0, 0, 255]));
+    assert_eq!(cv.get_pixel(4, 3), None);
+  }
+
+  #[test]
+  fn canvas_line_endpoints() {
+    let mut cv = Canvas2D::new(8, 8);
+    cv.draw_line(1, 1, 6, 3, RenderColor::WHITE);
+    assert_eq!(cv.get_pixel(1, 1), Some([255; 4]));
+    assert_eq!(cv.get_pixel(6, 3), Some([255; 4]));
+    let lit = cv.pixels().chunks_exact(4).filter(|p| p[3] > 0).count();
+    assert_eq!(lit, 6);
+  }
+
+  #[test]
+  fn canvas_blend() {
+    let mut cv = Canvas2D::new(1, 1);
+    cv.clear(RenderColor::BLACK);
+    cv.blit_image(0, 0, 1, 1, &[255, 255, 255, 128]);
+    let px = cv.get_pixel(0, 0).unwrap();
+    assert_eq!(px, [128, 128, 128, 255]);
+  }
+}
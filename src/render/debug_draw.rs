@@ -0,0 +1,171 @@
+use wgpu::{
+  vertex_attr_array, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor, BindGroupLayoutEntry,
+  BindingType, BlendState, Buffer, BufferAddress, BufferBindingType, BufferDescriptor, BufferUsages, ColorTargetState,
+  ColorWrites, Device, FragmentState, MultisampleState, PipelineCompilationOptions, PipelineLayoutDescriptor,
+  PrimitiveState, PrimitiveTopology, Queue, RenderPass, RenderPipeline, RenderPipelineDescriptor,
+  ShaderModuleDescriptor, ShaderSource, ShaderStages, TextureFormat, VertexBufferLayout, VertexState, VertexStepMode
+};
+
+use crate::utils::{Vec2, Vec3, PI};
+use super::{write_camera_buffer, RenderCamera, RenderColor, RenderDebugVertex};
+
+// segments used to approximate debug circles
+const DEBUG_CIRCLE_SEGMENTS: u32 = 32;
+
+/// immediate mode line drawing for visualizing positions + vectors,
+/// shapes queued during a frame are uploaded by flush and cleared for the next frame
+#[derive(Debug)]
+pub struct DebugDraw {
+  pipeline: RenderPipeline,
+  bind_group: BindGroup,
+  camera_buffer: Buffer,
+  vertex_buffer: Buffer,
+  // vertex capacity of vertex_buffer
+  capacity: usize,
+  vertices: Vec<RenderDebugVertex>,
+  // vertex count of the last flush
+  drawn: u32,
+}
+impl DebugDraw {
+  pub fn new(device: &Device, target_format: TextureFormat) -> Self {
+    let shader_mod = device.create_shader_module(ShaderModuleDescriptor {
+      label: Some("debug-shader"),
+      source: ShaderSource::Wgsl(include_str!("shaders/debug.wgsl").into()),
+    });
+    let layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+      label: Some("debug-bind-group-layout"),
+      entries: &[BindGroupLayoutEntry {
+        binding: 0,
+        visibility: ShaderStages::VERTEX,
+        ty: BindingType::Buffer {
+          ty: BufferBindingType::Uniform,
+          has_dynamic_offset: false,
+          min_binding_size: None,
+        },
+        count: None,
+      }],
+    });
+    let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+      label: Some("debug-pipeline-layout"),
+      bind_group_layouts: &[&layout],
+      push_constant_ranges: &[]
+    });
+    let vertex_attr = vertex_attr_array![0 => Float32x3, 1 => Float32x4];
+    let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+      label: Some("debug-pipeline"),
+      layout: Some(&pipeline_layout),
+      vertex: VertexState {
+        module: &shader_mod,
+        entry_point: Some("vertex_main"),
+        buffers: &[VertexBufferLayout {
+          array_stride: std::mem::size_of::<RenderDebugVertex>() as BufferAddress,
+          step_mode: VertexStepMode::Vertex,
+          attributes: &vertex_attr,
+        }],
+        compilation_options: PipelineCompilationOptions::default(),
+      },
+      fragment: Some(FragmentState {
+        module: &shader_mod,
+        entry_point: Some("fragment_main"),
+        targets: &[Some(ColorTargetState {
+          format: target_format,
+          blend: Some(BlendState::ALPHA_BLENDING),
+          write_mask: ColorWrites::ALL
+        })],
+        compilation_options: PipelineCompilationOptions::default(),
+      }),
+      multisample: MultisampleState::default(),
+      // drawn over everything, debug shapes should never be hidden
+      depth_stencil: None,
+      primitive: PrimitiveState { topology: PrimitiveTopology::LineList, ..PrimitiveState::default() },
+      multiview: None,
+      cache: None,
+    });
+    let camera_buffer = device.create_buffer(&BufferDescriptor {
+      label: Some("debug-camera-buffer"),
+      size: std::mem::size_of::<[f32; 32]>() as u64,
+      usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+      mapped_at_creation: false,
+    });
+    let bind_group = device.create_bind_group(&BindGroupDescriptor {
+      label: Some("debug-bind-group"),
+      layout: &layout,
+      entries: &[BindGroupEntry { binding: 0, resource: camera_buffer.as_entire_binding() }],
+    });
+    let capacity = 1024;
+
+    Self {
+      pipeline,
+      bind_group,
+      camera_buffer,
+      vertex_buffer: build_debug_vertex_buffer(device, capacity),
+      capacity,
+      vertices: Vec::new(),
+      drawn: 0,
+    }
+  }
+  pub fn update_camera(&mut self, queue: &Queue, camera: &RenderCamera) {
+    write_camera_buffer(queue, &self.camera_buffer, camera);
+  }
+  pub fn line(&mut self, a: Vec3, b: Vec3, color: RenderColor) {
+    let color = [color.r, color.g, color.b, color.a];
+    self.vertices.push(RenderDebugVertex { position: a.as_array(), color });
+    self.vertices.push(RenderDebugVertex { position: b.as_array(), color });
+  }
+  /// circle outline on the xy plane
+  pub fn circle(&mut self, center: Vec3, radius: f32, color: RenderColor) {
+    let point = |i: u32| {
+      let a = 2.0 * PI * i as f32 / DEBUG_CIRCLE_SEGMENTS as f32;
+      center + Vec3::new(f32::cos(a) * radius, f32::sin(a) * radius, 0.0)
+    };
+    for i in 0..DEBUG_CIRCLE_SEGMENTS {
+      self.line(point(i), point(i + 1), color);
+    }
+  }
+  /// rect outline on the xy plane
+  pub fn rect(&mut self, center: Vec3, size: Vec2, color: RenderColor) {
+    let (hw, hh) = (size.x * 0.5, size.y * 0.5);
+    let corners = [
+      center + Vec3::new(-hw, -hh, 0.0),
+      center + Vec3::new(hw, -hh, 0.0),
+      center + Vec3::new(hw, hh, 0.0),
+      center + Vec3::new(-hw, hh, 0.0),
+    ];
+    for i in 0..4 {
+      self.line(corners[i], corners[(i + 1) % 4], color);
+    }
+  }
+  /// uploads shapes queued since the last flush and starts a new frame
+  pub fn flush(&mut self, device: &Device, queue: &Queue) {
+    if self.vertices.len() > self.capacity {
+      self.vertex_buffer.destroy();
+      self.capacity = self.vertices.len().next_power_of_two();
+      self.vertex_buffer = build_debug_vertex_buffer(device, self.capacity);
+    }
+    if !self.vertices.is_empty() {
+      queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&self.vertices));
+    }
+    self.drawn = self.vertices.len() as u32;
+    self.vertices.clear();
+  }
+  pub fn render(&self, pass: &mut RenderPass) {
+    if self.drawn == 0 { return; }
+    pass.set_pipeline(&self.pipeline);
+    pass.set_bind_group(0, &self.bind_group, &[]);
+    pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+    pass.draw(0..self.drawn, 0..1);
+  }
+  pub fn destroy(&mut self) {
+    self.camera_buffer.destroy();
+    self.vertex_buffer.destroy();
+  }
+}
+
+fn build_debug_vertex_buffer(device: &Device, capacity: usize) -> Buffer {
+  device.create_buffer(&BufferDescriptor {
+    label: Some("debug-vertex-buffer"),
+    size: (std::mem::size_of::<RenderDebugVertex>() * capacity) as u64,
+    usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+    mapped_at_creation: false,
+  })
+}
\ No newline at end of file
@@ -7,4 +7,22 @@ pub use primitives::*;
 mod obj_pipeline;
 pub use obj_pipeline::*;
 mod text_engine;
-pub use text_engine::*;
\ No newline at end of file
+pub use text_engine::*;
+mod sdf3d_pipeline;
+pub use sdf3d_pipeline::*;
+mod sprite_batch;
+pub use sprite_batch::*;
+mod particles;
+pub use particles::*;
+mod debug_draw;
+pub use debug_draw::*;
+mod post_process;
+pub use post_process::*;
+mod canvas;
+pub use canvas::*;
+mod animation;
+pub use animation::*;
+mod screen_pass;
+pub use screen_pass::*;
+mod camera2d;
+pub use camera2d::*;
\ No newline at end of file
@@ -1,23 +1,129 @@
 use wgpu::{
-  vertex_attr_array, BindGroupLayout, BlendComponent, BlendFactor, BlendOperation, BlendState, Buffer, BufferAddress, BufferDescriptor, BufferUsages, ColorTargetState, ColorWrites, CompareFunction, DepthBiasState, DepthStencilState, Device, Face, FragmentState, IndexFormat, MultisampleState, PipelineCompilationOptions, PipelineLayoutDescriptor, PolygonMode, Queue, RenderPass, RenderPipeline, RenderPipelineDescriptor, StencilState, Texture, TextureFormat, VertexBufferLayout, VertexState, VertexStepMode
+  vertex_attr_array, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, BufferBindingType, BufferBinding, BlendComponent, BlendFactor, BlendOperation, BlendState, Buffer, BufferAddress, BufferDescriptor, BufferSize, BufferUsages, ColorTargetState, ColorWrites, CommandEncoder, CommandEncoderDescriptor, CompareFunction, DepthBiasState, DepthStencilState, Device, Extent3d, Face, Features, FragmentState, IndexFormat, LoadOp, Maintain, MapMode, MultisampleState, Operations, PipelineCompilationOptions, PipelineLayoutDescriptor, Origin3d, PolygonMode, PrimitiveState, PrimitiveTopology, Queue, RenderPass, RenderPassColorAttachment, RenderPassDepthStencilAttachment, RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor, ShaderModuleDescriptor, ShaderSource, ShaderStages, StencilState, StoreOp, TexelCopyBufferInfo, TexelCopyBufferLayout, TexelCopyTextureInfo, Texture, TextureAspect, TextureDescriptor, TextureDimension, TextureFormat, TextureFormatFeatureFlags, TextureUsages, TextureView, TextureViewDescriptor, VertexBufferLayout, VertexState, VertexStepMode
 };
 
+use std::cell::Cell;
+use std::collections::HashMap;
+use wgpu::util::StagingBelt;
+
+use crate::utils::{Mat4, Vec2};
 use super::{
-  build_default_bind_group, build_default_bind_group_layout, build_primitive_state,
-  build_shader_module, create_mvp, RenderObject, RenderObjectSetup,
-  RenderObjectUpdate, RenderVertex, ShaderType
+  bounding_sphere, build_default_bind_group, build_default_bind_group_layout, build_depth_map, build_light_buffer,
+  build_primitive_state, build_shader_module, build_shadow_bind_group, build_shadow_bind_group_layout, camera_matrices,
+  create_mvp, pack_joint_transforms, RenderCamera, RenderShadowC, sphere_in_frustum, Primitives, RenderObject, RenderObjectSetup,
+  RenderBlendMode, RenderColor, RenderLight, RenderLoadOp, RenderLightsC, RenderObjectUpdate, RenderPipelineSetup, RenderTransform, RenderVertex, ShaderType
 };
 
 #[derive(Debug)]
 pub struct ObjPipeline {
   pub pipeline: RenderPipeline,
   pub objects: Vec<RenderObject>,
+  // object indices sorted by RenderObject::order
+  draw_order: Vec<usize>,
+  // hides every object without touching their own visibility
+  visible: bool,
+  // for validating index counts
+  topology: PrimitiveTopology,
+  // kept for building the wireframe pipeline later
+  target_formats: Vec<TextureFormat>,
+  use_depth: bool,
+  blend: RenderBlendMode,
+  wireframe_pipeline: Option<RenderPipeline>,
+  // wireframe objects drawn before enable_wireframe, only reported once
+  wireframe_warned: Cell<bool>,
+  light_buffer: Buffer,
+  culling: bool,
+  target_size: (u32, u32),
+  // scissored objects drawn before resize was called, only reported once
+  scissor_warned: Cell<bool>,
+  // picking pipeline + its object id layout (group 1)
+  pick_pipeline: Option<(RenderPipeline, BindGroupLayout)>,
+  use_tangents: bool,
+  // extra per-vertex color buffer for ShaderType::VertexColor
+  vertex_colors: bool,
+  // sampler anisotropy for textures added after set_anisotropy, 1 = off
+  anisotropy: u16,
+  // texture 1 bound as texture_2d_array
+  texture_array: bool,
+  // uniform writes collected during a frame, see set_batched_uploads
+  upload_belt: Option<StagingBelt>,
+  pending_uploads: Vec<(usize, usize, Vec<u8>)>,
+  // shadow casting + receiving
+  depth_pipeline: Option<RenderPipeline>,
+  shadow_layout: Option<BindGroupLayout>,
+  shadow_group: Option<BindGroup>,
+  shadow_buffer: Option<Buffer>,
+  // group 1 uniform buffers for custom shaders, see RenderPipelineSetup::custom_uniforms
+  custom_layout: Option<BindGroupLayout>,
+  custom_uniforms: u32,
+  custom_uniform_size: u64,
 }
 impl ObjPipeline {
   pub fn new(device: &Device, target_format: TextureFormat, shader_type: ShaderType, use_depth: bool) -> Self {
+    Self::new_multi_target(device, &[target_format], shader_type, use_depth)
+  }
+  /// pipeline writing to multiple color targets at once,
+  /// fragment shader should return a struct with one @location per target
+  pub fn new_multi_target(device: &Device, target_formats: &[TextureFormat], shader_type: ShaderType, use_depth: bool) -> Self {
+    Self::new_with_setup(device, target_formats, shader_type, use_depth, RenderPipelineSetup::default())
+  }
+  /// pipeline with a non-default blend mode,
+  /// e.g. PremultipliedAlpha for textures from TextEngine::set_premultiply
+  pub fn new_with_blend(device: &Device, target_format: TextureFormat, shader_type: ShaderType, use_depth: bool, blend: RenderBlendMode) -> Self {
+    let setup = RenderPipelineSetup { blend, ..Default::default() };
+    Self::new_with_setup(device, &[target_format], shader_type, use_depth, setup)
+  }
+  /// alpha_to_coverage false for straight alpha textures (text, overlays),
+  /// whose soft edges can come out dithered with it on
+  pub fn new_with_alpha_to_coverage(device: &Device, target_format: TextureFormat, shader_type: ShaderType, use_depth: bool, alpha_to_coverage: bool) -> Self {
+    let setup = RenderPipelineSetup { alpha_to_coverage, ..Default::default() };
+    Self::new_with_setup(device, &[target_format], shader_type, use_depth, setup)
+  }
+  /// pipeline drawing vertex pairs as lines (e.g. Primitives::grid)
+  pub fn new_line_list(device: &Device, target_format: TextureFormat, shader_type: ShaderType, use_depth: bool) -> Self {
+    let setup = RenderPipelineSetup { topology: PrimitiveTopology::LineList, ..Default::default() };
+    Self::new_with_setup(device, &[target_format], shader_type, use_depth, setup)
+  }
+  /// pipeline with an extra per-vertex tangent buffer at @location(3) (vec4f, w = handedness),
+  /// for normal mapped custom shaders
+  pub fn new_with_tangents(device: &Device, target_format: TextureFormat, shader_type: ShaderType, use_depth: bool) -> Self {
+    let setup = RenderPipelineSetup { tangents: true, ..Default::default() };
+    Self::new_with_setup(device, &[target_format], shader_type, use_depth, setup)
+  }
+  /// pipeline with any combination of blend mode, alpha to coverage, tangents + topology,
+  /// e.g. premultiplied text without alpha to coverage
+  pub fn new_with_setup(
+    device: &Device,
+    target_formats: &[TextureFormat],
+    shader_type: ShaderType,
+    use_depth: bool,
+    setup: RenderPipelineSetup
+  ) -> Self {
+    let RenderPipelineSetup { blend, alpha_to_coverage, tangents: use_tangents, topology, .. } = setup;
+    let receives_shadows = matches!(shader_type, ShaderType::Shadowed);
+    // built-in shaders have no custom uniforms
+    let custom_uniforms = match shader_type {
+      ShaderType::Custom(_) => setup.custom_uniforms,
+      _ if setup.custom_uniforms > 0 => {
+        println!("ERR: Custom uniforms require ShaderType::Custom, ignoring");
+        0
+      }
+      _ => 0
+    };
+    let texture_array = matches!(shader_type, ShaderType::TextureArray);
+    let vertex_colors = matches!(shader_type, ShaderType::VertexColor);
+    // both extra buffers would claim @location(3)
+    let use_tangents = if use_tangents && vertex_colors {
+      println!("ERR: Tangents are not supported with ShaderType::VertexColor, ignoring");
+      false
+    } else { use_tangents };
     let shader_mod = build_shader_module(device, shader_type);
-    let bind_group0_layout = build_default_bind_group_layout(device);
-    let bind_group_container: Vec<&BindGroupLayout> = vec![&bind_group0_layout];
+    let bind_group0_layout = build_default_bind_group_layout(device, texture_array);
+    let shadow_layout = build_shadow_bind_group_layout(device);
+    let custom_layout = build_custom_bind_group_layout(device, custom_uniforms);
+    let mut bind_group_container: Vec<&BindGroupLayout> = vec![&bind_group0_layout];
+    if receives_shadows { bind_group_container.push(&shadow_layout); }
+    if custom_uniforms > 0 { bind_group_container.push(&custom_layout); }
 
     let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
       label: Some("pipeline-layout"),
@@ -31,6 +137,23 @@ impl ObjPipeline {
       step_mode: VertexStepMode::Vertex,
       attributes: &vertex_attr_static,
     };
+    let tangent_attr = vertex_attr_array![3 => Float32x4];
+    let tangent_layout = VertexBufferLayout {
+      array_stride: std::mem::size_of::<[f32; 4]>() as BufferAddress,
+      step_mode: VertexStepMode::Vertex,
+      attributes: &tangent_attr,
+    };
+    let color_attr = vertex_attr_array![3 => Float32x4];
+    let color_layout = VertexBufferLayout {
+      array_stride: std::mem::size_of::<[f32; 4]>() as BufferAddress,
+      step_mode: VertexStepMode::Vertex,
+      attributes: &color_attr,
+    };
+    let mut vertex_layouts = vec![vertex_layout];
+    if use_tangents { vertex_layouts.push(tangent_layout); }
+    if vertex_colors { vertex_layouts.push(color_layout); }
+
+    let targets = color_targets(target_formats, blend);
 
     let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
       label: Some("render-pipeline"),
@@ -38,34 +161,19 @@ impl ObjPipeline {
       vertex: VertexState {
         module: &shader_mod,
         entry_point: Some("vertex_main"),
-        buffers: &[vertex_layout],
+        buffers: &vertex_layouts,
         compilation_options: PipelineCompilationOptions::default(),
       },
       fragment: Some(FragmentState{
         module: &shader_mod,
         entry_point: Some("fragment_main"),
-        targets: &[Some(ColorTargetState{
-          format: target_format,
-          blend: Some(BlendState { 
-            color: BlendComponent {
-              operation: BlendOperation::Add,
-              src_factor: BlendFactor::SrcAlpha,
-              dst_factor: BlendFactor::OneMinusSrcAlpha
-            },
-            alpha: BlendComponent {
-              operation: BlendOperation::Add,
-              src_factor: BlendFactor::SrcAlpha,
-              dst_factor: BlendFactor::OneMinusSrcAlpha
-            }
-          }),
-          write_mask: ColorWrites::ALL
-        })],
+        targets: &targets,
         compilation_options: PipelineCompilationOptions::default(),
       }),
       multisample: MultisampleState {
         count: 1,
         mask: !0,
-        alpha_to_coverage_enabled: true,
+        alpha_to_coverage_enabled: alpha_to_coverage,
       },
       depth_stencil: if use_depth { 
         Some(DepthStencilState {
@@ -76,19 +184,115 @@ impl ObjPipeline {
           bias: DepthBiasState::default(),
         })
       } else { None },
-      primitive: build_primitive_state(Some(Face::Back), PolygonMode::Fill),
+      primitive: topology_primitive_state(device, topology),
       multiview: None,
       cache: None,
     });
 
+    // placeholder shadow map until set_shadow_map is called
+    let (shadow_group, shadow_buffer) = if receives_shadows {
+      let buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("shadow-uniform-buffer"),
+        size: std::mem::size_of::<RenderShadowC>() as u64,
+        usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+      });
+      let placeholder = build_depth_map(device, 1);
+      let group = build_shadow_bind_group(device, &shadow_layout, &placeholder, &buffer);
+      (Some(group), Some(buffer))
+    } else { (None, None) };
+
     Self {
       pipeline,
       objects: Vec::new(),
+      draw_order: Vec::new(),
+      visible: true,
+      topology,
+      target_formats: target_formats.to_vec(),
+      use_depth,
+      blend,
+      wireframe_pipeline: None,
+      wireframe_warned: Cell::new(false),
+      light_buffer: build_light_buffer(device),
+      culling: false,
+      target_size: (0, 0),
+      scissor_warned: Cell::new(false),
+      pick_pipeline: None,
+      use_tangents,
+      vertex_colors,
+      anisotropy: 1,
+      texture_array,
+      upload_belt: None,
+      pending_uploads: Vec::new(),
+      depth_pipeline: None,
+      shadow_layout: if receives_shadows { Some(shadow_layout) } else { None },
+      shadow_group,
+      shadow_buffer,
+      custom_layout: if custom_uniforms > 0 { Some(custom_layout) } else { None },
+      custom_uniforms,
+      custom_uniform_size: setup.custom_uniform_size.max(16),
+    }
+  }
+  /// binds a depth map from render_depth_map for ShaderType::Shadowed pipelines
+  pub fn set_shadow_map(&mut self, device: &Device, queue: &Queue, shadow_map: &Texture, light_camera: &RenderCamera) {
+    let (Some(layout), Some(buffer)) = (&self.shadow_layout, &self.shadow_buffer) else {
+      println!("ERR: Shadow maps require a pipeline built with ShaderType::Shadowed");
+      return;
+    };
+    let (view, proj) = camera_matrices(light_camera);
+    let data = RenderShadowC { view, proj, params: [1.0, 0.002, 0.0, 0.0] };
+    queue.write_buffer(buffer, 0, bytemuck::bytes_of(&data));
+    self.shadow_group = Some(build_shadow_bind_group(device, layout, shadow_map, buffer));
+  }
+  /// size of render target, required for resetting scissor rects
+  pub fn resize(&mut self, width: u32, height: u32) {
+    self.target_size = (width, height);
+  }
+  /// uploads lights shared by all objects in the pipeline
+  pub fn set_lights(&mut self, queue: &Queue, lights: &[RenderLight]) {
+    let data = RenderLightsC::from_lights(lights);
+    queue.write_buffer(&self.light_buffer, 0, bytemuck::bytes_of(&data));
+  }
+  /// skip drawing objects whose bounding sphere is outside the camera frustum
+  pub fn set_culling(&mut self, enabled: bool) {
+    self.culling = enabled;
+  }
+  /// collect update_object uniform writes and upload them together with flush_uploads,
+  /// instead of one queue write per buffer (for pipelines with many objects)
+  pub fn set_batched_uploads(&mut self, enabled: bool) {
+    self.upload_belt = if enabled { Some(StagingBelt::new(64 * 1024)) } else { None };
+    if !enabled { self.pending_uploads.clear(); }
+  }
+  /// records pending uniform writes into encoder, call before the render pass that draws this pipeline
+  pub fn flush_uploads(&mut self, device: &Device, encoder: &mut CommandEncoder) {
+    let Some(belt) = &mut self.upload_belt else { return; };
+    // previous frame's copies have been submitted by now
+    belt.recall();
+    for (idx, slot, bytes) in self.pending_uploads.drain(..) {
+      let (Some(obj), Some(size)) = (self.objects.get(idx), BufferSize::new(bytes.len() as u64)) else { continue; };
+      belt.write_buffer(encoder, &obj.buffers0[slot], 0, size, device).copy_from_slice(&bytes);
+    }
+    belt.finish();
+  }
+  /// anisotropic filtering (1-16) for textures of objects added or retextured afterwards,
+  /// sharpens textures viewed at grazing angles. pass GpuAccess::supports_anisotropy()
+  pub fn set_anisotropy(&mut self, anisotropy: u16, supported: bool) {
+    let anisotropy = anisotropy.clamp(1, 16);
+    if anisotropy > 1 && !supported {
+      println!("ERR: Anisotropic filtering is not supported by this device, using 1");
+      self.anisotropy = 1;
+      return;
     }
+    self.anisotropy = anisotropy;
   }
   pub fn add_object(&mut self, device: &Device, queue: &Queue, setup: RenderObjectSetup) -> usize {
     // create vertex buffer
     let vlen = setup.vertex_data.len();
+    #[cfg(debug_assertions)]
+    if let Some(e) = index_error(self.topology, vlen, &setup.indices) {
+      panic!("Invalid indices for object {} - {}", self.objects.len(), e);
+    }
+    let (bounding_center, bounding_radius) = bounding_sphere(&setup.vertex_data);
     let v_buffer = device.create_buffer(&BufferDescriptor {
       label: Some("vertex-buffer"),
       size: (std::mem::size_of::<RenderVertex>() * vlen) as u64,
@@ -111,8 +315,31 @@ impl ObjPipeline {
       index_buffer = Some(i_buffer);
     }
 
+    // create tangent buffer
+    let mut t_buffer: Option<Buffer> = None;
+    if self.use_tangents {
+      let tangents = if setup.tangents.len() == vlen { setup.tangents }
+        else { Primitives::compute_tangents(&setup.vertex_data, &setup.indices) };
+      t_buffer = Some(build_vec4_buffer(device, queue, "tangent-buffer", &tangents));
+    }
+
+    // create color buffer
+    let mut c_buffer: Option<Buffer> = None;
+    if self.vertex_colors {
+      let colors = if setup.colors.len() == vlen { setup.colors } else { vec![[1.0; 4]; vlen] };
+      c_buffer = Some(build_vec4_buffer(device, queue, "color-buffer", &colors));
+    }
+
     // create bind group 0
-    let (bind_group0, buffers0) = build_default_bind_group(device, &self.pipeline, &setup.texture1, &setup.texture2);
+    let (bind_group0, buffers0) = build_default_bind_group(
+      device, &self.pipeline, &self.light_buffer, &setup.texture1, &setup.texture2,
+      self.anisotropy, self.texture_array, setup.max_joints
+    );
+
+    // create zeroed custom uniform buffers + bind group 1
+    let custom_bind = self.custom_layout.as_ref().map(|layout| {
+      build_custom_bind_group(device, layout, self.custom_uniforms, self.custom_uniform_size)
+    });
 
     // save to cache
     let obj = RenderObject {
@@ -121,15 +348,29 @@ impl ObjPipeline {
       v_count: vlen,
       index_buffer,
       index_count: ilen as u32,
+      t_buffer,
+      c_buffer,
       instances: 1,
       bind_group0,
       buffers0,
       texture1: setup.texture1,
       texture2: setup.texture2,
       max_joints: setup.max_joints,
+      bounding_center,
+      bounding_radius,
+      in_view: true,
+      scissor: None,
+      order: 0,
+      wireframe: false,
+      model: Mat4::identity().as_col_major_array(),
+      shadow_bind: None,
+      custom_bind,
+      last_transform: None,
+      last_gen: None,
     };
     self.objects.push(obj);
     let idx = self.objects.len() - 1;
+    insert_draw_order(&mut self.draw_order, idx, |i| self.objects[i].order);
     self.update_object(idx, queue, RenderObjectUpdate {
       camera: setup.camera,
       ..Default::default()
@@ -137,29 +378,89 @@ impl ObjPipeline {
     idx
   }
   pub fn update_object(&mut self, idx: usize, queue: &Queue, update: RenderObjectUpdate) {
-    let mvp = create_mvp(&update);
+    if idx >= self.objects.len() {
+      println!("ERR: Tried to access an object that doesn't exist {}/{}", idx, self.objects.len());
+      return;
+    }
     let buf = update.gen_buf;
+    let batched = self.upload_belt.is_some();
     let obj = &mut self.objects[idx];
     obj.visible = update.visible;
+    obj.scissor = update.scissor;
+    obj.wireframe = update.wireframe;
+    let reorder = obj.order != update.order;
+    obj.order = update.order;
 
-    // let stride = self.limits.min_uniform_buffer_offset_alignment;
-    queue.write_buffer(&obj.buffers0[0], 0, bytemuck::cast_slice(&mvp));
-    queue.write_buffer(&obj.buffers0[1], 0, bytemuck::cast_slice(&buf.as_slice()));
+    // static objects skip rebuilding + uploading their mvp
+    let transform = RenderTransform::from(&update);
+    let mut mvp = None;
+    if update.force || obj.last_transform.as_ref() != Some(&transform) {
+      let m = create_mvp(&update);
+      obj.model.copy_from_slice(&m[0..16]);
+      obj.in_view = sphere_in_frustum(&m, obj.bounding_center, obj.bounding_radius, update.scale);
+      obj.last_transform = Some(transform);
+      mvp = Some(m);
+    }
+    let gen_changed = update.force || obj.last_gen != Some(buf);
+    if gen_changed { obj.last_gen = Some(buf); }
 
-    // merge animation matrices into single buffer
-    if obj.max_joints > 0 && update.anim_transforms.len() > 0 {
-      let mut anim_buffer: Vec<f32> = Vec::new();
-      for i in 0..obj.max_joints {
-        if i >= update.anim_transforms.len() {
-          break;
-        }
-        // merge [f32; 16] arrays into single anim_buffer
-        let a = update.anim_transforms[i];
-        anim_buffer.extend_from_slice(&a);
+    let uploads = object_uploads(mvp.as_ref(), gen_changed.then_some(&buf), obj.max_joints, &update.anim_transforms);
+    for (slot, bytes) in uploads {
+      if batched {
+        self.pending_uploads.push((idx, slot, bytes));
+      } else {
+        queue.write_buffer(&obj.buffers0[slot], 0, &bytes);
+      }
+    }
+    for (binding, bytes) in update.uniforms.iter().enumerate() {
+      if bytes.is_empty() { continue; }
+      let Some((_, buffers)) = &obj.custom_bind else {
+        println!("ERR: Uniforms require a pipeline with RenderPipelineSetup::custom_uniforms");
+        break;
+      };
+      match buffers.get(binding) {
+        Some(_) if bytes.len() as u64 > self.custom_uniform_size => println!(
+          "ERR: Uniform {} of object {} is {} bytes, larger than custom_uniform_size {}",
+          binding, idx, bytes.len(), self.custom_uniform_size
+        ),
+        Some(b) => queue.write_buffer(b, 0, bytes),
+        None => println!("ERR: Uniform binding {} is out of range for {} custom uniforms", binding, buffers.len()),
       }
-      queue.write_buffer(&obj.buffers0[1], 0, bytemuck::cast_slice(&anim_buffer));
+    }
+
+    if reorder {
+      sort_draw_order(&mut self.draw_order, |i| self.objects[i].order);
     }
   }
+  /// shows/hides an object without rebuilding its transforms,
+  /// the next update_object overrides it with RenderObjectUpdate::visible
+  pub fn set_visible(&mut self, idx: usize, visible: bool) {
+    match self.objects.get_mut(idx) {
+      Some(obj) => obj.visible = visible,
+      None => println!("ERR: Tried to access an object that doesn't exist {}/{}", idx, self.objects.len())
+    }
+  }
+  /// builds the line mode pipeline used by RenderObjectUpdate::with_wireframe,
+  /// requires a triangle list pipeline and Features::POLYGON_MODE_LINE
+  pub fn enable_wireframe(&mut self, device: &Device) {
+    if self.wireframe_pipeline.is_some() { return; }
+    if self.topology != PrimitiveTopology::TriangleList {
+      println!("ERR: Wireframes require a triangle list pipeline");
+      return;
+    }
+    if !device.features().contains(Features::POLYGON_MODE_LINE) {
+      println!("ERR: Wireframes are not supported by this device (POLYGON_MODE_LINE)");
+      return;
+    }
+    self.wireframe_pipeline = Some(Self::build_wireframe_pipeline(device, &self.target_formats, self.use_depth, self.texture_array, self.blend));
+  }
+  /// skips the whole pipeline in render, shadow and picking passes
+  pub fn set_pipeline_visible(&mut self, visible: bool) {
+    self.visible = visible;
+  }
+  pub fn is_pipeline_visible(&self) -> bool {
+    self.visible
+  }
   pub fn replace_texture(&mut self, device: &Device, object_idx: usize, slot: u8, texture: Texture) {
     if object_idx >= self.objects.len() {
       println!("ERR: Tried to access an object that doesn't exist {}/{}", object_idx, self.objects.len());
@@ -181,9 +482,15 @@ impl ObjPipeline {
       }
     }
     // replace bind group
-    let (new_bind_group, new_buffers) = build_default_bind_group(device, &self.pipeline, &obj.texture1, &obj.texture2);
+    let (new_bind_group, new_buffers) = build_default_bind_group(
+      device, &self.pipeline, &self.light_buffer, &obj.texture1, &obj.texture2,
+      self.anisotropy, self.texture_array, obj.max_joints
+    );
     obj.bind_group0 = new_bind_group;
     obj.buffers0 = new_buffers;
+    // new buffers start empty
+    obj.last_transform = None;
+    obj.last_gen = None;
   }
   pub fn replace_vertices(
     &mut self,
@@ -197,6 +504,10 @@ impl ObjPipeline {
       println!("ERR: Tried to access an object that doesn't exist {}/{}", object_idx, self.objects.len());
       return;
     }
+    #[cfg(debug_assertions)]
+    if let Some(e) = indices.as_ref().and_then(|i| index_error(self.topology, vertices.len(), i)) {
+      panic!("Invalid indices for object {} - {}", object_idx, e);
+    }
     let obj = &mut self.objects[object_idx];
     // create vertex buffer
     let vlen = vertices.len();
@@ -209,6 +520,9 @@ impl ObjPipeline {
     queue.write_buffer(&v_buffer, 0, bytemuck::cast_slice(&vertices));
     obj.v_buffer = v_buffer;
     obj.v_count = vlen;
+    (obj.bounding_center, obj.bounding_radius) = bounding_sphere(&vertices);
+    // recompute in_view on the next update
+    obj.last_transform = None;
 
     // create index buffer
     if let Some(idcs) = indices {
@@ -226,30 +540,814 @@ impl ObjPipeline {
       }
       obj.index_buffer = index_buffer;
       obj.index_count = ilen as u32;
+      if self.use_tangents {
+        if let Some(b) = &obj.t_buffer { b.destroy(); }
+        obj.t_buffer = Some(build_vec4_buffer(device, queue, "tangent-buffer", &Primitives::compute_tangents(&vertices, &idcs)));
+      }
+    } else if self.use_tangents {
+      if obj.index_buffer.is_some() {
+        println!("ERR: Tangents can't be recomputed for indexed objects without indices");
+      } else {
+        if let Some(b) = &obj.t_buffer { b.destroy(); }
+        obj.t_buffer = Some(build_vec4_buffer(device, queue, "tangent-buffer", &Primitives::compute_tangents(&vertices, &[])));
+      }
+    }
+    // stale colors no longer line up with the new vertices
+    let c_size = (std::mem::size_of::<[f32; 4]>() * vlen) as u64;
+    if self.vertex_colors && obj.c_buffer.as_ref().is_some_and(|b| b.size() != c_size) {
+      if let Some(b) = &obj.c_buffer { b.destroy(); }
+      obj.c_buffer = Some(build_vec4_buffer(device, queue, "color-buffer", &vec![[1.0; 4]; vlen]));
+    }
+  }
+  /// replaces per-vertex colors of a ShaderType::VertexColor object (one rgba per vertex)
+  pub fn replace_colors(&mut self, device: &Device, queue: &Queue, object_idx: usize, colors: &[[f32; 4]]) {
+    if object_idx >= self.objects.len() {
+      println!("ERR: Tried to access an object that doesn't exist {}/{}", object_idx, self.objects.len());
+      return;
+    }
+    if !self.vertex_colors {
+      println!("ERR: Vertex colors require a pipeline built with ShaderType::VertexColor");
+      return;
+    }
+    let obj = &mut self.objects[object_idx];
+    if colors.len() != obj.v_count {
+      println!("ERR: Expected {} vertex colors, got {}", obj.v_count, colors.len());
+      return;
+    }
+    match &obj.c_buffer {
+      Some(b) => queue.write_buffer(b, 0, bytemuck::cast_slice(colors)),
+      None => obj.c_buffer = Some(build_vec4_buffer(device, queue, "color-buffer", colors)),
     }
   }
   pub fn render(&self, pass: &mut RenderPass) {
+    if !self.visible { return; }
     pass.set_pipeline(&self.pipeline);
-    for i in 0..self.objects.len() {
+    for &i in &self.draw_order {
       let obj = &self.objects[i];
       if !obj.visible { continue; }
+      if self.culling && !obj.in_view { continue; }
+      let scissor = object_scissor(obj.scissor, self.target_size);
+      match scissor {
+        ObjScissor::Hidden => continue,
+        ObjScissor::Clip { rect: [x, y, w, h], .. } => pass.set_scissor_rect(x, y, w, h),
+        ObjScissor::Full => if obj.scissor.is_some() && !self.scissor_warned.replace(true) {
+          println!("ERR: Scissor rect requires pipeline target size (call resize), drawing without it");
+        }
+      }
       pass.set_vertex_buffer(0, obj.v_buffer.slice(..));
+      if let Some(t_buffer) = &obj.t_buffer {
+        pass.set_vertex_buffer(1, t_buffer.slice(..));
+      }
+      if let Some(c_buffer) = &obj.c_buffer {
+        pass.set_vertex_buffer(1, c_buffer.slice(..));
+      }
       pass.set_bind_group(0, &obj.bind_group0, &[]);
+      if let Some(g) = &self.shadow_group {
+        pass.set_bind_group(1, g, &[]);
+      }
+      if let Some((g, _)) = &obj.custom_bind {
+        pass.set_bind_group(1, g, &[]);
+      }
       if let Some(i_buffer) = &obj.index_buffer {
         pass.set_index_buffer(i_buffer.slice(..), IndexFormat::Uint32);
         pass.draw_indexed(0..obj.index_count, 0, 0..obj.instances);
       } else {
         pass.draw(0..(obj.v_count as u32), 0..obj.instances);
       }
+      // same geometry again as edges, index buffer + bind group are still set
+      match (obj.wireframe, &self.wireframe_pipeline) {
+        (true, Some(wp)) => {
+          pass.set_pipeline(wp);
+          if obj.index_buffer.is_some() {
+            pass.draw_indexed(0..obj.index_count, 0, 0..obj.instances);
+          } else {
+            pass.draw(0..(obj.v_count as u32), 0..obj.instances);
+          }
+          pass.set_pipeline(&self.pipeline);
+        }
+        (true, None) if !self.wireframe_warned.replace(true) => {
+          println!("ERR: Wireframe requires ObjPipeline::enable_wireframe, drawing without it");
+        }
+        _ => ()
+      }
+      // reset to full screen, only if this object was clipped
+      if let ObjScissor::Clip { reset: [x, y, w, h], .. } = scissor {
+        pass.set_scissor_rect(x, y, w, h);
+      }
+    }
+  }
+  fn build_depth_pipeline(device: &Device) -> RenderPipeline {
+    let shader_mod = device.create_shader_module(ShaderModuleDescriptor {
+      label: Some("shadow-depth-shader-module"),
+      source: ShaderSource::Wgsl(include_str!("shaders/shadow_depth.wgsl").into()),
+    });
+    let vertex_attr_static = vertex_attr_array![0 => Float32x3, 1 => Float32x2, 2 => Float32x3];
+    let vertex_layout = VertexBufferLayout {
+      array_stride: std::mem::size_of::<RenderVertex>() as BufferAddress,
+      step_mode: VertexStepMode::Vertex,
+      attributes: &vertex_attr_static,
+    };
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+      label: Some("shadow-depth-pipeline"),
+      layout: None,
+      vertex: VertexState {
+        module: &shader_mod,
+        entry_point: Some("vertex_main"),
+        buffers: &[vertex_layout],
+        compilation_options: PipelineCompilationOptions::default(),
+      },
+      fragment: None,
+      multisample: MultisampleState::default(),
+      depth_stencil: Some(DepthStencilState {
+        format: TextureFormat::Depth32Float,
+        depth_write_enabled: true,
+        depth_compare: CompareFunction::LessEqual,
+        stencil: StencilState::default(),
+        // reduce shadow acne
+        bias: DepthBiasState { constant: 2, slope_scale: 2.0, clamp: 0.0 },
+      }),
+      primitive: build_primitive_state(device, None, PolygonMode::Fill),
+      multiview: None,
+      cache: None,
+    })
+  }
+  /// draws visible objects into the current depth-only pass from the light's point of view
+  fn render_shadow_casters(&mut self, device: &Device, queue: &Queue, pass: &mut RenderPass, view: &[f32; 16], proj: &[f32; 16]) {
+    if !self.visible { return; }
+    if self.depth_pipeline.is_none() {
+      self.depth_pipeline = Some(Self::build_depth_pipeline(device));
+    }
+    let depth_pipeline = self.depth_pipeline.as_ref().unwrap();
+    pass.set_pipeline(depth_pipeline);
+    for obj in &mut self.objects {
+      if !obj.visible { continue; }
+      if obj.shadow_bind.is_none() {
+        let buffer = device.create_buffer(&BufferDescriptor {
+          label: Some("shadow-mvp-buffer"),
+          size: std::mem::size_of::<[f32; 48]>() as u64,
+          usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+          mapped_at_creation: false,
+        });
+        let group = device.create_bind_group(&BindGroupDescriptor {
+          label: Some("shadow-mvp-bind-group"),
+          layout: &depth_pipeline.get_bind_group_layout(0),
+          entries: &[BindGroupEntry {
+            binding: 0,
+            resource: BindingResource::Buffer(BufferBinding { buffer: &buffer, offset: 0, size: None })
+          }],
+        });
+        obj.shadow_bind = Some((group, buffer));
+      }
+      let (group, buffer) = obj.shadow_bind.as_ref().unwrap();
+      let mut mvp = [0.0; 48];
+      mvp[0..16].copy_from_slice(&obj.model);
+      mvp[16..32].copy_from_slice(view);
+      mvp[32..48].copy_from_slice(proj);
+      queue.write_buffer(buffer, 0, bytemuck::cast_slice(&mvp));
+
+      pass.set_vertex_buffer(0, obj.v_buffer.slice(..));
+      pass.set_bind_group(0, group, &[]);
+      if let Some(i_buffer) = &obj.index_buffer {
+        pass.set_index_buffer(i_buffer.slice(..), IndexFormat::Uint32);
+        pass.draw_indexed(0..obj.index_count, 0, 0..obj.instances);
+      } else {
+        pass.draw(0..(obj.v_count as u32), 0..obj.instances);
+      }
+    }
+  }
+  fn build_wireframe_pipeline(
+    device: &Device,
+    target_formats: &[TextureFormat],
+    use_depth: bool,
+    texture_array: bool,
+    blend: RenderBlendMode
+  ) -> RenderPipeline {
+    let shader_mod = device.create_shader_module(ShaderModuleDescriptor {
+      label: Some("wireframe-shader-module"),
+      source: ShaderSource::Wgsl(include_str!("shaders/wireframe.wgsl").into()),
+    });
+    // must match the object bind groups it reuses
+    let bind_group0_layout = build_default_bind_group_layout(device, texture_array);
+    let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+      label: Some("wireframe-pipeline-layout"),
+      bind_group_layouts: &[&bind_group0_layout],
+      push_constant_ranges: &[]
+    });
+    let vertex_attr_static = vertex_attr_array![0 => Float32x3, 1 => Float32x2, 2 => Float32x3];
+    let vertex_layout = VertexBufferLayout {
+      array_stride: std::mem::size_of::<RenderVertex>() as BufferAddress,
+      step_mode: VertexStepMode::Vertex,
+      attributes: &vertex_attr_static,
+    };
+    // same blending as the filled objects, edge colors are premultiplied to match
+    let targets = color_targets(target_formats, blend);
+    let constants = HashMap::from([
+      ("premultiplied".to_owned(), if blend == RenderBlendMode::PremultipliedAlpha { 1.0 } else { 0.0 })
+    ]);
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+      label: Some("wireframe-pipeline"),
+      layout: Some(&pipeline_layout),
+      vertex: VertexState {
+        module: &shader_mod,
+        entry_point: Some("vertex_main"),
+        buffers: &[vertex_layout],
+        compilation_options: PipelineCompilationOptions::default(),
+      },
+      fragment: Some(FragmentState {
+        module: &shader_mod,
+        entry_point: Some("fragment_main"),
+        targets: &targets,
+        compilation_options: PipelineCompilationOptions { constants: &constants, ..Default::default() },
+      }),
+      multisample: MultisampleState::default(),
+      depth_stencil: if use_depth {
+        Some(DepthStencilState {
+          format: TextureFormat::Depth24Plus,
+          depth_write_enabled: false,
+          depth_compare: CompareFunction::LessEqual,
+          stencil: StencilState::default(),
+          // pull edges towards the camera so they don't z-fight with the filled faces
+          bias: DepthBiasState { constant: -2, slope_scale: -1.0, clamp: 0.0 },
+        })
+      } else { None },
+      // polygon mode keeps triangle topology, unlike build_primitive_state's line mode
+      primitive: PrimitiveState {
+        cull_mode: Some(Face::Back),
+        polygon_mode: PolygonMode::Line,
+        ..PrimitiveState::default()
+      },
+      multiview: None,
+      cache: None,
+    })
+  }
+  fn build_pick_pipeline(device: &Device, texture_array: bool, topology: PrimitiveTopology) -> (RenderPipeline, BindGroupLayout) {
+    let shader_mod = device.create_shader_module(ShaderModuleDescriptor {
+      label: Some("picking-shader-module"),
+      source: ShaderSource::Wgsl(include_str!("shaders/picking.wgsl").into()),
+    });
+    // must match the object bind groups it reuses
+    let bind_group0_layout = build_default_bind_group_layout(device, texture_array);
+    // one id slot per object, selected with a dynamic offset
+    let id_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+      label: Some("picking-id-bind-group-layout"),
+      entries: &[BindGroupLayoutEntry {
+        binding: 0,
+        visibility: ShaderStages::VERTEX,
+        ty: BindingType::Buffer {
+          ty: BufferBindingType::Uniform,
+          has_dynamic_offset: true,
+          min_binding_size: BufferSize::new(PICK_ID_SIZE),
+        },
+        count: None,
+      }],
+    });
+    let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+      label: Some("picking-pipeline-layout"),
+      bind_group_layouts: &[&bind_group0_layout, &id_layout],
+      push_constant_ranges: &[]
+    });
+    let vertex_attr_static = vertex_attr_array![0 => Float32x3, 1 => Float32x2, 2 => Float32x3];
+    let vertex_layout = VertexBufferLayout {
+      array_stride: std::mem::size_of::<RenderVertex>() as BufferAddress,
+      step_mode: VertexStepMode::Vertex,
+      attributes: &vertex_attr_static,
+    };
+    let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+      label: Some("picking-pipeline"),
+      layout: Some(&pipeline_layout),
+      vertex: VertexState {
+        module: &shader_mod,
+        entry_point: Some("vertex_main"),
+        buffers: &[vertex_layout],
+        compilation_options: PipelineCompilationOptions::default(),
+      },
+      fragment: Some(FragmentState {
+        module: &shader_mod,
+        entry_point: Some("fragment_main"),
+        targets: &[Some(ColorTargetState {
+          format: TextureFormat::R32Uint,
+          blend: None,
+          write_mask: ColorWrites::ALL
+        })],
+        compilation_options: PipelineCompilationOptions::default(),
+      }),
+      multisample: MultisampleState::default(),
+      depth_stencil: Some(DepthStencilState {
+        format: TextureFormat::Depth24Plus,
+        depth_write_enabled: true,
+        depth_compare: CompareFunction::LessEqual,
+        stencil: StencilState::default(),
+        bias: DepthBiasState::default(),
+      }),
+      // same rasterization as the visible pipeline, e.g. line lists stay lines
+      primitive: topology_primitive_state(device, topology),
+      multiview: None,
+      cache: None,
+    });
+    (pipeline, id_layout)
+  }
+  /// renders object indices to an offscreen texture and reads back the pixel under screen_pos
+  pub fn pick_object_at(&mut self, device: &Device, queue: &Queue, screen_pos: Vec2, screen_size: Vec2) -> Option<usize> {
+    let width = screen_size.x as u32;
+    let height = screen_size.y as u32;
+    if width == 0 || height == 0 || !self.visible { return None; }
+    if screen_pos.x < 0.0 || screen_pos.y < 0.0 { return None; }
+    let px = screen_pos.x as u32;
+    let py = screen_pos.y as u32;
+    if px >= width || py >= height { return None; }
+    if self.pick_pipeline.is_none() {
+      self.pick_pipeline = Some(Self::build_pick_pipeline(device, self.texture_array, self.topology));
+    }
+    let (pick_pipeline, id_layout) = self.pick_pipeline.as_ref().unwrap();
+
+    // object index + 1 for every object, at uniform offset alignment
+    let stride = device.limits().min_uniform_buffer_offset_alignment as usize;
+    let mut ids = vec![0u8; stride * self.objects.len().max(1)];
+    for i in 0..self.objects.len() {
+      ids[i * stride..i * stride + 4].copy_from_slice(&(i as u32 + 1).to_ne_bytes());
+    }
+    let id_buffer = device.create_buffer(&BufferDescriptor {
+      label: Some("picking-id-buffer"),
+      size: ids.len() as u64,
+      usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+      mapped_at_creation: false
+    });
+    queue.write_buffer(&id_buffer, 0, &ids);
+    let id_group = device.create_bind_group(&BindGroupDescriptor {
+      label: Some("picking-id-bind-group"),
+      layout: id_layout,
+      entries: &[BindGroupEntry {
+        binding: 0,
+        resource: BindingResource::Buffer(BufferBinding { buffer: &id_buffer, offset: 0, size: BufferSize::new(PICK_ID_SIZE) })
+      }],
+    });
+
+    // offscreen targets
+    let size = Extent3d { width, height, depth_or_array_layers: 1 };
+    let id_texture = device.create_texture(&TextureDescriptor {
+      label: Some("picking-texture"),
+      size,
+      sample_count: 1,
+      mip_level_count: 1,
+      dimension: TextureDimension::D2,
+      format: TextureFormat::R32Uint,
+      usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+      view_formats: &[]
+    });
+    let depth_texture = device.create_texture(&TextureDescriptor {
+      label: Some("picking-depth-texture"),
+      size,
+      sample_count: 1,
+      mip_level_count: 1,
+      dimension: TextureDimension::D2,
+      format: TextureFormat::Depth24Plus,
+      usage: TextureUsages::RENDER_ATTACHMENT,
+      view_formats: &[]
+    });
+    let id_view = id_texture.create_view(&TextureViewDescriptor::default());
+    let depth_view = depth_texture.create_view(&TextureViewDescriptor::default());
+    // bytes_per_row must be aligned to 256
+    let readback = device.create_buffer(&BufferDescriptor {
+      label: Some("picking-readback-buffer"),
+      size: 256,
+      usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+      mapped_at_creation: false
+    });
+
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor { label: Some("picking-encoder") });
+    {
+      let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+        label: Some("picking-render"),
+        color_attachments: &[Some(RenderPassColorAttachment {
+          view: &id_view,
+          resolve_target: None,
+          ops: Operations {
+            load: LoadOp::Clear(wgpu::Color::TRANSPARENT),
+            store: StoreOp::Store
+          }
+        })],
+        depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+          view: &depth_view,
+          depth_ops: Some(Operations {
+            load: LoadOp::Clear(1.0),
+            store: StoreOp::Store
+          }),
+          stencil_ops: None,
+        }),
+        ..Default::default()
+      });
+      pass.set_pipeline(pick_pipeline);
+      for &i in &self.draw_order {
+        let obj = &self.objects[i];
+        if !obj.visible { continue; }
+        if self.culling && !obj.in_view { continue; }
+        // clipped away parts are not pickable
+        if let Some(rect) = obj.scissor {
+          let Some([x, y, w, h]) = clamp_scissor(rect, width, height) else { continue; };
+          pass.set_scissor_rect(x, y, w, h);
+        }
+        pass.set_vertex_buffer(0, obj.v_buffer.slice(..));
+        pass.set_bind_group(0, &obj.bind_group0, &[]);
+        pass.set_bind_group(1, &id_group, &[(i * stride) as u32]);
+        if let Some(i_buffer) = &obj.index_buffer {
+          pass.set_index_buffer(i_buffer.slice(..), IndexFormat::Uint32);
+          pass.draw_indexed(0..obj.index_count, 0, 0..obj.instances);
+        } else {
+          pass.draw(0..(obj.v_count as u32), 0..obj.instances);
+        }
+        if obj.scissor.is_some() {
+          pass.set_scissor_rect(0, 0, width, height);
+        }
+      }
     }
+    encoder.copy_texture_to_buffer(
+      TexelCopyTextureInfo {
+        texture: &id_texture,
+        mip_level: 0,
+        origin: Origin3d { x: px, y: py, z: 0 },
+        aspect: TextureAspect::All,
+      },
+      TexelCopyBufferInfo {
+        buffer: &readback,
+        layout: TexelCopyBufferLayout {
+          offset: 0,
+          bytes_per_row: Some(256),
+          rows_per_image: Some(1),
+        },
+      },
+      Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    // wait for gpu to finish before reading
+    let slice = readback.slice(..);
+    slice.map_async(MapMode::Read, |_| ());
+    let _ = device.poll(Maintain::Wait);
+    let id = {
+      let data = slice.get_mapped_range();
+      u32::from_ne_bytes([data[0], data[1], data[2], data[3]])
+    };
+    readback.unmap();
+    readback.destroy();
+    id_buffer.destroy();
+    id_texture.destroy();
+    depth_texture.destroy();
+
+    if id == 0 { None } else { Some(id as usize - 1) }
   }
   pub fn destroy(&mut self) {
     for i in 0..self.objects.len() {
       self.objects[i].v_buffer.destroy();
       if let Some(b) = &self.objects[i].index_buffer { b.destroy(); }
+      if let Some(b) = &self.objects[i].t_buffer { b.destroy(); }
+      if let Some(b) = &self.objects[i].c_buffer { b.destroy(); }
+      if let Some((_, b)) = &self.objects[i].shadow_bind { b.destroy(); }
+      if let Some((_, bs)) = &self.objects[i].custom_bind {
+        for b in bs { b.destroy(); }
+      }
       if let Some(tx) = &self.objects[i].texture1 { tx.destroy(); }
       if let Some(tx) = &self.objects[i].texture2 { tx.destroy(); }
       for b in &self.objects[i].buffers0 { b.destroy(); }
     }
+    self.light_buffer.destroy();
+    if let Some(b) = &self.shadow_buffer { b.destroy(); }
+  }
+}
+
+// vec4<u32> object id read by picking.wgsl
+const PICK_ID_SIZE: u64 = 16;
+
+// n uniform buffers at bindings 0..n, visible to both stages
+fn build_custom_bind_group_layout(device: &Device, count: u32) -> BindGroupLayout {
+  let entries: Vec<BindGroupLayoutEntry> = (0..count).map(|binding| BindGroupLayoutEntry {
+    binding,
+    visibility: ShaderStages::VERTEX_FRAGMENT,
+    ty: BindingType::Buffer {
+      ty: BufferBindingType::Uniform,
+      has_dynamic_offset: false,
+      min_binding_size: None,
+    },
+    count: None,
+  }).collect();
+  device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+    label: Some("custom-uniform-bind-group-layout"),
+    entries: &entries,
+  })
+}
+
+// count zeroed buffers of size bytes, matching build_custom_bind_group_layout
+fn build_custom_bind_group(device: &Device, layout: &BindGroupLayout, count: u32, size: u64) -> (BindGroup, Vec<Buffer>) {
+  let buffers: Vec<Buffer> = (0..count).map(|_| device.create_buffer(&BufferDescriptor {
+    label: Some("custom-uniform-buffer"),
+    size,
+    usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+    mapped_at_creation: false
+  })).collect();
+  let entries: Vec<BindGroupEntry> = buffers.iter().enumerate().map(|(i, b)| BindGroupEntry {
+    binding: i as u32,
+    resource: b.as_entire_binding(),
+  }).collect();
+  let group = device.create_bind_group(&BindGroupDescriptor {
+    label: Some("custom-uniform-bind-group"),
+    layout,
+    entries: &entries,
+  });
+  (group, buffers)
+}
+
+// one color target per format with the pipeline's blend mode,
+// integer formats cannot be blended
+fn color_targets(target_formats: &[TextureFormat], blend: RenderBlendMode) -> Vec<Option<ColorTargetState>> {
+  // premultiplied colors already carry their alpha
+  let src_factor = match blend {
+    RenderBlendMode::AlphaBlend => BlendFactor::SrcAlpha,
+    RenderBlendMode::PremultipliedAlpha => BlendFactor::One,
+  };
+  target_formats.iter().map(|format| {
+    let blendable = format.guaranteed_format_features(Features::empty())
+      .flags.contains(TextureFormatFeatureFlags::BLENDABLE);
+    Some(ColorTargetState {
+      format: *format,
+      blend: if blendable {
+        Some(BlendState {
+          color: BlendComponent {
+            operation: BlendOperation::Add,
+            src_factor,
+            dst_factor: BlendFactor::OneMinusSrcAlpha
+          },
+          alpha: BlendComponent {
+            operation: BlendOperation::Add,
+            src_factor,
+            dst_factor: BlendFactor::OneMinusSrcAlpha
+          }
+        })
+      } else { None },
+      write_mask: ColorWrites::ALL
+    })
+  }).collect()
+}
+
+// triangle lists are back-face culled, lines have no facing
+// and non-fill polygon modes need extra device features
+fn topology_primitive_state(device: &Device, topology: PrimitiveTopology) -> PrimitiveState {
+  match topology {
+    PrimitiveTopology::TriangleList => build_primitive_state(device, Some(Face::Back), PolygonMode::Fill),
+    _ => PrimitiveState { topology, ..PrimitiveState::default() }
+  }
+}
+
+// lower order draws first, ties in insertion (object index) order
+fn sort_draw_order(draw_order: &mut [usize], order: impl Fn(usize) -> i32) {
+  draw_order.sort_by_key(|&i| (order(i), i));
+}
+
+// new objects have the highest index, so they go after every object of the same or lower order
+fn insert_draw_order(draw_order: &mut Vec<usize>, idx: usize, order: impl Fn(usize) -> i32) {
+  let pos = draw_order.partition_point(|&i| order(i) <= order(idx));
+  draw_order.insert(pos, idx);
+}
+
+// how render clips one object
+#[derive(Debug, PartialEq)]
+enum ObjScissor {
+  // no scissor, or no target size to clip against (drawn unclipped)
+  Full,
+  // scissor rect + full target rect to restore after drawing
+  Clip { rect: [u32; 4], reset: [u32; 4] },
+  // scissor rect is outside the target
+  Hidden,
+}
+
+fn object_scissor(scissor: Option<[u32; 4]>, target_size: (u32, u32)) -> ObjScissor {
+  let (tw, th) = target_size;
+  match scissor {
+    Some(_) if tw == 0 || th == 0 => ObjScissor::Full,
+    Some(rect) => match clamp_scissor(rect, tw, th) {
+      Some(rect) => ObjScissor::Clip { rect, reset: [0, 0, tw, th] },
+      None => ObjScissor::Hidden,
+    },
+    None => ObjScissor::Full,
+  }
+}
+
+// scissor rect [x, y, w, h] contained within a target_w x target_h target, None if nothing is left
+fn clamp_scissor(rect: [u32; 4], target_w: u32, target_h: u32) -> Option<[u32; 4]> {
+  let [x, y, w, h] = rect;
+  let x0 = u32::min(x, target_w);
+  let y0 = u32::min(y, target_h);
+  let w0 = u32::min(w, target_w - x0);
+  let h0 = u32::min(h, target_h - y0);
+  if w0 == 0 || h0 == 0 { None } else { Some([x0, y0, w0, h0]) }
+}
+
+// buffers0 slots written by update_object
+const MVP_SLOT: usize = 0;
+const GEN_SLOT: usize = 1;
+const JOINTS_SLOT: usize = 2;
+
+// (buffers0 slot, bytes) to upload for an update, None skips an unchanged mvp/gen buffer.
+// animation matrices go to their own joints buffer so they never overwrite the gen data
+fn object_uploads(mvp: Option<&[f32; 48]>, gen_buf: Option<&[f32; 64]>, max_joints: usize, anim: &[[f32; 16]]) -> Vec<(usize, Vec<u8>)> {
+  let mut uploads = Vec::new();
+  if let Some(m) = mvp {
+    uploads.push((MVP_SLOT, bytemuck::cast_slice(m.as_slice()).to_vec()));
+  }
+  if let Some(g) = gen_buf {
+    uploads.push((GEN_SLOT, bytemuck::cast_slice(g.as_slice()).to_vec()));
   }
-}
\ No newline at end of file
+  if max_joints > 0 && !anim.is_empty() {
+    uploads.push((JOINTS_SLOT, bytemuck::cast_slice(&pack_joint_transforms(max_joints, anim)).to_vec()));
+  }
+  uploads
+}
+
+// out of range indices or a count that doesn't fit the topology,
+// checked in debug builds since the gpu error would not say which object
+fn index_error(topology: PrimitiveTopology, vertex_count: usize, indices: &[u32]) -> Option<String> {
+  if let Some((i, v)) = indices.iter().enumerate().find(|(_, v)| **v as usize >= vertex_count) {
+    return Some(format!("index {} at position {} is out of range for {} vertices", v, i, vertex_count));
+  }
+  let per_prim = match topology {
+    PrimitiveTopology::TriangleList => 3,
+    PrimitiveTopology::LineList => 2,
+    _ => 1
+  };
+  if !indices.len().is_multiple_of(per_prim) {
+    return Some(format!("{} indices is not a multiple of {} for {:?}", indices.len(), per_prim, topology));
+  }
+  None
+}
+
+/// renders objects of the given pipelines into a size x size Depth32Float shadow map
+/// as seen from light_camera (pass the result to set_shadow_map)
+pub fn render_depth_map(
+  device: &Device,
+  queue: &Queue,
+  pipelines: &mut [&mut ObjPipeline],
+  light_camera: &RenderCamera,
+  size: u32
+) -> Texture {
+  let depth_map = build_depth_map(device, size);
+  let depth_view = depth_map.create_view(&TextureViewDescriptor::default());
+  let (view, proj) = camera_matrices(light_camera);
+  let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor { label: Some("shadow-encoder") });
+  {
+    let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+      label: Some("shadow-render"),
+      color_attachments: &[],
+      depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+        view: &depth_view,
+        depth_ops: Some(Operations {
+          load: LoadOp::Clear(1.0),
+          store: StoreOp::Store
+        }),
+        stencil_ops: None,
+      }),
+      timestamp_writes: None,
+      occlusion_query_set: None,
+    });
+    for p in pipelines.iter_mut() {
+      p.render_shadow_casters(device, queue, &mut pass, &view, &proj);
+    }
+  }
+  queue.submit(std::iter::once(encoder.finish()));
+  depth_map
+}
+
+// extra vec4f vertex attribute buffer (tangents, colors)
+fn build_vec4_buffer(device: &Device, queue: &Queue, label: &str, data: &[[f32; 4]]) -> Buffer {
+  let buffer = device.create_buffer(&BufferDescriptor {
+    label: Some(label),
+    size: std::mem::size_of_val(data) as u64,
+    usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+    mapped_at_creation: false
+  });
+  queue.write_buffer(&buffer, 0, bytemuck::cast_slice(data));
+  buffer
+}
+
+/// renders pipelines into multiple color targets in a single pass,
+/// targets without a matching load op are cleared to transparent
+pub fn render_to_textures(
+  encoder: &mut CommandEncoder,
+  pipelines: &[&ObjPipeline],
+  targets: &[&TextureView],
+  load_ops: &[RenderLoadOp<RenderColor>],
+  depth_target: Option<(&TextureView, RenderLoadOp<f32>)>,
+) {
+  let color_attachments: Vec<Option<RenderPassColorAttachment>> = targets.iter().enumerate().map(|(i, view)| {
+    let load = load_ops.get(i).copied().unwrap_or(RenderLoadOp::Clear(RenderColor::TRANSPARENT));
+    Some(RenderPassColorAttachment {
+      view,
+      resolve_target: None,
+      ops: Operations {
+        load: load.into(),
+        store: StoreOp::Store
+      }
+    })
+  }).collect();
+  let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+    label: Some("multi-target-render"),
+    color_attachments: &color_attachments,
+    depth_stencil_attachment: depth_target.map(|(view, load)| RenderPassDepthStencilAttachment {
+      view,
+      depth_ops: Some(Operations {
+        load: load.into(),
+        store: StoreOp::Store
+      }),
+      stencil_ops: None,
+    }),
+    ..Default::default()
+  });
+  for p in pipelines {
+    p.render(&mut pass);
+  }
+}
+
+/// renders pipelines onto the screen surface from GpuAccess::begin_render,
+/// RenderLoadOp::Load keeps the last frame (e.g. for trails) at some performance cost
+pub fn render_to_screen(
+  encoder: &mut CommandEncoder,
+  pipelines: &[&ObjPipeline],
+  surface: &wgpu::SurfaceTexture,
+  load: RenderLoadOp<RenderColor>,
+) {
+  let target = surface.texture.create_view(&TextureViewDescriptor::default());
+  render_to_textures(encoder, pipelines, &[&target], &[load], None);
+}
+
+#[cfg(test)]
+mod obj_pipeline_tests {
+  use super::*;
+
+  #[test]
+  fn index_validation() {
+    let (verts, indices) = Primitives::rect_indexed(10.0, 10.0, 0.0);
+    assert_eq!(index_error(PrimitiveTopology::TriangleList, verts.len(), &indices), None);
+    let err = index_error(PrimitiveTopology::TriangleList, verts.len(), &[0, 1, 4]).unwrap();
+    assert!(err.contains("index 4 at position 2"));
+    assert!(index_error(PrimitiveTopology::TriangleList, verts.len(), &[0, 1, 2, 3]).is_some());
+    assert_eq!(index_error(PrimitiveTopology::LineList, verts.len(), &[0, 1, 2, 3]), None);
+  }
+
+  #[test]
+  fn scissor_clamped_to_target() {
+    assert_eq!(clamp_scissor([10, 20, 30, 40], 100, 100), Some([10, 20, 30, 40]));
+    assert_eq!(clamp_scissor([90, 80, 30, 40], 100, 100), Some([90, 80, 10, 20]));
+    assert_eq!(clamp_scissor([100, 0, 30, 40], 100, 100), None);
+    assert_eq!(clamp_scissor([0, 0, 0, 40], 100, 100), None);
+  }
+
+  #[test]
+  fn draw_order_sorted_after_add() {
+    let mut orders = vec![0, 0];
+    let mut draw_order = Vec::new();
+    insert_draw_order(&mut draw_order, 0, |i| orders[i]);
+    insert_draw_order(&mut draw_order, 1, |i| orders[i]);
+    assert_eq!(draw_order, vec![0, 1]);
+    orders[0] = 5;
+    sort_draw_order(&mut draw_order, |i| orders[i]);
+    assert_eq!(draw_order, vec![1, 0]);
+    // added after the reorder, still drawn before the higher order object
+    orders.push(0);
+    insert_draw_order(&mut draw_order, 2, |i| orders[i]);
+    assert_eq!(draw_order, vec![1, 2, 0]);
+    // ties go back to insertion order
+    orders[0] = 0;
+    sort_draw_order(&mut draw_order, |i| orders[i]);
+    assert_eq!(draw_order, vec![0, 1, 2]);
+  }
+
+  #[test]
+  fn unsized_scissor_leaves_later_objects_unclipped() {
+    // without resize neither object sets or resets a scissor rect
+    let scissors = [Some([10, 10, 20, 20]), None];
+    let actions: Vec<ObjScissor> = scissors.iter().map(|s| object_scissor(*s, (0, 0))).collect();
+    assert_eq!(actions, vec![ObjScissor::Full, ObjScissor::Full]);
+    // sized pipelines restore the full target after the clipped object
+    let actions: Vec<ObjScissor> = scissors.iter().map(|s| object_scissor(*s, (100, 50))).collect();
+    assert_eq!(actions, vec![
+      ObjScissor::Clip { rect: [10, 10, 20, 20], reset: [0, 0, 100, 50] },
+      ObjScissor::Full
+    ]);
+    assert_eq!(object_scissor(Some([200, 0, 10, 10]), (100, 50)), ObjScissor::Hidden);
+  }
+
+  #[test]
+  fn anim_uploads_keep_gen_color() {
+    let update = RenderObjectUpdate::default()
+      .with_color(RenderColor::RED)
+      .with_anim(vec![Mat4::translate(1.0, 2.0, 3.0); 3]);
+    let uploads = object_uploads(None, Some(&update.gen_buf), 2, &update.anim_transforms);
+    assert_eq!(uploads.iter().map(|u| u.0).collect::<Vec<_>>(), vec![GEN_SLOT, JOINTS_SLOT]);
+    let gen_data: &[f32] = bytemuck::cast_slice(&uploads[0].1);
+    assert_eq!(&gen_data[0..4], &[1.0, 0.0, 0.0, 1.0]);
+    // only max_joints matrices are sent
+    let joints: &[f32] = bytemuck::cast_slice(&uploads[1].1);
+    assert_eq!(joints.len(), 32);
+    assert_eq!(&joints[12..15], &[1.0, 2.0, 3.0]);
+    // unchanged mvp + gen with joints disabled uploads nothing
+    let uploads = object_uploads(None, None, 0, &update.anim_transforms);
+    assert!(uploads.is_empty());
+  }
+}
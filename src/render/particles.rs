@@ -0,0 +1,180 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::utils::{linear, Rng, Tweenable, Vec2, Vec4};
+use super::{RenderColor, SpriteBatch};
+
+// default seeds, so emitters created together don't spawn in lockstep
+static NEXT_SEED: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Particle {
+  pub position: Vec2,
+  pub velocity: Vec2,
+  pub age: f32,
+  pub lifetime: f32,
+}
+impl Particle {
+  /// 0 at spawn, 1 at end of life
+  pub fn life_pct(&self) -> f32 {
+    if self.lifetime > 0.0 { (self.age / self.lifetime).min(1.0) } else { 1.0 }
+  }
+}
+
+/// cpu simulated 2D emitter, drawn as quads through a SpriteBatch
+#[derive(Debug, Clone)]
+pub struct ParticleSystem {
+  pub origin: Vec2,
+  /// particles spawned per second by update, 0 = only spawn with emit
+  pub spawn_rate: f32,
+  /// (min, max) lifetime in seconds
+  pub lifetime: (f32, f32),
+  /// (min, max) initial velocity on each axis
+  pub velocity_min: Vec2,
+  pub velocity_max: Vec2,
+  pub gravity: Vec2,
+  /// (start, end) color over life
+  pub color: (RenderColor, RenderColor),
+  /// (start, end) size over life
+  pub size: (f32, f32),
+  /// easing applied to color + size over life
+  pub easing: fn(f32) -> f32,
+  pub max_particles: usize,
+  particles: Vec<Particle>,
+  spawn_accum: f32,
+  rng: Rng,
+}
+impl ParticleSystem {
+  /// seeded from a counter of created systems, see with_seed
+  pub fn new(origin: Vec2, max_particles: usize) -> Self {
+    Self {
+      origin,
+      spawn_rate: 0.0,
+      lifetime: (1.0, 1.0),
+      velocity_min: Vec2::new(-50.0, -50.0),
+      velocity_max: Vec2::new(50.0, 50.0),
+      gravity: Vec2::zero(),
+      color: (RenderColor::WHITE, RenderColor::WHITE),
+      size: (8.0, 8.0),
+      easing: linear,
+      max_particles,
+      particles: Vec::with_capacity(max_particles),
+      spawn_accum: 0.0,
+      rng: Rng::new(NEXT_SEED.fetch_add(1, Ordering::Relaxed)),
+    }
+  }
+  pub fn with_spawn_rate(mut self, rate: f32) -> Self {
+    self.spawn_rate = rate;
+    self
+  }
+  pub fn with_lifetime(mut self, min: f32, max: f32) -> Self {
+    self.lifetime = (min, max);
+    self
+  }
+  pub fn with_velocity(mut self, min: Vec2, max: Vec2) -> Self {
+    self.velocity_min = min;
+    self.velocity_max = max;
+    self
+  }
+  pub fn with_gravity(mut self, gravity: Vec2) -> Self {
+    self.gravity = gravity;
+    self
+  }
+  pub fn with_color(mut self, start: RenderColor, end: RenderColor) -> Self {
+    self.color = (start, end);
+    self
+  }
+  pub fn with_size(mut self, start: f32, end: f32) -> Self {
+    self.size = (start, end);
+    self
+  }
+  pub fn with_easing(mut self, easing: fn(f32) -> f32) -> Self {
+    self.easing = easing;
+    self
+  }
+  /// fixed seed for reproducible spawns
+  pub fn with_seed(mut self, seed: u64) -> Self {
+    self.rng = Rng::new(seed);
+    self
+  }
+  pub fn particles(&self) -> &[Particle] {
+    &self.particles
+  }
+  pub fn clear(&mut self) {
+    self.particles.clear();
+  }
+  /// spawns count particles at origin, capped at max_particles
+  pub fn emit(&mut self, count: usize) {
+    let count = count.min(self.max_particles - self.particles.len().min(self.max_particles));
+    for _ in 0..count {
+      let velocity = Vec2::new(
+        self.rng.range(self.velocity_min.x, self.velocity_max.x),
+        self.rng.range(self.velocity_min.y, self.velocity_max.y)
+      );
+      let lifetime = self.rng.range(self.lifetime.0, self.lifetime.1);
+      self.particles.push(Particle { position: self.origin, velocity, age: 0.0, lifetime });
+    }
+  }
+  /// ages + moves particles, removes expired ones and spawns new ones at spawn_rate
+  pub fn update(&mut self, dt: f32) {
+    for p in self.particles.iter_mut() {
+      p.age += dt;
+      p.velocity += self.gravity * dt;
+      p.position += p.velocity * dt;
+    }
+    self.particles.retain(|p| p.age < p.lifetime);
+    if self.spawn_rate > 0.0 {
+      self.spawn_accum += self.spawn_rate * dt;
+      let count = self.spawn_accum.floor();
+      self.spawn_accum -= count;
+      self.emit(count as usize);
+    }
+  }
+  /// queues all particles into a batch, uv_rect = atlas cell of the particle sprite
+  pub fn draw(&self, batch: &mut SpriteBatch, uv_rect: Vec4) {
+    for p in &self.particles {
+      let t = (self.easing)(p.life_pct());
+      let size = f32::interpolate(self.size.0, self.size.1, t);
+      let color = RenderColor::interpolate(self.color.0, self.color.1, t);
+      batch.draw(Vec4::new(p.position.x, p.position.y, size, size), uv_rect, color);
+    }
+  }
+}
+
+#[cfg(test)]
+mod particles_tests {
+  use super::*;
+
+  #[test]
+  fn particles_emit_and_expire() {
+    let mut ps = ParticleSystem::new(Vec2::zero(), 10).with_lifetime(1.0, 2.0).with_seed(7);
+    ps.emit(4);
+    assert_eq!(ps.particles().len(), 4);
+    // capped at max_particles
+    ps.emit(20);
+    assert_eq!(ps.particles().len(), 10);
+    ps.update(0.5);
+    assert_eq!(ps.particles().len(), 10);
+    ps.update(2.0);
+    assert!(ps.particles().is_empty());
+  }
+
+  #[test]
+  fn particles_spawn_rate() {
+    let mut ps = ParticleSystem::new(Vec2::zero(), 100).with_spawn_rate(10.0).with_lifetime(5.0, 5.0);
+    for _ in 0..10 { ps.update(0.1); }
+    assert_eq!(ps.particles().len(), 10);
+  }
+
+  #[test]
+  fn particles_gravity() {
+    let mut ps = ParticleSystem::new(Vec2::zero(), 1)
+      .with_velocity(Vec2::zero(), Vec2::zero())
+      .with_gravity(Vec2::new(0.0, -10.0))
+      .with_lifetime(10.0, 10.0);
+    ps.emit(1);
+    ps.update(1.0);
+    let p = ps.particles()[0];
+    assert_eq!(p.velocity, Vec2::new(0.0, -10.0));
+    assert_eq!(p.position, Vec2::new(0.0, -10.0));
+  }
+}
\ No newline at end of file
@@ -0,0 +1,191 @@
+use wgpu::{
+  AddressMode, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntry,
+  BindingResource, BindingType, Buffer, BufferBindingType, BufferDescriptor, BufferUsages, ColorTargetState,
+  ColorWrites, CommandEncoder, Device, FilterMode, FragmentState, LoadOp, MultisampleState, Operations,
+  PipelineCompilationOptions, PipelineLayoutDescriptor, PrimitiveState, Queue, RenderPassColorAttachment,
+  RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor,
+  ShaderModuleDescriptor, ShaderSource, ShaderStages, StoreOp, Texture, TextureFormat, TextureSampleType,
+  TextureViewDescriptor, TextureViewDimension, VertexState
+};
+
+use super::{build_render_texture, FULLSCREEN_VERTEX_SHADER};
+
+pub const POST_BLUR_SHADER: &str = include_str!("shaders/post_blur.wgsl");
+
+#[derive(Debug)]
+struct PostPass {
+  pipeline: RenderPipeline,
+  params_buffer: Buffer,
+}
+
+/// chain of full screen passes, each reading the previous output as texture1.
+/// pass shaders bind sampler (0), texture1 (1) and a vec4f params uniform (2),
+/// and only need `fragment_main(input: VertOut)`, the vertex stage is FULLSCREEN_VERTEX_SHADER
+#[derive(Debug)]
+pub struct PostProcess {
+  passes: Vec<PostPass>,
+  layout: BindGroupLayout,
+  sampler: Sampler,
+  format: TextureFormat,
+  // ping-pong outputs
+  targets: [Texture; 2],
+}
+impl PostProcess {
+  pub fn new(device: &Device, format: TextureFormat, width: u32, height: u32) -> Self {
+    let texture_entry = BindGroupLayoutEntry {
+      binding: 1,
+      visibility: ShaderStages::FRAGMENT,
+      ty: BindingType::Texture {
+        sample_type: TextureSampleType::Float { filterable: true },
+        view_dimension: TextureViewDimension::D2,
+        multisampled: false,
+      },
+      count: None,
+    };
+    let layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+      label: Some("post-bind-group-layout"),
+      entries: &[
+        BindGroupLayoutEntry {
+          binding: 0,
+          visibility: ShaderStages::FRAGMENT,
+          ty: BindingType::Sampler(SamplerBindingType::Filtering),
+          count: None,
+        },
+        texture_entry,
+        BindGroupLayoutEntry {
+          binding: 2,
+          visibility: ShaderStages::FRAGMENT,
+          ty: BindingType::Buffer {
+            ty: BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+          },
+          count: None,
+        },
+      ],
+    });
+    let sampler = device.create_sampler(&SamplerDescriptor {
+      label: Some("post-sampler"),
+      address_mode_u: AddressMode::ClampToEdge,
+      address_mode_v: AddressMode::ClampToEdge,
+      address_mode_w: AddressMode::ClampToEdge,
+      mag_filter: FilterMode::Linear,
+      min_filter: FilterMode::Linear,
+      ..Default::default()
+    });
+
+    Self {
+      passes: Vec::new(),
+      layout,
+      sampler,
+      format,
+      targets: [
+        build_render_texture(device, width.max(1), height.max(1), format),
+        build_render_texture(device, width.max(1), height.max(1), format),
+      ],
+    }
+  }
+  /// appends a full screen pass, returns its index for set_params
+  pub fn add_pass(&mut self, device: &Device, queue: &Queue, shader: &str, params: [f32; 4]) -> usize {
+    let shader_mod = device.create_shader_module(ShaderModuleDescriptor {
+      label: Some("post-shader"),
+      source: ShaderSource::Wgsl(format!("{}\n{}", FULLSCREEN_VERTEX_SHADER, shader).into()),
+    });
+    let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+      label: Some("post-pipeline-layout"),
+      bind_group_layouts: &[&self.layout],
+      push_constant_ranges: &[]
+    });
+    let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+      label: Some("post-pipeline"),
+      layout: Some(&pipeline_layout),
+      vertex: VertexState {
+        module: &shader_mod,
+        entry_point: Some("vertex_main"),
+        buffers: &[],
+        compilation_options: PipelineCompilationOptions::default(),
+      },
+      fragment: Some(FragmentState {
+        module: &shader_mod,
+        entry_point: Some("fragment_main"),
+        targets: &[Some(ColorTargetState {
+          format: self.format,
+          blend: None,
+          write_mask: ColorWrites::ALL
+        })],
+        compilation_options: PipelineCompilationOptions::default(),
+      }),
+      multisample: MultisampleState::default(),
+      depth_stencil: None,
+      primitive: PrimitiveState::default(),
+      multiview: None,
+      cache: None,
+    });
+    let params_buffer = device.create_buffer(&BufferDescriptor {
+      label: Some("post-params-buffer"),
+      size: std::mem::size_of::<[f32; 4]>() as u64,
+      usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+      mapped_at_creation: false,
+    });
+    queue.write_buffer(&params_buffer, 0, bytemuck::cast_slice(&params));
+    self.passes.push(PostPass { pipeline, params_buffer });
+    self.passes.len() - 1
+  }
+  /// horizontal + vertical gaussian blur passes, sigma in pixels
+  pub fn add_gaussian_blur(&mut self, device: &Device, queue: &Queue, sigma: f32) {
+    self.add_pass(device, queue, POST_BLUR_SHADER, [1.0, 0.0, sigma, 0.0]);
+    self.add_pass(device, queue, POST_BLUR_SHADER, [0.0, 1.0, sigma, 0.0]);
+  }
+  pub fn set_params(&mut self, queue: &Queue, idx: usize, params: [f32; 4]) {
+    match self.passes.get(idx) {
+      Some(p) => queue.write_buffer(&p.params_buffer, 0, bytemuck::cast_slice(&params)),
+      None => println!("ERR: Tried to access a post pass that doesn't exist {}/{}", idx, self.passes.len())
+    }
+  }
+  pub fn resize(&mut self, device: &Device, width: u32, height: u32) {
+    for t in &self.targets { t.destroy(); }
+    self.targets = [
+      build_render_texture(device, width.max(1), height.max(1), self.format),
+      build_render_texture(device, width.max(1), height.max(1), self.format),
+    ];
+  }
+  /// records all passes into encoder, returns the last output (input if there are no passes)
+  pub fn apply<'a>(&'a self, device: &Device, encoder: &mut CommandEncoder, input: &'a Texture) -> &'a Texture {
+    let mut source = input;
+    for (i, p) in self.passes.iter().enumerate() {
+      let target = &self.targets[i % 2];
+      let source_view = source.create_view(&TextureViewDescriptor::default());
+      let target_view = target.create_view(&TextureViewDescriptor::default());
+      let bind_group = device.create_bind_group(&BindGroupDescriptor {
+        label: Some("post-bind-group"),
+        layout: &self.layout,
+        entries: &[
+          BindGroupEntry { binding: 0, resource: BindingResource::Sampler(&self.sampler) },
+          BindGroupEntry { binding: 1, resource: BindingResource::TextureView(&source_view) },
+          BindGroupEntry { binding: 2, resource: p.params_buffer.as_entire_binding() },
+        ],
+      });
+      let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+        label: Some("post-render"),
+        color_attachments: &[Some(RenderPassColorAttachment {
+          view: &target_view,
+          resolve_target: None,
+          ops: Operations {
+            load: LoadOp::Clear(wgpu::Color::TRANSPARENT),
+            store: StoreOp::Store
+          }
+        })],
+        ..Default::default()
+      });
+      pass.set_pipeline(&p.pipeline);
+      pass.set_bind_group(0, &bind_group, &[]);
+      pass.draw(0..3, 0..1);
+      source = target;
+    }
+    source
+  }
+  pub fn destroy(&mut self) {
+    for p in &self.passes { p.params_buffer.destroy(); }
+    for t in &self.targets { t.destroy(); }
+  }
+}
\ No newline at end of file
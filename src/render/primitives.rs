@@ -1,6 +1,6 @@
 #![allow(dead_code)]
 
-use crate::utils::PI;
+use crate::utils::{Aabb3, PI, Vec2, Vec3};
 use super::shared::RenderVertex;
 
 // note: uv_y is inverted
@@ -12,6 +12,60 @@ impl Primitives {
       v.uv[1] = 1.0 - v.uv[1];
     }
   }
+  // bounds of vertex positions, zero sized box at origin for empty input
+  pub fn compute_aabb(vertices: &[RenderVertex]) -> Aabb3 {
+    if vertices.is_empty() { return Aabb3::new(Vec3::zero(), Vec3::zero()); }
+    let mut min = Vec3::from_array(vertices[0].position);
+    let mut max = min;
+    for v in vertices {
+      min = Vec3::new(min.x.min(v.position[0]), min.y.min(v.position[1]), min.z.min(v.position[2]));
+      max = Vec3::new(max.x.max(v.position[0]), max.y.max(v.position[1]), max.z.max(v.position[2]));
+    }
+    Aabb3::new(min, max)
+  }
+  // per-vertex tangents from uv gradients (w = bitangent handedness),
+  // empty indices treats vertices as a triangle list
+  pub fn compute_tangents(vertices: &[RenderVertex], indices: &[u32]) -> Vec<[f32; 4]> {
+    let n = vertices.len();
+    let mut tan = vec![Vec3::zero(); n];
+    let mut bitan = vec![Vec3::zero(); n];
+    let tris: Vec<[usize; 3]> = if indices.is_empty() {
+      (0..n / 3).map(|i| [3 * i, 3 * i + 1, 3 * i + 2]).collect()
+    } else {
+      indices.chunks_exact(3).map(|c| [c[0] as usize, c[1] as usize, c[2] as usize]).collect()
+    };
+    for [i0, i1, i2] in tris {
+      if i0 >= n || i1 >= n || i2 >= n { continue; }
+      let (v0, v1, v2) = (&vertices[i0], &vertices[i1], &vertices[i2]);
+      let e1 = Vec3::from_array(v1.position) - Vec3::from_array(v0.position);
+      let e2 = Vec3::from_array(v2.position) - Vec3::from_array(v0.position);
+      let du1 = v1.uv[0] - v0.uv[0];
+      let dv1 = v1.uv[1] - v0.uv[1];
+      let du2 = v2.uv[0] - v0.uv[0];
+      let dv2 = v2.uv[1] - v0.uv[1];
+      let det = du1 * dv2 - du2 * dv1;
+      if det.abs() < 0.000001 { continue; }
+      let r = 1.0 / det;
+      let t = (e1 * dv2 - e2 * dv1) * r;
+      let b = (e2 * du1 - e1 * du2) * r;
+      for i in [i0, i1, i2] {
+        tan[i] += t;
+        bitan[i] += b;
+      }
+    }
+    (0..n).map(|i| {
+      let normal = Vec3::from_array(vertices[i].normal);
+      // orthogonalize against normal
+      let mut t = (tan[i] - normal * normal.dot(tan[i])).normalize();
+      if t.magnitude() == 0.0 {
+        // no uv gradient, pick any perpendicular axis
+        let axis = if normal.x.abs() < 0.9 { Vec3::unit_x() } else { Vec3::unit_y() };
+        t = normal.cross(axis).normalize();
+      }
+      let w = if normal.cross(t).dot(bitan[i]) < 0.0 { -1.0 } else { 1.0 };
+      [t.x, t.y, t.z, w]
+    }).collect()
+  }
   // 2d primitives
   pub fn rect(width: f32, height: f32, z_index: f32) -> Vec<RenderVertex> {
     let w = width / 2.0;
@@ -101,6 +155,193 @@ impl Primitives {
 
     (v, idx)
   }
+  // filled pie slice, or an annular ring segment when thickness is given
+  pub fn arc(
+    radius: f32, start_deg: f32, end_deg: f32, segments: u32, thickness: Option<f32>
+  ) -> (Vec<RenderVertex>, Vec<u32>) {
+    let mut v: Vec<RenderVertex> = vec![];
+    let mut idx: Vec<u32> = vec![];
+    let segments = segments.max(1);
+    let a0 = start_deg.to_radians();
+    let da = (end_deg - start_deg).to_radians() / segments as f32;
+    // clockwise sweeps need flipped winding to keep facing +z
+    let ccw = end_deg >= start_deg;
+    let mut push_tri = |a: u32, b: u32, c: u32| {
+      if ccw { idx.extend([a, b, c]); } else { idx.extend([a, c, b]); }
+    };
+    let build_vertex = |x: f32, y: f32, r: f32| RenderVertex {
+      position: [x * r, y * r, 0.0],
+      uv: [(1.0 + x * r / radius)/2.0, 1.0 - (1.0 + y * r / radius)/2.0],
+      normal: [0.0, 0.0, 1.0]
+    };
+    match thickness {
+      Some(t) => {
+        let inner_radius = (radius - t).max(0.0);
+        // outer/inner pairs along the sweep
+        for i in 0..=segments {
+          let theta = a0 + da * i as f32;
+          let x = f32::cos(theta);
+          let y = f32::sin(theta);
+          v.push(build_vertex(x, y, radius));
+          v.push(build_vertex(x, y, inner_radius));
+        }
+        for i in 0..segments {
+          let o0 = 2 * i;
+          push_tri(o0 + 1, o0, o0 + 2);
+          push_tri(o0 + 1, o0 + 2, o0 + 3);
+        }
+      }
+      None => {
+        // fan from center
+        v.push(build_vertex(0.0, 0.0, radius));
+        for i in 0..=segments {
+          let theta = a0 + da * i as f32;
+          v.push(build_vertex(f32::cos(theta), f32::sin(theta), radius));
+        }
+        for i in 1..=segments {
+          push_tri(0, i, i + 1);
+        }
+      }
+    }
+
+    (v, idx)
+  }
+  pub fn line(p0: Vec2, p1: Vec2, thickness: f32) -> (Vec<RenderVertex>, Vec<u32>) {
+    Self::polyline(&[p0, p1], thickness, false)
+  }
+  // thick line strip through points with mitered joins
+  pub fn polyline(points: &[Vec2], thickness: f32, closed: bool) -> (Vec<RenderVertex>, Vec<u32>) {
+    let mut v: Vec<RenderVertex> = vec![];
+    let mut idx: Vec<u32> = vec![];
+    let n = points.len();
+    if n < 2 { return (v, idx); }
+    let half = thickness / 2.0;
+    let perp = |a: Vec2, b: Vec2| {
+      let d = (b - a).normalize();
+      Vec2::new(-d.y, d.x)
+    };
+    // total length for uv mapping
+    let mut total = 0.0;
+    for i in 1..n { total += Vec2::distance(points[i - 1], points[i]); }
+    if closed { total += Vec2::distance(points[n - 1], points[0]); }
+    let mut travelled = 0.0;
+
+    for i in 0..n {
+      let p = points[i];
+      let has_prev = i > 0 || closed;
+      let has_next = i < n - 1 || closed;
+      let prev = points[(i + n - 1) % n];
+      let next = points[(i + 1) % n];
+      // offset direction + length
+      let (dir, len) = if has_prev && has_next {
+        let n0 = perp(prev, p);
+        let n1 = perp(p, next);
+        let miter = (n0 + n1).normalize();
+        let cos_half = miter.dot(n1);
+        // limit spikes on very sharp corners
+        let len = if cos_half.abs() > 0.25 { half / cos_half } else { half * 4.0 };
+        (miter, len)
+      } else if has_next {
+        (perp(p, next), half)
+      } else {
+        (perp(prev, p), half)
+      };
+      if i > 0 { travelled += Vec2::distance(prev, p); }
+      let u = if total > 0.0 { travelled / total } else { 0.0 };
+      let l = p + dir * len;
+      let r = p - dir * len;
+      v.push(RenderVertex { position: [l.x, l.y, 0.0], uv: [u, 0.0], normal: [0.0, 0.0, 1.0] });
+      v.push(RenderVertex { position: [r.x, r.y, 0.0], uv: [u, 1.0], normal: [0.0, 0.0, 1.0] });
+    }
+    // join consecutive left/right pairs
+    let seg_count = if closed { n } else { n - 1 };
+    for i in 0..seg_count {
+      let l0 = 2 * i as u32;
+      let l1 = 2 * ((i + 1) % n) as u32;
+      idx.extend([l0 + 1, l1 + 1, l1]);
+      idx.extend([l1, l0, l0 + 1]);
+    }
+
+    (v, idx)
+  }
+  pub fn rounded_rect(width: f32, height: f32, radius: f32, corner_segments: u32) -> (Vec<RenderVertex>, Vec<u32>) {
+    let mut v: Vec<RenderVertex> = vec![];
+    let mut idx: Vec<u32> = vec![];
+    let w = width / 2.0;
+    let h = height / 2.0;
+    let r = radius.clamp(0.0, w.min(h));
+    let segs = corner_segments.max(1);
+    let build_vertex = |x: f32, y: f32| RenderVertex {
+      position: [x, y, 0.0],
+      uv: [x / width + 0.5, 0.5 - y / height],
+      normal: [0.0, 0.0, 1.0]
+    };
+    // fan from center around each corner arc (tr, tl, bl, br)
+    v.push(build_vertex(0.0, 0.0));
+    let corners = [(w - r, h - r), (-w + r, h - r), (-w + r, -h + r), (w - r, -h + r)];
+    for (q, (cx, cy)) in corners.iter().enumerate() {
+      for j in 0..=segs {
+        let theta = (q as f32 + j as f32 / segs as f32) * PI / 2.0;
+        v.push(build_vertex(cx + r * f32::cos(theta), cy + r * f32::sin(theta)));
+      }
+    }
+    let outline = v.len() as u32 - 1;
+    for i in 1..=outline {
+      idx.extend([0, i, i % outline + 1]);
+    }
+
+    (v, idx)
+  }
+  // line list grid on the xz plane, centered on origin
+  pub fn grid(size: f32, divisions: u32) -> Vec<RenderVertex> {
+    let mut v: Vec<RenderVertex> = vec![];
+    let divisions = divisions.max(1);
+    let h = size / 2.0;
+    let step = size / divisions as f32;
+    let build_vertex = |x: f32, z: f32| RenderVertex {
+      position: [x, 0.0, z],
+      uv: [x / size + 0.5, z / size + 0.5],
+      normal: [0.0, 1.0, 0.0]
+    };
+    for i in 0..=divisions {
+      let d = -h + step * i as f32;
+      // line along z
+      v.push(build_vertex(d, -h));
+      v.push(build_vertex(d, h));
+      // line along x
+      v.push(build_vertex(-h, d));
+      v.push(build_vertex(h, d));
+    }
+
+    v
+  }
+  // line list of the x and z center axes, for drawing over a grid in a bolder color
+  pub fn grid_axes(size: f32) -> Vec<RenderVertex> {
+    let h = size / 2.0;
+    vec![
+      RenderVertex { position: [-h, 0.0, 0.0], uv: [0.0, 0.5], normal: [0.0, 1.0, 0.0] },
+      RenderVertex { position: [h, 0.0, 0.0], uv: [1.0, 0.5], normal: [0.0, 1.0, 0.0] },
+      RenderVertex { position: [0.0, 0.0, -h], uv: [0.5, 0.0], normal: [0.0, 1.0, 0.0] },
+      RenderVertex { position: [0.0, 0.0, h], uv: [0.5, 1.0], normal: [0.0, 1.0, 0.0] },
+    ]
+  }
+  // expands a line list into quads for ShaderType::Line, each vertex stores its segment's
+  // other endpoint in normal and (side, start/end) in uv so the shader can widen it on screen
+  pub fn line_quads(line_list: &[RenderVertex]) -> (Vec<RenderVertex>, Vec<u32>) {
+    let mut v: Vec<RenderVertex> = vec![];
+    let mut idx: Vec<u32> = vec![];
+    for seg in line_list.chunks_exact(2) {
+      let (a, b) = (seg[0].position, seg[1].position);
+      let i = v.len() as u32;
+      v.push(RenderVertex { position: a, uv: [-1.0, 0.0], normal: b });
+      v.push(RenderVertex { position: b, uv: [-1.0, 1.0], normal: a });
+      v.push(RenderVertex { position: b, uv: [1.0, 1.0], normal: a });
+      v.push(RenderVertex { position: a, uv: [1.0, 0.0], normal: b });
+      idx.extend_from_slice(&[i, i + 1, i + 2, i, i + 2, i + 3]);
+    }
+
+    (v, idx)
+  }
   // 3d primitives
   pub fn cube(width: f32, height: f32, depth: f32) -> Vec<RenderVertex> {
     let w = width /2.0;
@@ -198,10 +439,49 @@ impl Primitives {
     (a, b)
   }
   pub fn cylinder(radius: f32, height: f32, sides: u32) -> (Vec<RenderVertex>, Vec<u32>) {
+    Self::cylinder_capped(radius, height, sides, true)
+  }
+  /// cylinder with or without the flat top/bottom faces
+  pub fn cylinder_capped(radius: f32, height: f32, sides: u32, caps: bool) -> (Vec<RenderVertex>, Vec<u32>) {
     let mut v: Vec<RenderVertex> = vec![];
     let mut idx: Vec<u32> = vec![];
     let h: f32 = height / 2.0;
-    // build top/bottom center
+    if caps {
+      Self::cylinder_caps(&mut v, &mut idx, radius, h, sides);
+    }
+
+    // build sides
+    let new0 = v.len();
+    for i in 0..sides + 1 {
+      let theta: f32 = 2.0 * PI * (i as f32 / sides as f32);
+      let x: f32 = f32::cos(theta);
+      let z: f32 = f32::sin(theta);
+      let v1 = RenderVertex {
+        position: [x * radius, h, z * radius],
+        uv: [(i as f32 / sides as f32), 1.0],
+        normal: [x, 0.0, z]
+      };
+      let v2 = RenderVertex {
+        position: [x * radius, -h, z * radius],
+        uv: [(i as f32 / sides as f32), 0.0],
+        normal: [x, 0.0, z]
+      };
+      v.push(v1);
+      v.push(v2);
+    }
+    // generate indexing
+    for i in new0..v.len() - 2 {
+      if i % 2 == 0 {
+        idx.push(i as u32 + 1); idx.push(i as u32); idx.push(i as u32 + 2);
+      } else {
+        idx.push(i as u32); idx.push(i as u32 + 1); idx.push(i as u32 + 2);
+      }
+    }
+
+    (v, idx)
+  }
+  // flat top/bottom faces with +/-y normals, separate from the side vertices
+  fn cylinder_caps(v: &mut Vec<RenderVertex>, idx: &mut Vec<u32>, radius: f32, h: f32, sides: u32) {
     let top_center = RenderVertex {
       position: [0.0, h, 0.0],
       uv: [0.5, 0.5],
@@ -244,36 +524,6 @@ impl Primitives {
     }
     idx.push(v.len() as u32 - 2); idx.push(0); idx.push(2);
     idx.push(v.len() as u32 - 1); idx.push(3); idx.push(1);
-
-    // build sides
-    let new0 = v.len();
-    for i in 0..sides + 1 {
-      let theta: f32 = 2.0 * PI * (i as f32 / sides as f32);
-      let x: f32 = f32::cos(theta);
-      let z: f32 = f32::sin(theta);
-      let v1 = RenderVertex {
-        position: [x * radius, h, z * radius],
-        uv: [(i as f32 / sides as f32), 1.0],
-        normal: [x, 0.0, z]
-      };
-      let v2 = RenderVertex {
-        position: [x * radius, -h, z * radius],
-        uv: [(i as f32 / sides as f32), 0.0],
-        normal: [x, 0.0, z]
-      };
-      v.push(v1);
-      v.push(v2);
-    }
-    // generate indexing
-    for i in new0..v.len() - 2 {
-      if i % 2 == 0 {
-        idx.push(i as u32 + 1); idx.push(i as u32); idx.push(i as u32 + 2);
-      } else {
-        idx.push(i as u32); idx.push(i as u32 + 1); idx.push(i as u32 + 2);
-      }
-    }
-
-    (v, idx)
   }
   pub fn tube(outer_radius: f32, inner_radius: f32, height: f32, sides: u32) -> (Vec<RenderVertex>, Vec<u32>) {
     let mut v: Vec<RenderVertex> = vec![];
@@ -375,15 +625,16 @@ impl Primitives {
       normal: [0.0, 1.0, 0.0]
     };
     v.push(v0);
-    // build sides
+    // build sides, normals tilt up with the slope
     for i in 0..sides+1 {
       let theta = 2.0 * PI * (i as f32) / (sides as f32);
       let x: f32 = f32::cos(theta);
       let z: f32 = f32::sin(theta);
+      let n = Vec3::new(x * height, radius, z * height).normalize();
       let v1 = RenderVertex {
         position: [x * radius, 0.0, z * radius],
         uv: [(i as f32) / (sides as f32), 0.0],
-        normal: [x, 0.0, z]
+        normal: [n.x, n.y, n.z]
       };
       v.push(v1);
     }
@@ -411,8 +662,8 @@ impl Primitives {
       };
       v.push(v1);
     }
-    // generate index
-    for i in new0..v.len() {
+    // generate index, last triangle wraps back to the first rim vertex
+    for i in new0..v.len() - 1 {
       idx.push(i as u32); idx.push(i as u32 + 1); idx.push(new0 as u32 - 1);
     }
     idx.push(v.len() as u32 - 1); idx.push(new0 as u32); idx.push(new0 as u32 - 1);
@@ -553,4 +804,105 @@ impl Primitives {
 
     (v, idx)
   }
+}
+
+#[cfg(test)]
+mod primitives_tests {
+  use super::*;
+
+  #[test]
+  fn tangents_follow_u() {
+    let (v, idx) = Primitives::rect_indexed(2.0, 2.0, 0.0);
+    let t = Primitives::compute_tangents(&v, &idx);
+    assert_eq!(t.len(), 4);
+    for tangent in t {
+      assert!((tangent[0] - 1.0).abs() < 0.0001);
+      assert!(tangent[1].abs() < 0.0001 && tangent[2].abs() < 0.0001);
+    }
+  }
+
+  #[test]
+  fn aabb_of_cube() {
+    let b = Primitives::compute_aabb(&Primitives::cube(2.0, 4.0, 6.0));
+    assert_eq!(b.min, Vec3::new(-1.0, -2.0, -3.0));
+    assert_eq!(b.max, Vec3::new(1.0, 2.0, 3.0));
+    assert_eq!(Primitives::compute_aabb(&[]).half_size(), Vec3::zero());
+  }
+
+  #[test]
+  fn line_segment() {
+    let (v, idx) = Primitives::line(Vec2::new(0.0, 0.0), Vec2::new(4.0, 0.0), 2.0);
+    assert_eq!(v.len(), 4);
+    assert_eq!(idx.len(), 6);
+    assert_eq!(v[0].position, [0.0, 1.0, 0.0]);
+    assert_eq!(v[3].position, [4.0, -1.0, 0.0]);
+  }
+
+  #[test]
+  fn polyline_miter() {
+    // right angle corner keeps edges parallel to each segment
+    let pts = [Vec2::new(0.0, 0.0), Vec2::new(4.0, 0.0), Vec2::new(4.0, 4.0)];
+    let (v, idx) = Primitives::polyline(&pts, 2.0, false);
+    assert_eq!(v.len(), 6);
+    assert_eq!(idx.len(), 12);
+    let corner = v[2].position;
+    assert!((corner[0] - 3.0).abs() < 0.0001 && (corner[1] - 1.0).abs() < 0.0001);
+
+    let (_, idx) = Primitives::polyline(&pts, 2.0, true);
+    assert_eq!(idx.len(), 18);
+  }
+
+  #[test]
+  fn line_quads_from_list() {
+    let (v, idx) = Primitives::line_quads(&Primitives::grid_axes(2.0));
+    assert_eq!(v.len(), 8);
+    assert_eq!(idx.len(), 12);
+    // every vertex points at the other end of its segment
+    assert_eq!(v[0].normal, v[1].position);
+    assert_eq!(v[2].normal, v[3].position);
+    assert_eq!(v[4].position, [0.0, 0.0, -1.0]);
+  }
+
+  #[test]
+  fn cylinder_caps() {
+    let (v, idx) = Primitives::cylinder_capped(1.0, 2.0, 8, true);
+    let (v_open, idx_open) = Primitives::cylinder_capped(1.0, 2.0, 8, false);
+    // caps add 2 centers + 2 rims, open version is only the side wall
+    assert_eq!(v.len(), v_open.len() + 2 + 2 * 8);
+    assert_eq!(idx.len(), idx_open.len() + 2 * 3 * 8);
+    assert!(idx.iter().all(|&i| (i as usize) < v.len()));
+    assert!(idx_open.iter().all(|&i| (i as usize) < v_open.len()));
+    assert!(v_open.iter().all(|vert| vert.normal[1] == 0.0));
+    let ys: Vec<f32> = v[..2 + 2 * 8].iter().map(|vert| vert.normal[1]).collect();
+    assert!(ys.iter().all(|&y| y == 1.0 || y == -1.0));
+  }
+
+  #[test]
+  fn cone_side_normals() {
+    let (v, idx) = Primitives::cone(1.0, 1.0, 4);
+    assert!(idx.iter().all(|&i| (i as usize) < v.len()));
+    // 45 degree slope
+    let n = v[1].normal;
+    assert!((n[0] - n[1]).abs() < 0.0001 && (n[0] - 0.5f32.sqrt()).abs() < 0.0001);
+    assert_eq!(v.last().unwrap().normal, [0.0, -1.0, 0.0]);
+  }
+
+  #[test]
+  fn cone_indices_in_range() {
+    // bottom face used to index one past the last vertex
+    let (v, idx) = Primitives::cone(1.0, 2.0, 12);
+    assert!(idx.iter().all(|&i| (i as usize) < v.len()));
+    assert_eq!(idx.len() % 3, 0);
+  }
+
+  #[test]
+  fn rounded_rect_bounds() {
+    let (v, idx) = Primitives::rounded_rect(10.0, 6.0, 2.0, 4);
+    assert_eq!(v.len(), 1 + 4 * 5);
+    assert_eq!(idx.len(), 3 * 4 * 5);
+    for vert in &v {
+      assert!(vert.position[0].abs() <= 5.0 + 0.0001);
+      assert!(vert.position[1].abs() <= 3.0 + 0.0001);
+    }
+  }
 }
\ No newline at end of file
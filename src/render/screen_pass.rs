@@ -0,0 +1,118 @@
+use wgpu::{
+  BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType,
+  BlendState, Buffer, BufferBindingType, BufferDescriptor, BufferUsages, ColorTargetState, ColorWrites, Device,
+  FragmentState, MultisampleState, PipelineCompilationOptions, PipelineLayoutDescriptor, PrimitiveState, Queue,
+  RenderPass, RenderPipeline, RenderPipelineDescriptor, ShaderModuleDescriptor, ShaderSource, ShaderStages,
+  TextureFormat, VertexState
+};
+
+/// full screen triangle vertex stage providing VertOut (uv), prepend it to a fragment shader
+pub const FULLSCREEN_VERTEX_SHADER: &str = include_str!("shaders/fullscreen.wgsl");
+const SCREEN_PASS_UNIFORMS: &str = include_str!("shaders/screen_pass.wgsl");
+
+/// runs a fragment shader over the whole target with a single generated triangle.
+/// the fragment source only needs `fragment_main(input: VertOut)`, it is appended to
+/// FULLSCREEN_VERTEX_SHADER + the `screen` uniform (shaders/screen_pass.wgsl)
+#[derive(Debug)]
+pub struct ScreenPass {
+  pipeline: RenderPipeline,
+  bind_group: BindGroup,
+  buffer: Buffer,
+  // size (w, h, time, 0), params
+  data: [[f32; 4]; 2],
+}
+impl ScreenPass {
+  pub fn new(device: &Device, queue: &Queue, target_format: TextureFormat, fragment_shader: &str, width: u32, height: u32) -> Self {
+    let shader_mod = device.create_shader_module(ShaderModuleDescriptor {
+      label: Some("screen-pass-shader"),
+      source: ShaderSource::Wgsl(format!("{}\n{}\n{}", FULLSCREEN_VERTEX_SHADER, SCREEN_PASS_UNIFORMS, fragment_shader).into()),
+    });
+    let layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+      label: Some("screen-pass-bind-group-layout"),
+      entries: &[BindGroupLayoutEntry {
+        binding: 0,
+        visibility: ShaderStages::FRAGMENT,
+        ty: BindingType::Buffer {
+          ty: BufferBindingType::Uniform,
+          has_dynamic_offset: false,
+          min_binding_size: None,
+        },
+        count: None,
+      }],
+    });
+    let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+      label: Some("screen-pass-pipeline-layout"),
+      bind_group_layouts: &[&layout],
+      push_constant_ranges: &[]
+    });
+    let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+      label: Some("screen-pass-pipeline"),
+      layout: Some(&pipeline_layout),
+      vertex: VertexState {
+        module: &shader_mod,
+        entry_point: Some("vertex_main"),
+        buffers: &[],
+        compilation_options: PipelineCompilationOptions::default(),
+      },
+      fragment: Some(FragmentState {
+        module: &shader_mod,
+        entry_point: Some("fragment_main"),
+        targets: &[Some(ColorTargetState {
+          format: target_format,
+          blend: Some(BlendState::ALPHA_BLENDING),
+          write_mask: ColorWrites::ALL
+        })],
+        compilation_options: PipelineCompilationOptions::default(),
+      }),
+      multisample: MultisampleState::default(),
+      depth_stencil: None,
+      primitive: PrimitiveState::default(),
+      multiview: None,
+      cache: None,
+    });
+
+    let buffer = device.create_buffer(&BufferDescriptor {
+      label: Some("screen-pass-buffer"),
+      size: std::mem::size_of::<[[f32; 4]; 2]>() as u64,
+      usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+      mapped_at_creation: false,
+    });
+    let bind_group = device.create_bind_group(&BindGroupDescriptor {
+      label: Some("screen-pass-bind-group"),
+      layout: &layout,
+      entries: &[BindGroupEntry { binding: 0, resource: buffer.as_entire_binding() }],
+    });
+
+    let data = [[width as f32, height as f32, 0.0, 0.0], [0.0; 4]];
+    queue.write_buffer(&buffer, 0, bytemuck::cast_slice(&data));
+
+    Self {
+      pipeline,
+      bind_group,
+      buffer,
+      data,
+    }
+  }
+  pub fn resize(&mut self, queue: &Queue, width: u32, height: u32) {
+    self.data[0][0] = width as f32;
+    self.data[0][1] = height as f32;
+    queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&self.data));
+  }
+  /// advances screen.size.z, call once per frame with the frame time
+  pub fn update_time(&mut self, queue: &Queue, dt: f32) {
+    self.data[0][2] += dt;
+    queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&self.data));
+  }
+  pub fn set_params(&mut self, queue: &Queue, params: [f32; 4]) {
+    self.data[1] = params;
+    queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&self.data));
+  }
+  pub fn render(&self, pass: &mut RenderPass) {
+    pass.set_pipeline(&self.pipeline);
+    pass.set_bind_group(0, &self.bind_group, &[]);
+    pass.draw(0..3, 0..1);
+  }
+  pub fn destroy(&mut self) {
+    self.buffer.destroy();
+  }
+}
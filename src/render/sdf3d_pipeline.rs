@@ -0,0 +1,171 @@
+use wgpu::{
+  BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType,
+  BlendState, Buffer, BufferBindingType, BufferDescriptor, BufferUsages, ColorTargetState, ColorWrites, Device,
+  FragmentState, MultisampleState, PipelineCompilationOptions, PipelineLayoutDescriptor, PrimitiveState, Queue,
+  RenderPass, RenderPipeline, RenderPipelineDescriptor, ShaderModuleDescriptor, ShaderSource, ShaderStages,
+  TextureFormat, VertexState
+};
+
+use crate::utils::{SDFObject3D, Vec3};
+use super::{FULLSCREEN_VERTEX_SHADER, RenderCamera, RenderColor, RenderSDF3DObjectC, RenderSDF3DSceneC};
+
+// matches array size in sdf3d shader
+pub const MAX_SDF3D_OBJECTS: usize = 32;
+
+/// raymarches SDFObject3D shapes over a full screen triangle
+#[derive(Debug)]
+pub struct SDF3DPipeline {
+  pipeline: RenderPipeline,
+  bind_group: BindGroup,
+  scene_buffer: Buffer,
+  object_buffer: Buffer,
+  scene: RenderSDF3DSceneC,
+}
+impl SDF3DPipeline {
+  pub fn new(device: &Device, target_format: TextureFormat) -> Self {
+    let shader_mod = device.create_shader_module(ShaderModuleDescriptor {
+      label: Some("sdf3d-shader"),
+      source: ShaderSource::Wgsl(format!("{}\n{}", FULLSCREEN_VERTEX_SHADER, include_str!("shaders/sdf3d.wgsl")).into()),
+    });
+    let uniform_entry = |binding: u32| BindGroupLayoutEntry {
+      binding,
+      visibility: ShaderStages::FRAGMENT,
+      ty: BindingType::Buffer {
+        ty: BufferBindingType::Uniform,
+        has_dynamic_offset: false,
+        min_binding_size: None,
+      },
+      count: None,
+    };
+    let layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+      label: Some("sdf3d-bind-group-layout"),
+      entries: &[uniform_entry(0), uniform_entry(1)],
+    });
+    let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+      label: Some("sdf3d-pipeline-layout"),
+      bind_group_layouts: &[&layout],
+      push_constant_ranges: &[]
+    });
+    let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+      label: Some("sdf3d-pipeline"),
+      layout: Some(&pipeline_layout),
+      vertex: VertexState {
+        module: &shader_mod,
+        entry_point: Some("vertex_main"),
+        buffers: &[],
+        compilation_options: PipelineCompilationOptions::default(),
+      },
+      fragment: Some(FragmentState {
+        module: &shader_mod,
+        entry_point: Some("fragment_main"),
+        targets: &[Some(ColorTargetState {
+          format: target_format,
+          blend: Some(BlendState::ALPHA_BLENDING),
+          write_mask: ColorWrites::ALL
+        })],
+        compilation_options: PipelineCompilationOptions::default(),
+      }),
+      multisample: MultisampleState::default(),
+      depth_stencil: None,
+      primitive: PrimitiveState::default(),
+      multiview: None,
+      cache: None,
+    });
+
+    let scene_buffer = device.create_buffer(&BufferDescriptor {
+      label: Some("sdf3d-scene-buffer"),
+      size: std::mem::size_of::<RenderSDF3DSceneC>() as u64,
+      usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+      mapped_at_creation: false,
+    });
+    let object_buffer = device.create_buffer(&BufferDescriptor {
+      label: Some("sdf3d-object-buffer"),
+      size: (std::mem::size_of::<RenderSDF3DObjectC>() * MAX_SDF3D_OBJECTS) as u64,
+      usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+      mapped_at_creation: false,
+    });
+    let bind_group = device.create_bind_group(&BindGroupDescriptor {
+      label: Some("sdf3d-bind-group"),
+      layout: &layout,
+      entries: &[
+        BindGroupEntry { binding: 0, resource: scene_buffer.as_entire_binding() },
+        BindGroupEntry { binding: 1, resource: object_buffer.as_entire_binding() },
+      ],
+    });
+
+    Self {
+      pipeline,
+      bind_group,
+      scene_buffer,
+      object_buffer,
+      scene: RenderSDF3DSceneC {
+        cam_pos: [0.0, 0.0, 100.0, 0.0],
+        cam_fwd: [0.0, 0.0, -1.0, 1000.0],
+        cam_right: [1.0, 0.0, 0.0, 0.0],
+        cam_up: [0.0, 1.0, 0.0, 0.0],
+        light_dir: [0.5, 1.0, 0.5, 1.0],
+        params: [0.0; 4],
+      },
+    }
+  }
+  /// ray origins + directions follow the camera, ray length is capped at camera far plane
+  pub fn update_camera(&mut self, queue: &Queue, camera: &RenderCamera) {
+    let fwd = (camera.look_at - camera.position).normalize();
+    let right = fwd.cross(camera.up).normalize();
+    let up = right.cross(fwd);
+    let aspect = camera.target_size.x / camera.target_size.y;
+    // ortho cameras offset ray origins by half the view size instead
+    let (ortho, extent_x, extent_y) = if camera.fov_y > 0.0 {
+      let tan_y = f32::tan(camera.zoomed_fov_y().to_radians() * 0.5);
+      (0.0, tan_y * aspect, tan_y)
+    } else {
+      (1.0, camera.target_size.x * 0.5 / camera.zoom, camera.target_size.y * 0.5 / camera.zoom)
+    };
+    let (right, up) = (right * extent_x, up * extent_y);
+    self.scene.cam_pos = [camera.position.x, camera.position.y, camera.position.z, self.scene.cam_pos[3]];
+    self.scene.cam_fwd = [fwd.x, fwd.y, fwd.z, camera.far];
+    self.scene.cam_right = [right.x, right.y, right.z, ortho];
+    self.scene.cam_up = [up.x, up.y, up.z, 0.0];
+    queue.write_buffer(&self.scene_buffer, 0, bytemuck::bytes_of(&self.scene));
+  }
+  /// direction towards the light, shadow intensity 0 = no shadows, 1 = full soft shadows
+  pub fn set_light(&mut self, queue: &Queue, light_dir: Vec3, shadow_intensity: f32) {
+    let l = light_dir.normalize();
+    self.scene.light_dir = [l.x, l.y, l.z, f32::clamp(shadow_intensity, 0.0, 1.0)];
+    queue.write_buffer(&self.scene_buffer, 0, bytemuck::bytes_of(&self.scene));
+  }
+  /// advances the shader clock, call once per frame with the frame time
+  pub fn update_time(&mut self, queue: &Queue, dt: f32) {
+    self.scene.params[0] += dt;
+    queue.write_buffer(&self.scene_buffer, 0, bytemuck::bytes_of(&self.scene));
+  }
+  pub fn update_objects(&mut self, queue: &Queue, objs: &[(SDFObject3D, RenderColor)]) {
+    if objs.len() > MAX_SDF3D_OBJECTS {
+      println!("ERR: Exceeded max SDF objects ({}), extra objects are ignored", MAX_SDF3D_OBJECTS);
+    }
+    let data: Vec<RenderSDF3DObjectC> = objs.iter().take(MAX_SDF3D_OBJECTS).map(|(obj, color)| {
+      let t: u32 = obj.obj_type.into();
+      RenderSDF3DObjectC {
+        center: [obj.center.x, obj.center.y, obj.center.z, t as f32],
+        params: [obj.size.x, obj.size.y, obj.size.z, obj.radius],
+        color: [color.r, color.g, color.b, color.a],
+        style: [obj.feather, obj.glow, 0.0, 0.0],
+      }
+    }).collect();
+    if !data.is_empty() {
+      queue.write_buffer(&self.object_buffer, 0, bytemuck::cast_slice(&data));
+    }
+    self.scene.cam_pos[3] = data.len() as f32;
+    queue.write_buffer(&self.scene_buffer, 0, bytemuck::bytes_of(&self.scene));
+  }
+  pub fn render(&self, pass: &mut RenderPass) {
+    if self.scene.cam_pos[3] < 1.0 { return; }
+    pass.set_pipeline(&self.pipeline);
+    pass.set_bind_group(0, &self.bind_group, &[]);
+    pass.draw(0..3, 0..1);
+  }
+  pub fn destroy(&mut self) {
+    self.scene_buffer.destroy();
+    self.object_buffer.destroy();
+  }
+}
\ No newline at end of file
@@ -0,0 +1,29 @@
+@group(0) @binding(0) var<uniform> camera: Camera;
+
+struct Camera {
+  view: mat4x4<f32>,
+  proj: mat4x4<f32>,
+}
+
+struct VertIn {
+  @location(0) pos: vec3f,
+  @location(1) color: vec4f,
+}
+
+struct VertOut {
+  @builtin(position) pos: vec4f,
+  @location(0) color: vec4f,
+}
+
+@vertex
+fn vertex_main(input: VertIn) -> VertOut {
+  var out: VertOut;
+  out.pos = camera.proj * camera.view * vec4f(input.pos, 1.0);
+  out.color = input.color;
+  return out;
+}
+
+@fragment
+fn fragment_main(input: VertOut) -> @location(0) vec4f {
+  return input.color;
+}
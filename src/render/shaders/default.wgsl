@@ -2,6 +2,7 @@
 @group(0) @binding(2) var tx_sampler: sampler;
 @group(0) @binding(3) var texture1: texture_2d<f32>;
 @group(0) @binding(4) var texture2: texture_2d<f32>;
+@group(0) @binding(5) var<uniform> lights: Lights;
 
 struct MVP {
   model: mat4x4<f32>,
@@ -9,6 +10,20 @@ struct MVP {
   proj: mat4x4<f32>,
 }
 
+struct Light {
+  // xyz: direction or position, w: 0 = directional, 1 = point
+  pos: vec4f,
+  // rgb: color, a: intensity
+  color: vec4f,
+  // x: ambient
+  params: vec4f,
+}
+
+struct Lights {
+  count: u32,
+  lights: array<Light, 8>,
+}
+
 struct VertIn {
   @location(0) pos: vec3f,
   @location(1) uv: vec2f,
@@ -19,6 +34,22 @@ struct VertOut {
   @builtin(position) pos: vec4f,
   @location(0) uv: vec2f,
   @location(1) normal: vec3f,
+  @location(2) world_pos: vec3f,
+}
+
+fn lambert(normal: vec3f, world_pos: vec3f) -> vec3f {
+  // unlit if no lights are provided
+  if (lights.count == 0u) { return vec3f(1.0); }
+  let n = normalize(normal);
+  var out = vec3f(0.0);
+  for (var i = 0u; i < min(lights.count, 8u); i++) {
+    let l = lights.lights[i];
+    var dir = normalize(-l.pos.xyz);
+    if (l.pos.w > 0.5) { dir = normalize(l.pos.xyz - world_pos); }
+    let diffuse = max(dot(n, dir), 0.0) * l.color.a;
+    out += l.color.rgb * (diffuse + l.params.x);
+  }
+  return out;
 }
 
 @vertex
@@ -28,6 +59,7 @@ fn vertex_main(input: VertIn) -> VertOut {
   out.pos = mvp_mat * vec4f(input.pos, 1.0);
   out.uv = vec2f(input.uv.x, input.uv.y);
   out.normal = (mvp.model * vec4f(input.normal, 0.0)).xyz;
+  out.world_pos = (mvp.model * vec4f(input.pos, 1.0)).xyz;
   return out;
 }
 
@@ -40,5 +72,6 @@ fn fragment_main(input: VertOut) -> @location(0) vec4f {
   tx1 = mix(tx1, vec4f(n, 1.0), step(tx1.a, 0.0001));
   // mix tx1 and tx2, tx2 overwrites tx1
   let blend = mix(tx1, tx2, tx2.a);
-  return vec4f(blend.rgb, tx1.a);
+  let light = lambert(input.normal, input.world_pos);
+  return vec4f(blend.rgb * light, tx1.a);
 }
\ No newline at end of file
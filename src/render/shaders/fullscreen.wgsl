@@ -0,0 +1,17 @@
+// full screen vertex stage shared by ScreenPass, PostProcess, SDF3DPipeline + mipmaps,
+// fragment shaders are appended after this
+struct VertOut {
+  @builtin(position) pos: vec4f,
+  // 0,0 top left to 1,1 bottom right
+  @location(0) uv: vec2f,
+}
+
+// single triangle covering the screen, no vertex buffer
+@vertex
+fn vertex_main(@builtin(vertex_index) idx: u32) -> VertOut {
+  var out: VertOut;
+  let uv = vec2f(f32((idx << 1u) & 2u), f32(idx & 2u));
+  out.pos = vec4f(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+  out.uv = uv;
+  return out;
+}
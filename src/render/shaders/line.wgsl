@@ -0,0 +1,62 @@
+@group(0) @binding(0) var<uniform> mvp: MVP;
+@group(0) @binding(1) var<uniform> gen: Gen;
+
+struct MVP {
+  model: mat4x4<f32>,
+  view: mat4x4<f32>,
+  proj: mat4x4<f32>,
+}
+
+struct Gen {
+  albedo: vec4f,
+  border: vec4f,
+  albedo2: vec4f,
+  gradient: vec4f,
+  // x: width (px), y: feather (px), zw: target size (px)
+  line: vec4f,
+}
+
+struct VertIn {
+  @location(0) pos: vec3f,
+  // x: side (-1 or 1), y: 0 = segment start, 1 = segment end
+  @location(1) uv: vec2f,
+  // other endpoint of the segment
+  @location(2) normal: vec3f,
+}
+
+struct VertOut {
+  @builtin(position) pos: vec4f,
+  // signed distance from line center (px)
+  @location(0) edge: f32,
+}
+
+@vertex
+fn vertex_main(input: VertIn) -> VertOut {
+  var out: VertOut;
+  let mvp_mat = mvp.proj * mvp.view * mvp.model;
+  var a = mvp_mat * vec4f(input.pos, 1.0);
+  var b = mvp_mat * vec4f(input.normal, 1.0);
+  // pull endpoints behind the camera onto the near side so the direction stays valid
+  let near_w = 0.0001;
+  if (a.w < near_w && b.w >= near_w) { a = mix(a, b, (near_w - a.w) / (b.w - a.w)); }
+  if (b.w < near_w && a.w >= near_w) { b = mix(b, a, (near_w - b.w) / (a.w - b.w)); }
+  let size = max(gen.line.zw, vec2f(1.0));
+  // segment direction in pixels, always start -> end so both ends agree on the sides
+  var dir = (b.xy / b.w - a.xy / a.w) * size;
+  if (input.uv.y > 0.5) { dir = -dir; }
+  if (length(dir) < 0.0001) { dir = vec2f(1.0, 0.0); }
+  dir = normalize(dir);
+  let half_width = max(gen.line.x, 1.0) * 0.5 + gen.line.y;
+  let offset = vec2f(-dir.y, dir.x) * input.uv.x * half_width;
+  out.pos = vec4f(a.xy + offset / size * 2.0 * a.w, a.zw);
+  out.edge = input.uv.x * half_width;
+  return out;
+}
+
+@fragment
+fn fragment_main(input: VertOut) -> @location(0) vec4f {
+  let half_width = max(gen.line.x, 1.0) * 0.5;
+  let feather = max(gen.line.y, 0.0001);
+  let alpha = clamp((half_width + gen.line.y - abs(input.edge)) / feather, 0.0, 1.0);
+  return vec4f(gen.albedo.rgb, gen.albedo.a * alpha);
+}
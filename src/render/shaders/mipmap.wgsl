@@ -0,0 +1,8 @@
+@group(0) @binding(0) var tx_sampler: sampler;
+@group(0) @binding(1) var src_texture: texture_2d<f32>;
+
+// VertOut + vertex_main come from fullscreen.wgsl
+@fragment
+fn fragment_main(input: VertOut) -> @location(0) vec4f {
+  return textureSample(src_texture, tx_sampler, input.uv);
+}
\ No newline at end of file
@@ -0,0 +1,34 @@
+@group(0) @binding(0) var<uniform> mvp: MVP;
+// object index + 1 in x, 0 is reserved for empty
+@group(1) @binding(0) var<uniform> pick_id: vec4<u32>;
+
+struct MVP {
+  model: mat4x4<f32>,
+  view: mat4x4<f32>,
+  proj: mat4x4<f32>,
+}
+
+struct VertIn {
+  @location(0) pos: vec3f,
+  @location(1) uv: vec2f,
+  @location(2) normal: vec3f,
+}
+
+struct VertOut {
+  @builtin(position) pos: vec4f,
+  @location(0) @interpolate(flat) id: u32,
+}
+
+@vertex
+fn vertex_main(input: VertIn) -> VertOut {
+  var out: VertOut;
+  let mvp_mat = mvp.proj * mvp.view * mvp.model;
+  out.pos = mvp_mat * vec4f(input.pos, 1.0);
+  out.id = pick_id.x;
+  return out;
+}
+
+@fragment
+fn fragment_main(input: VertOut) -> @location(0) u32 {
+  return input.id;
+}
\ No newline at end of file
@@ -0,0 +1,20 @@
+@group(0) @binding(0) var tx_sampler: sampler;
+@group(0) @binding(1) var texture1: texture_2d<f32>;
+@group(0) @binding(2) var<uniform> params: vec4f;
+
+// VertOut + vertex_main come from fullscreen.wgsl
+@fragment
+fn fragment_main(input: VertOut) -> @location(0) vec4f {
+  let texel = params.xy / vec2f(textureDimensions(texture1));
+  let sigma = max(params.z, 0.001);
+  let taps = min(i32(ceil(sigma * 3.0)), 32);
+  var sum = vec4f(0.0);
+  var total = 0.0;
+  for (var i = -taps; i <= taps; i++) {
+    let x = f32(i);
+    let w = exp(-(x * x) / (2.0 * sigma * sigma));
+    sum += textureSampleLevel(texture1, tx_sampler, input.uv + texel * x, 0.0) * w;
+    total += w;
+  }
+  return sum / total;
+}
@@ -0,0 +1,9 @@
+// ScreenPass uniforms, between fullscreen.wgsl and the fragment shader
+@group(0) @binding(0) var<uniform> screen: Screen;
+
+struct Screen {
+  // xy: target size in pixels, z: elapsed time in seconds
+  size: vec4f,
+  // user defined, see ScreenPass::set_params
+  params: vec4f,
+}
@@ -0,0 +1,151 @@
+@group(0) @binding(0) var<uniform> scene: Scene;
+@group(0) @binding(1) var<uniform> objs: array<Obj, 32>;
+
+struct Scene {
+  // w: object count
+  cam_pos: vec4f,
+  // w: max ray distance
+  cam_fwd: vec4f,
+  // w: 1 = orthographic
+  cam_right: vec4f,
+  cam_up: vec4f,
+  // w: shadow intensity
+  light_dir: vec4f,
+  // x: elapsed time in seconds, for animating shapes/colors in custom variants
+  params: vec4f,
+}
+
+struct Obj {
+  // w: 1 = sphere, 2 = box, 3 = plane
+  center: vec4f,
+  params: vec4f,
+  color: vec4f,
+  // x: edge feather width (centered on the surface), y: glow radius
+  style: vec4f,
+}
+
+// VertOut + vertex_main come from fullscreen.wgsl
+fn obj_dist(p: vec3f, obj: Obj) -> f32 {
+  let t = u32(obj.center.w);
+  if (t == 1u) {
+    return length(p - obj.center.xyz) - obj.params.x;
+  }
+  if (t == 2u) {
+    let q = abs(p - obj.center.xyz) - (obj.params.xyz - obj.params.w);
+    return length(max(q, vec3f(0.0))) + min(max(q.x, max(q.y, q.z)), 0.0) - obj.params.w;
+  }
+  if (t == 3u) {
+    return dot(p, obj.params.xyz) - obj.params.w;
+  }
+  return scene.cam_fwd.w;
+}
+
+// x: closest distance, y: index of closest object
+fn scene_sdf(p: vec3f) -> vec2f {
+  var res = vec2f(scene.cam_fwd.w, -1.0);
+  let count = u32(scene.cam_pos.w);
+  for (var i = 0u; i < count; i++) {
+    let d = obj_dist(p, objs[i]);
+    if (d < res.x) { res = vec2f(d, f32(i)); }
+  }
+  return res;
+}
+
+fn scene_normal(p: vec3f) -> vec3f {
+  let e = vec2f(0.001, 0.0);
+  return normalize(vec3f(
+    scene_sdf(p + e.xyy).x - scene_sdf(p - e.xyy).x,
+    scene_sdf(p + e.yxy).x - scene_sdf(p - e.yxy).x,
+    scene_sdf(p + e.yyx).x - scene_sdf(p - e.yyx).x,
+  ));
+}
+
+// penumbra estimate from the closest miss along the shadow ray
+fn soft_shadow(origin: vec3f, dir: vec3f) -> f32 {
+  var res = 1.0;
+  var t = 0.05;
+  for (var i = 0; i < 64; i++) {
+    let d = scene_sdf(origin + dir * t).x;
+    if (d < 0.001) { return 0.0; }
+    res = min(res, 8.0 * d / t);
+    t += clamp(d, 0.01, 10.0);
+    if (t > scene.cam_fwd.w) { break; }
+  }
+  return clamp(res, 0.0, 1.0);
+}
+
+// ambient + shadowed diffuse term at surface point p
+fn light_term(p: vec3f, n: vec3f) -> f32 {
+  let l = normalize(scene.light_dir.xyz);
+  let shadow = mix(1.0, soft_shadow(p + n * 0.01, l), scene.light_dir.w);
+  let diffuse = max(dot(n, l), 0.0) * shadow;
+  return 0.15 + 0.85 * diffuse;
+}
+
+// lowest distance to obj along the ray past a hit at t, stops once it is half the feather deep.
+// steps are safe since the distance can't drop below -half faster than d + half
+fn hit_depth(origin: vec3f, dir: vec3f, t: f32, obj: Obj) -> f32 {
+  let half = obj.style.x * 0.5;
+  var depth = 0.0;
+  var s = t;
+  for (var i = 0; i < 32; i++) {
+    let d = obj_dist(origin + dir * s, obj);
+    depth = min(depth, d);
+    if (d <= -half || d > half) { break; }
+    s += max(d + half, 0.001 * max(s, 1.0));
+  }
+  return depth;
+}
+
+@fragment
+fn fragment_main(input: VertOut) -> @location(0) vec4f {
+  let ndc = vec2f(input.uv.x * 2.0 - 1.0, 1.0 - input.uv.y * 2.0);
+  var origin = scene.cam_pos.xyz;
+  var dir = normalize(scene.cam_fwd.xyz + ndc.x * scene.cam_right.xyz + ndc.y * scene.cam_up.xyz);
+  if (scene.cam_right.w > 0.5) {
+    origin = origin + ndc.x * scene.cam_right.xyz + ndc.y * scene.cam_up.xyz;
+    dir = normalize(scene.cam_fwd.xyz);
+  }
+  // march until hit or max distance, tracking the closest miss for feather + glow
+  var t = 0.0;
+  var hit = vec2f(scene.cam_fwd.w, -1.0);
+  var closest = vec2f(scene.cam_fwd.w, -1.0);
+  var closest_t = 0.0;
+  var is_hit = false;
+  for (var i = 0; i < 128; i++) {
+    hit = scene_sdf(origin + dir * t);
+    if (hit.x < closest.x) { closest = hit; closest_t = t; }
+    if (hit.x < 0.001 * max(t, 1.0)) { is_hit = true; break; }
+    t += hit.x;
+    if (t > scene.cam_fwd.w) { break; }
+  }
+  if (!is_hit && closest.y < 0.0) { discard; }
+
+  // signed distance of the ray to the surface: positive for misses, negative once it goes in
+  var edge = closest.x;
+  var p = origin + dir * closest_t;
+  var obj = objs[u32(closest.y)];
+  if (is_hit) {
+    obj = objs[u32(hit.y)];
+    p = origin + dir * t;
+    edge = select(-1.0, hit_depth(origin, dir, t, obj), obj.style.x > 0.0);
+  }
+  // misses are lit like the nearest surface point so the fringe matches the fill
+  var n = scene_normal(p);
+  if (!is_hit) {
+    p = p - n * closest.x;
+    n = scene_normal(p);
+  }
+  let half = max(obj.style.x, 0.0001) * 0.5;
+  let fill = (1.0 - smoothstep(-half, half, edge)) * obj.color.a;
+  var glow = 0.0;
+  if (obj.style.y > 0.0) {
+    let g = 1.0 - clamp(max(edge, 0.0) / obj.style.y, 0.0, 1.0);
+    glow = g * g * obj.color.a;
+  }
+  // glow halo is added outside the fill instead of replacing its alpha
+  let alpha = fill + glow * (1.0 - fill);
+  if (alpha <= 0.0) { discard; }
+  let rgb = obj.color.rgb * light_term(p, n) * fill + obj.color.rgb * glow * (1.0 - fill);
+  return vec4f(rgb / alpha, alpha);
+}
\ No newline at end of file
@@ -0,0 +1,19 @@
+@group(0) @binding(0) var<uniform> mvp: MVP;
+
+// model matrix with the light's view + projection
+struct MVP {
+  model: mat4x4<f32>,
+  view: mat4x4<f32>,
+  proj: mat4x4<f32>,
+}
+
+struct VertIn {
+  @location(0) pos: vec3f,
+  @location(1) uv: vec2f,
+  @location(2) normal: vec3f,
+}
+
+@vertex
+fn vertex_main(input: VertIn) -> @builtin(position) vec4f {
+  return mvp.proj * mvp.view * mvp.model * vec4f(input.pos, 1.0);
+}
\ No newline at end of file
@@ -0,0 +1,108 @@
+@group(0) @binding(0) var<uniform> mvp: MVP;
+@group(0) @binding(2) var tx_sampler: sampler;
+@group(0) @binding(3) var texture1: texture_2d<f32>;
+@group(0) @binding(4) var texture2: texture_2d<f32>;
+@group(0) @binding(5) var<uniform> lights: Lights;
+@group(1) @binding(0) var shadow_map: texture_depth_2d;
+@group(1) @binding(1) var shadow_sampler: sampler_comparison;
+@group(1) @binding(2) var<uniform> shadow: Shadow;
+
+struct MVP {
+  model: mat4x4<f32>,
+  view: mat4x4<f32>,
+  proj: mat4x4<f32>,
+}
+
+struct Light {
+  // xyz: direction or position, w: 0 = directional, 1 = point
+  pos: vec4f,
+  // rgb: color, a: intensity
+  color: vec4f,
+  // x: ambient
+  params: vec4f,
+}
+
+struct Lights {
+  count: u32,
+  lights: array<Light, 8>,
+}
+
+struct Shadow {
+  view: mat4x4<f32>,
+  proj: mat4x4<f32>,
+  // x: 1 = shadow map bound, y: depth bias
+  params: vec4f,
+}
+
+struct VertIn {
+  @location(0) pos: vec3f,
+  @location(1) uv: vec2f,
+  @location(2) normal: vec3f,
+}
+
+struct VertOut {
+  @builtin(position) pos: vec4f,
+  @location(0) uv: vec2f,
+  @location(1) normal: vec3f,
+  @location(2) world_pos: vec3f,
+}
+
+// 1 = lit, 0 = fully shadowed
+fn shadow_factor(world_pos: vec3f) -> f32 {
+  let clip = shadow.proj * shadow.view * vec4f(world_pos, 1.0);
+  let ndc = clip.xyz / clip.w;
+  let uv = vec2f(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+  // 3x3 pcf
+  let texel = 1.0 / vec2f(textureDimensions(shadow_map));
+  var lit = 0.0;
+  for (var x = -1; x <= 1; x++) {
+    for (var y = -1; y <= 1; y++) {
+      let offset = vec2f(f32(x), f32(y)) * texel;
+      lit += textureSampleCompareLevel(shadow_map, shadow_sampler, uv + offset, ndc.z - shadow.params.y);
+    }
+  }
+  lit /= 9.0;
+  // anything outside the light's frustum is lit
+  let inside = all(uv >= vec2f(0.0)) && all(uv <= vec2f(1.0)) && ndc.z <= 1.0;
+  return select(1.0, lit, inside && shadow.params.x > 0.5);
+}
+
+fn lambert(normal: vec3f, world_pos: vec3f) -> vec3f {
+  // unlit if no lights are provided
+  if (lights.count == 0u) { return vec3f(1.0); }
+  let n = normalize(normal);
+  let lit = shadow_factor(world_pos);
+  var out = vec3f(0.0);
+  for (var i = 0u; i < min(lights.count, 8u); i++) {
+    let l = lights.lights[i];
+    var dir = normalize(-l.pos.xyz);
+    if (l.pos.w > 0.5) { dir = normalize(l.pos.xyz - world_pos); }
+    let diffuse = max(dot(n, dir), 0.0) * l.color.a;
+    out += l.color.rgb * (diffuse * lit + l.params.x);
+  }
+  return out;
+}
+
+@vertex
+fn vertex_main(input: VertIn) -> VertOut {
+  var out: VertOut;
+  let mvp_mat = mvp.proj * mvp.view * mvp.model;
+  out.pos = mvp_mat * vec4f(input.pos, 1.0);
+  out.uv = vec2f(input.uv.x, input.uv.y);
+  out.normal = (mvp.model * vec4f(input.normal, 0.0)).xyz;
+  out.world_pos = (mvp.model * vec4f(input.pos, 1.0)).xyz;
+  return out;
+}
+
+@fragment
+fn fragment_main(input: VertOut) -> @location(0) vec4f {
+  let n = (1.0 + input.normal) / 2.0;
+  var tx1 = textureSample(texture1, tx_sampler, input.uv);
+  var tx2 = textureSample(texture2, tx_sampler, input.uv);
+  // draw normal instead of texture if alpha < 0.0001
+  tx1 = mix(tx1, vec4f(n, 1.0), step(tx1.a, 0.0001));
+  // mix tx1 and tx2, tx2 overwrites tx1
+  let blend = mix(tx1, tx2, tx2.a);
+  let light = lambert(input.normal, input.world_pos);
+  return vec4f(blend.rgb * light, tx1.a);
+}
\ No newline at end of file
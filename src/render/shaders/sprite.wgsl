@@ -0,0 +1,34 @@
+@group(0) @binding(0) var<uniform> camera: Camera;
+@group(0) @binding(1) var tx_sampler: sampler;
+@group(0) @binding(2) var atlas: texture_2d<f32>;
+
+struct Camera {
+  view: mat4x4<f32>,
+  proj: mat4x4<f32>,
+}
+
+struct VertIn {
+  @location(0) pos: vec2f,
+  @location(1) uv: vec2f,
+  @location(2) color: vec4f,
+}
+
+struct VertOut {
+  @builtin(position) pos: vec4f,
+  @location(0) uv: vec2f,
+  @location(1) color: vec4f,
+}
+
+@vertex
+fn vertex_main(input: VertIn) -> VertOut {
+  var out: VertOut;
+  out.pos = camera.proj * camera.view * vec4f(input.pos, 0.0, 1.0);
+  out.uv = input.uv;
+  out.color = input.color;
+  return out;
+}
+
+@fragment
+fn fragment_main(input: VertOut) -> @location(0) vec4f {
+  return textureSample(atlas, tx_sampler, input.uv) * input.color;
+}
@@ -0,0 +1,46 @@
+@group(0) @binding(0) var<uniform> mvp: MVP;
+@group(0) @binding(1) var<uniform> gen: Gen;
+@group(0) @binding(2) var tx_sampler: sampler;
+@group(0) @binding(3) var texture1: texture_2d_array<f32>;
+
+struct MVP {
+  model: mat4x4<f32>,
+  view: mat4x4<f32>,
+  proj: mat4x4<f32>,
+}
+
+struct Gen {
+  albedo: vec4f,
+  border: vec4f,
+  albedo2: vec4f,
+  gradient: vec4f,
+  line: vec4f,
+  // x: texture array layer
+  layer: vec4f,
+}
+
+struct VertIn {
+  @location(0) pos: vec3f,
+  @location(1) uv: vec2f,
+  @location(2) normal: vec3f,
+}
+
+struct VertOut {
+  @builtin(position) pos: vec4f,
+  @location(0) uv: vec2f,
+}
+
+@vertex
+fn vertex_main(input: VertIn) -> VertOut {
+  var out: VertOut;
+  let mvp_mat = mvp.proj * mvp.view * mvp.model;
+  out.pos = mvp_mat * vec4f(input.pos, 1.0);
+  out.uv = input.uv;
+  return out;
+}
+
+@fragment
+fn fragment_main(input: VertOut) -> @location(0) vec4f {
+  let layer = i32(gen.layer.x + 0.5);
+  return textureSample(texture1, tx_sampler, input.uv, layer);
+}
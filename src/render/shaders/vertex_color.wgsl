@@ -0,0 +1,83 @@
+@group(0) @binding(0) var<uniform> mvp: MVP;
+@group(0) @binding(1) var<uniform> gen: Gen;
+@group(0) @binding(5) var<uniform> lights: Lights;
+
+struct MVP {
+  model: mat4x4<f32>,
+  view: mat4x4<f32>,
+  proj: mat4x4<f32>,
+}
+
+struct Gen {
+  albedo: vec4f,
+  // xy: rect size, z: border radius
+  border: vec4f,
+  // gradient end color
+  albedo2: vec4f,
+  // x: gradient angle (radians)
+  gradient: vec4f,
+}
+
+struct Light {
+  // xyz: direction or position, w: 0 = directional, 1 = point
+  pos: vec4f,
+  // rgb: color, a: intensity
+  color: vec4f,
+  // x: ambient
+  params: vec4f,
+}
+
+struct Lights {
+  count: u32,
+  lights: array<Light, 8>,
+}
+
+struct VertIn {
+  @location(0) pos: vec3f,
+  @location(1) uv: vec2f,
+  @location(2) normal: vec3f,
+  @location(3) color: vec4f,
+}
+
+struct VertOut {
+  @builtin(position) pos: vec4f,
+  @location(0) uv: vec2f,
+  @location(1) normal: vec3f,
+  @location(2) world_pos: vec3f,
+  @location(3) color: vec4f,
+}
+
+fn lambert(normal: vec3f, world_pos: vec3f) -> vec3f {
+  // unlit if no lights are provided
+  if (lights.count == 0u) { return vec3f(1.0); }
+  let n = normalize(normal);
+  var out = vec3f(0.0);
+  for (var i = 0u; i < min(lights.count, 8u); i++) {
+    let l = lights.lights[i];
+    var dir = normalize(-l.pos.xyz);
+    if (l.pos.w > 0.5) { dir = normalize(l.pos.xyz - world_pos); }
+    let diffuse = max(dot(n, dir), 0.0) * l.color.a;
+    out += l.color.rgb * (diffuse + l.params.x);
+  }
+  return out;
+}
+
+@vertex
+fn vertex_main(input: VertIn) -> VertOut {
+  var out: VertOut;
+  let mvp_mat = mvp.proj * mvp.view * mvp.model;
+  out.pos = mvp_mat * vec4f(input.pos, 1.0);
+  out.uv = vec2f(input.uv.x, input.uv.y);
+  out.normal = (mvp.model * vec4f(input.normal, 0.0)).xyz;
+  out.world_pos = (mvp.model * vec4f(input.pos, 1.0)).xyz;
+  out.color = input.color;
+  return out;
+}
+
+@fragment
+fn fragment_main(input: VertOut) -> @location(0) vec4f {
+  // per-vertex color tinted by the object color
+  let albedo = gen.albedo * input.color;
+  let light = lambert(input.normal, input.world_pos);
+  return vec4f(albedo.rgb * light, albedo.a);
+}
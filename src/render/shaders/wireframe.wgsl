@@ -0,0 +1,41 @@
+@group(0) @binding(0) var<uniform> mvp: MVP;
+@group(0) @binding(1) var<uniform> gen: Gen;
+
+struct MVP {
+  model: mat4x4<f32>,
+  view: mat4x4<f32>,
+  proj: mat4x4<f32>,
+}
+
+struct Gen {
+  albedo: vec4f,
+  border: vec4f,
+  albedo2: vec4f,
+  gradient: vec4f,
+  line: vec4f,
+  layer: vec4f,
+  // edge color (RenderObjectUpdate::with_wireframe)
+  wireframe: vec4f,
+}
+
+struct VertIn {
+  @location(0) pos: vec3f,
+  @location(1) uv: vec2f,
+  @location(2) normal: vec3f,
+}
+
+@vertex
+fn vertex_main(input: VertIn) -> @builtin(position) vec4f {
+  let mvp_mat = mvp.proj * mvp.view * mvp.model;
+  return mvp_mat * vec4f(input.pos, 1.0);
+}
+
+// set by the pipeline for RenderBlendMode::PremultipliedAlpha
+override premultiplied: bool = false;
+
+@fragment
+fn fragment_main() -> @location(0) vec4f {
+  let c = gen.wireframe;
+  if (premultiplied) { return vec4f(c.rgb * c.a, c.a); }
+  return c;
+}
@@ -2,28 +2,34 @@ use bytemuck::{Pod, Zeroable};
 use wgpu::{
   AddressMode, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
   BindGroupLayoutEntry, BindingResource, BindingType, Buffer, BufferBinding, BufferBindingType,
-  BufferDescriptor, BufferUsages, Device, Extent3d, Face, FilterMode, Limits, PolygonMode, PrimitiveState,
-  PrimitiveTopology, RenderPipeline, SamplerBindingType, SamplerDescriptor, ShaderModule, ShaderModuleDescriptor,
-  ShaderSource, ShaderStages, Texture, TextureDescriptor, TextureDimension, TextureFormat, TextureSampleType,
-  TextureUsages, TextureView, TextureViewDescriptor, TextureViewDimension
+  BufferDescriptor, BufferUsages, ColorTargetState, CompareFunction, ColorWrites, CommandEncoderDescriptor, Device, Extent3d,
+  Face, Features, FilterMode, FragmentState, Limits, LoadOp, MultisampleState, Operations, Origin3d, PolygonMode,
+  PrimitiveState, PrimitiveTopology, Queue, RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline,
+  RenderPipelineDescriptor, SamplerBindingType, SamplerDescriptor, ShaderModule, ShaderModuleDescriptor,
+  ShaderSource, ShaderStages, StoreOp, TexelCopyBufferLayout, TexelCopyTextureInfo, Texture, TextureAspect,
+  TextureDescriptor, TextureDimension, TextureFormat, TextureSampleType, TextureUsages, TextureView,
+  TextureViewDescriptor, TextureViewDimension, VertexState
 };
 
 use crate::{vec2f, vec3f};
-use crate::utils::{ Vec2, Vec3, Mat4 };
+use crate::utils::{ Aabb3, Vec2, Vec3, Vec4, Mat4, Tweenable };
+use super::FULLSCREEN_VERTEX_SHADER;
 
 // --- --- --- --- --- --- --- --- --- //
 // --- --- - HELPER STRUCTS -- --- --- //
 // --- --- --- --- --- --- --- --- --- //
 
 // helper for updating render object
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum RenderRotation {
   AxisAngle(Vec3, f32),
   Euler(f32, f32, f32)
 }
 
 // helper for defining camera/view matrix
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct RenderCamera {
   pub cam_type: u8,
   pub position: Vec3,
@@ -33,6 +39,8 @@ pub struct RenderCamera {
   pub near: f32,
   pub far: f32,
   pub target_size: Vec2,
+  /// magnification applied to the projection, > 1 zooms in (ortho extents, perspective fov)
+  pub zoom: f32,
 }
 impl Default for RenderCamera {
   fn default() -> Self {
@@ -45,6 +53,7 @@ impl Default for RenderCamera {
       near: 0.0,
       far: 1000.0,
       target_size: vec2f!(100.0, 100.0),
+      zoom: 1.0,
     }
   }
 }
@@ -61,6 +70,7 @@ impl RenderCamera {
       near,
       far,
       target_size,
+      zoom: 1.0,
     }
   }
   pub fn new_persp(fov_y: f32, near: f32, far: f32, target_size: Vec2) -> Self {
@@ -73,12 +83,82 @@ impl RenderCamera {
       near,
       far,
       target_size,
+      zoom: 1.0,
     }
   }
+  /// vertical fov in degrees for perspective cameras, clamped to 1-179
+  pub fn set_fov(&mut self, fov_y: f32) {
+    if self.cam_type != RenderCamera::PERSPECTIVE {
+      println!("ERR: Field of view only applies to perspective cameras");
+      return;
+    }
+    self.fov_y = fov_y.clamp(1.0, 179.0);
+  }
+  /// zooms without moving the camera or changing target_size, 1 = no zoom
+  pub fn set_zoom(&mut self, zoom: f32) {
+    self.zoom = zoom.max(0.001);
+  }
+  /// fov_y after zoom, in degrees
+  pub fn zoomed_fov_y(&self) -> f32 {
+    let half = f32::atan(f32::tan(self.fov_y.to_radians() * 0.5) / self.zoom);
+    (half * 2.0).to_degrees()
+  }
+  /// rotates the position around look_at, yaw around the up axis then pitch towards up (degrees).
+  /// pitch stops 1 degree short of either pole
+  pub fn orbit(&mut self, yaw: f32, pitch: f32) {
+    let up = self.up.normalize();
+    let offset = self.position - self.look_at;
+    let dist = offset.magnitude();
+    if dist == 0.0 { return; }
+    let rotate = |v: Vec3, axis: Vec3, deg: f32| {
+      let r = Mat4::from_col_major(Mat4::rotate(&axis, deg)).multiply_vec4(&Vec4::new(v.x, v.y, v.z, 0.0));
+      vec3f!(r.x, r.y, r.z)
+    };
+    let mut offset = rotate(offset, up, yaw);
+    let axis = offset.cross(up);
+    if axis.magnitude() > 0.0 {
+      let polar = f32::acos((offset.dot(up) / dist).clamp(-1.0, 1.0)).to_degrees();
+      let target = (polar - pitch).clamp(1.0, 179.0);
+      offset = rotate(offset, axis, polar - target);
+    }
+    self.position = self.look_at + offset.normalize() * dist;
+  }
+  /// moves the camera back along its current view direction until the box fits in view
+  pub fn fit_aabb(&mut self, aabb: &Aabb3) {
+    let center = aabb.center();
+    let radius = aabb.half_size().magnitude().max(0.001);
+    let dir = self.position - self.look_at;
+    let dir = if dir.magnitude() > 0.0 { dir.normalize() } else { vec3f!(0.0, 0.0, 1.0) };
+    let dist = if self.cam_type == RenderCamera::PERSPECTIVE {
+      // bounding sphere against the narrower of the vertical and horizontal fov
+      let half_y = self.zoomed_fov_y().to_radians() * 0.5;
+      let aspect = self.target_size.x / self.target_size.y;
+      let half_x = f32::atan(f32::tan(half_y) * aspect);
+      radius / f32::sin(half_y.min(half_x))
+    } else {
+      // ortho size is fixed in pixels, only keep the box between near and far
+      radius + self.near
+    };
+    self.look_at = center;
+    self.position = center + dir * dist;
+    self.far = self.far.max(dist + radius);
+  }
+  /// projects a world position to pixel coordinates (top left origin), None if behind the camera
+  pub fn world_to_screen(&self, world: Vec3, screen_size: Vec2) -> Option<Vec2> {
+    let (view, proj) = camera_matrices(self);
+    let view_pos = Mat4::from_col_major(view).multiply_vec4(&Vec4::new(world.x, world.y, world.z, 1.0));
+    // camera looks down -z in view space
+    if view_pos.z >= 0.0 { return None; }
+    let clip = Mat4::from_col_major(proj).multiply_vec4(&view_pos);
+    if clip.w <= 0.0 { return None; }
+    let (ndc_x, ndc_y) = (clip.x / clip.w, clip.y / clip.w);
+    Some(vec2f!((ndc_x + 1.0) * 0.5 * screen_size.x, (1.0 - ndc_y) * 0.5 * screen_size.y))
+  }
 }
 
 // color helper (for passing into uniform)
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RenderColor {
   pub r: f32,
   pub g: f32,
@@ -114,6 +194,16 @@ impl Into<wgpu::Color> for RenderColor {
     }
   }
 }
+impl Tweenable for RenderColor {
+  fn interpolate(a: Self, b: Self, t: f32) -> Self {
+    Self {
+      r: a.r + (b.r - a.r) * t,
+      g: a.g + (b.g - a.g) * t,
+      b: a.b + (b.b - a.b) * t,
+      a: a.a + (b.a - a.a) * t,
+    }
+  }
+}
 impl RenderColor {
   pub fn rgba_pct(r: f32, g: f32, b: f32, a: f32) -> Self {
     Self { r, g, b, a }
@@ -134,6 +224,20 @@ impl RenderColor {
       a: a as f32 / 255.0,
     }
   }
+  /// sRGB encoded 0-255 channels (e.g. from a color picker) converted to linear values,
+  /// which an sRGB surface encodes back to the intended color
+  pub fn from_srgb_u8(r: u8, g: u8, b: u8, a: u8) -> Self {
+    Self::rgba(r, g, b, a).to_linear()
+  }
+  /// treats rgb as sRGB encoded and converts it to linear (alpha is unchanged)
+  pub fn to_linear(self) -> Self {
+    Self {
+      r: srgb_to_linear(self.r),
+      g: srgb_to_linear(self.g),
+      b: srgb_to_linear(self.b),
+      a: self.a,
+    }
+  }
   pub fn hsv(h: f32, s: f32, v: f32) -> Self {
     let i = f32::floor(h * 6.0);
     let f = h * 6.0 - i;
@@ -153,7 +257,43 @@ impl RenderColor {
     }
     clr
   }
-  // pre-defined colors
+  /// inverse of hsv(), returns (h, s, v) in 0-1 range
+  pub fn to_hsv(self) -> (f32, f32, f32) {
+    let max = f32::max(self.r, f32::max(self.g, self.b));
+    let min = f32::min(self.r, f32::min(self.g, self.b));
+    let d = max - min;
+    let s = if max > 0.0 { d / max } else { 0.0 };
+    (hue_of(self.r, self.g, self.b, max, d), s, max)
+  }
+  pub fn hsl(h: f32, s: f32, l: f32) -> Self {
+    // map onto hsv
+    let v = l + s * f32::min(l, 1.0 - l);
+    let sv = if v > 0.0 { 2.0 * (1.0 - l / v) } else { 0.0 };
+    Self::hsv(h, sv, v)
+  }
+  /// inverse of hsl(), returns (h, s, l) in 0-1 range
+  pub fn to_hsl(self) -> (f32, f32, f32) {
+    let max = f32::max(self.r, f32::max(self.g, self.b));
+    let min = f32::min(self.r, f32::min(self.g, self.b));
+    let d = max - min;
+    let l = (max + min) * 0.5;
+    let s = if l > 0.0 && l < 1.0 { d / (1.0 - f32::abs(2.0 * l - 1.0)) } else { 0.0 };
+    (hue_of(self.r, self.g, self.b, max, d), s, l)
+  }
+  /// blend between two colors in linear space
+  pub fn lerp(a: Self, b: Self, t: f32) -> Self {
+    let mix = |x: f32, y: f32| {
+      let lin = srgb_to_linear(x) + (srgb_to_linear(y) - srgb_to_linear(x)) * t;
+      linear_to_srgb(lin)
+    };
+    Self {
+      r: mix(a.r, b.r),
+      g: mix(a.g, b.g),
+      b: mix(a.b, b.b),
+      a: a.a + (b.a - a.a) * t,
+    }
+  }
+  // pre-defined colors, values written as-is (sRGB encoded, call to_linear for exact tones)
   pub const TRANSPARENT: Self = Self {
     r: 0.0, g: 0.0, b: 0.0, a: 0.0,
   };
@@ -192,6 +332,200 @@ impl RenderColor {
   };
 }
 
+// shared hue calculation for to_hsv/to_hsl
+fn hue_of(r: f32, g: f32, b: f32, max: f32, d: f32) -> f32 {
+  if d <= 0.0 { return 0.0; }
+  let h = if max == r { (g - b) / d }
+    else if max == g { (b - r) / d + 2.0 }
+    else { (r - g) / d + 4.0 };
+  (h / 6.0).rem_euclid(1.0)
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+  if c <= 0.04045 { c / 12.92 } else { f32::powf((c + 0.055) / 1.055, 2.4) }
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+  if c <= 0.0031308 { c * 12.92 } else { 1.055 * f32::powf(c, 1.0 / 2.4) - 0.055 }
+}
+
+// color target blending for ObjPipeline
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum RenderBlendMode {
+  /// straight alpha (SrcAlpha, OneMinusSrcAlpha)
+  #[default]
+  AlphaBlend,
+  /// color already multiplied by alpha (One, OneMinusSrcAlpha)
+  PremultipliedAlpha,
+}
+
+// what to do with existing target contents at the start of a render pass
+// note: Load on the screen surface forces the previous frame to be kept around,
+// which is slower on tiled gpus + undefined after resize/present on some backends
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RenderLoadOp<T> {
+  Clear(T),
+  Load,
+}
+impl From<RenderLoadOp<RenderColor>> for wgpu::LoadOp<wgpu::Color> {
+  fn from(op: RenderLoadOp<RenderColor>) -> Self {
+    match op {
+      RenderLoadOp::Clear(c) => wgpu::LoadOp::Clear(c.into()),
+      RenderLoadOp::Load => wgpu::LoadOp::Load,
+    }
+  }
+}
+impl From<RenderLoadOp<f32>> for wgpu::LoadOp<f32> {
+  fn from(op: RenderLoadOp<f32>) -> Self {
+    match op {
+      RenderLoadOp::Clear(d) => wgpu::LoadOp::Clear(d),
+      RenderLoadOp::Load => wgpu::LoadOp::Load,
+    }
+  }
+}
+
+// light helper (for passing into shared light uniform)
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RenderLightType {
+  Directional,
+  Point,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RenderLight {
+  pub light_type: RenderLightType,
+  // direction for directional lights, position for point lights
+  pub direction: Vec3,
+  pub color: RenderColor,
+  pub intensity: f32,
+  pub ambient: f32,
+}
+impl Default for RenderLight {
+  fn default() -> Self {
+    Self {
+      light_type: RenderLightType::Directional,
+      direction: vec3f!(0.0, -1.0, -1.0),
+      color: RenderColor::WHITE,
+      intensity: 1.0,
+      ambient: 0.1,
+    }
+  }
+}
+impl RenderLight {
+  pub fn directional(direction: Vec3, color: RenderColor, intensity: f32) -> Self {
+    Self {
+      light_type: RenderLightType::Directional,
+      direction,
+      color,
+      intensity,
+      ..Default::default()
+    }
+  }
+  pub fn point(position: Vec3, color: RenderColor, intensity: f32) -> Self {
+    Self {
+      light_type: RenderLightType::Point,
+      direction: position,
+      color,
+      intensity,
+      ..Default::default()
+    }
+  }
+  pub fn with_ambient(mut self, ambient: f32) -> Self {
+    self.ambient = ambient;
+    self
+  }
+}
+
+pub const MAX_LIGHTS: usize = 8;
+
+// matches Lights struct in shaders
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct RenderLightsC {
+  pub count: u32,
+  pub _pad: [u32; 3],
+  pub lights: [[f32; 12]; MAX_LIGHTS],
+}
+impl RenderLightsC {
+  pub fn from_lights(lights: &[RenderLight]) -> Self {
+    let mut out = Self::zeroed();
+    if lights.len() > MAX_LIGHTS {
+      println!("ERR: Only {} lights are supported, ignoring the rest", MAX_LIGHTS);
+    }
+    for (i, l) in lights.iter().take(MAX_LIGHTS).enumerate() {
+      let t = match l.light_type {
+        RenderLightType::Directional => 0.0,
+        RenderLightType::Point => 1.0,
+      };
+      out.lights[i] = [
+        l.direction.x, l.direction.y, l.direction.z, t,
+        l.color.r, l.color.g, l.color.b, l.intensity,
+        l.ambient, 0.0, 0.0, 0.0,
+      ];
+      out.count += 1;
+    }
+    out
+  }
+}
+
+// matches Shadow struct in shadowed shader
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct RenderShadowC {
+  pub view: [f32; 16],
+  pub proj: [f32; 16],
+  // x: enabled, y: depth bias
+  pub params: [f32; 4],
+}
+
+// matches Scene struct in sdf3d shader
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct RenderSDF3DSceneC {
+  // w: object count
+  pub cam_pos: [f32; 4],
+  // w: max ray distance
+  pub cam_fwd: [f32; 4],
+  // scaled by view extents, w: 1 = orthographic
+  pub cam_right: [f32; 4],
+  pub cam_up: [f32; 4],
+  // direction towards the light, w: shadow intensity
+  pub light_dir: [f32; 4],
+  // x: elapsed time in seconds
+  pub params: [f32; 4],
+}
+
+// matches Obj struct in sdf3d shader
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, Pod, Zeroable)]
+pub struct RenderSDF3DObjectC {
+  // w: object type
+  pub center: [f32; 4],
+  // xyz: size or normal, w: corner radius or plane offset
+  pub params: [f32; 4],
+  pub color: [f32; 4],
+  // x: feather, y: glow
+  pub style: [f32; 4],
+}
+
+// float offsets into RenderObjectUpdate::gen_buf, one vec4f each (the Gen struct in the shaders)
+/// albedo color (with_color)
+pub const GEN_ALBEDO: usize = 0;
+/// rect size xy + corner radius z (with_round_border)
+pub const GEN_BORDER: usize = 4;
+/// second gradient color (with_gradient)
+pub const GEN_ALBEDO2: usize = 8;
+/// gradient angle in radians x (with_gradient)
+pub const GEN_GRADIENT: usize = 12;
+/// line width, feather + target size (with_line_width)
+pub const GEN_LINE: usize = 16;
+/// texture array layer x (with_layer)
+pub const GEN_LAYER: usize = 20;
+/// wireframe edge color (with_wireframe)
+pub const GEN_WIREFRAME: usize = 24;
+/// first vec4f free for custom shaders, with_gen_vec4 slot 0
+pub const GEN_USER: usize = 28;
+
 // helper for defining object updates
 #[derive(Debug)]
 pub struct RenderObjectUpdate<'a> {
@@ -201,8 +535,15 @@ pub struct RenderObjectUpdate<'a> {
   pub visible: bool,
   pub camera: Option<&'a RenderCamera>,
   pub gen_buf: [f32; 64],
+  /// bytes for each @group(1) @binding(i) buffer of a custom uniform pipeline, empty entries are skipped
   pub uniforms: Vec<&'a [u8]>,
   pub anim_transforms: Vec<[f32; 16]>,
+  pub scissor: Option<[u32; 4]>,
+  /// draw order within the pipeline, lower draws first (ties keep insertion order)
+  pub order: i32,
+  /// upload mvp + gen buffers even if they match the last update
+  pub force: bool,
+  pub wireframe: bool,
 }
 impl Default for RenderObjectUpdate<'_> {
   fn default() -> Self {
@@ -215,6 +556,10 @@ impl Default for RenderObjectUpdate<'_> {
       uniforms: Vec::new(),
       anim_transforms: Vec::new(),
       gen_buf: [0.0; 64],
+      scissor: None,
+      order: 0,
+      force: false,
+      wireframe: false,
     }
   }
 }
@@ -235,31 +580,104 @@ impl<'a> RenderObjectUpdate<'a> {
     self.scale = scale;
     self
   }
+  pub fn with_visible(mut self, visible: bool) -> Self {
+    self.visible = visible;
+    self
+  }
   pub fn with_camera(mut self, camera: &'a RenderCamera) -> Self {
     self.camera = Some(camera);
     self
   }
   pub fn with_color(mut self, color: RenderColor) -> Self {
-    self.gen_buf[0] = color.r;
-    self.gen_buf[1] = color.g;
-    self.gen_buf[2] = color.b;
-    self.gen_buf[3] = color.a;
+    let c = [color.r, color.g, color.b, color.a];
+    self.gen_buf[GEN_ALBEDO..GEN_ALBEDO + 4].copy_from_slice(&c);
+    // flat color is a gradient with equal endpoints
+    self.gen_buf[GEN_ALBEDO2..GEN_ALBEDO2 + 4].copy_from_slice(&c);
+    self
+  }
+  /// linear gradient from c0 to c1 across the object's uvs (angle 0 = left to right, 90 = bottom to top)
+  pub fn with_gradient(mut self, c0: RenderColor, c1: RenderColor, angle_deg: f32) -> Self {
+    self = self.with_color(c0);
+    self.gen_buf[GEN_ALBEDO2..GEN_ALBEDO2 + 4].copy_from_slice(&[c1.r, c1.g, c1.b, c1.a]);
+    self.gen_buf[GEN_GRADIENT] = angle_deg.to_radians();
     self
   }
   pub fn with_round_border(mut self, rect_size: Vec2, radius: f32) -> Self {
-    self.gen_buf[4] = rect_size.x;
-    self.gen_buf[5] = rect_size.y;
-    self.gen_buf[6] = radius;
+    self.gen_buf[GEN_BORDER] = rect_size.x;
+    self.gen_buf[GEN_BORDER + 1] = rect_size.y;
+    self.gen_buf[GEN_BORDER + 2] = radius;
     self
   }
+  /// line width + edge feather in pixels for ShaderType::Line, target_size is the render target size
+  pub fn with_line_width(mut self, width: f32, feather: f32, target_size: Vec2) -> Self {
+    self.gen_buf[GEN_LINE..GEN_LINE + 4].copy_from_slice(&[width, feather, target_size.x, target_size.y]);
+    self
+  }
+  /// texture array layer for ShaderType::TextureArray
+  pub fn with_layer(mut self, layer: u32) -> Self {
+    self.gen_buf[GEN_LAYER] = layer as f32;
+    self
+  }
+  /// draws the triangle edges over the filled object, needs ObjPipeline::enable_wireframe
+  pub fn with_wireframe(mut self, color: RenderColor) -> Self {
+    self.wireframe = true;
+    self.gen_buf[GEN_WIREFRAME..GEN_WIREFRAME + 4].copy_from_slice(&[color.r, color.g, color.b, color.a]);
+    self
+  }
+  /// custom shader data in the free part of gen_buf, slot 0 starts at GEN_USER.
+  /// in wgsl the slot is the Gen struct member after the 7 built in vec4fs
+  pub fn with_gen_vec4(mut self, slot: usize, value: [f32; 4]) -> Self {
+    let i = GEN_USER + slot * 4;
+    if i + 4 > self.gen_buf.len() {
+      println!("ERR: Gen buffer slot {} is out of range (max {})", slot, (self.gen_buf.len() - GEN_USER) / 4 - 1);
+      return self;
+    }
+    self.gen_buf[i..i + 4].copy_from_slice(&value);
+    self
+  }
+  /// single float at offset (from GEN_USER) in the free part of gen_buf
+  pub fn with_gen_f32(mut self, offset: usize, value: f32) -> Self {
+    let i = GEN_USER + offset;
+    if i >= self.gen_buf.len() {
+      println!("ERR: Gen buffer offset {} is out of range (max {})", offset, self.gen_buf.len() - GEN_USER - 1);
+      return self;
+    }
+    self.gen_buf[i] = value;
+    self
+  }
+  /// raw bytes for the custom uniform buffers, see RenderPipelineSetup::custom_uniforms
   pub fn with_uniforms(mut self, uniforms: Vec<&'a [u8]>) -> Self {
     self.uniforms = uniforms;
     self
   }
+  /// custom shader struct for the uniform buffer at @group(1) @binding(binding),
+  /// T must match the wgsl struct layout (see RenderPipelineSetup::custom_uniforms)
+  pub fn with_uniform_struct<T: Pod>(mut self, binding: usize, value: &'a T) -> Self {
+    if self.uniforms.len() <= binding {
+      self.uniforms.resize(binding + 1, &[]);
+    }
+    self.uniforms[binding] = bytemuck::bytes_of(value);
+    self
+  }
   pub fn with_anim(mut self, transforms: Vec<[f32; 16]>) -> Self {
     self.anim_transforms = transforms;
     self
   }
+  /// painter's order for overlapping objects in the same pipeline, higher is drawn on top
+  pub fn with_order(mut self, order: i32) -> Self {
+    self.order = order;
+    self
+  }
+  /// skip the unchanged-transform check in update_object
+  pub fn with_force(mut self, force: bool) -> Self {
+    self.force = force;
+    self
+  }
+  /// clip object to [x, y, width, height] in physical pixels of the render target
+  pub fn with_scissor(mut self, x: u32, y: u32, width: u32, height: u32) -> Self {
+    self.scissor = Some([x, y, width, height]);
+    self
+  }
 }
 
 #[derive(Debug)]
@@ -271,12 +689,48 @@ pub struct RenderObject {
   pub max_joints: usize,
   pub index_buffer: Option<Buffer>,
   pub index_count: u32,
+  pub t_buffer: Option<Buffer>,
+  pub c_buffer: Option<Buffer>,
   pub instances: u32,
   // render data
   pub bind_group0: wgpu::BindGroup,
   pub buffers0: Vec<wgpu::Buffer>,
   pub texture1: Option<Texture>,
   pub texture2: Option<Texture>,
+  // culling data
+  pub bounding_center: Vec3,
+  pub bounding_radius: f32,
+  pub in_view: bool,
+  pub scissor: Option<[u32; 4]>,
+  pub order: i32,
+  pub wireframe: bool,
+  // shadow pass data
+  pub model: [f32; 16],
+  pub shadow_bind: Option<(wgpu::BindGroup, Buffer)>,
+  // group 1 for custom uniform pipelines
+  pub custom_bind: Option<(wgpu::BindGroup, Vec<Buffer>)>,
+  // last uploaded values, None forces the next update_object to write
+  pub last_transform: Option<RenderTransform>,
+  pub last_gen: Option<[f32; 64]>,
+}
+
+// inputs of create_mvp, compared to skip redundant uploads
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderTransform {
+  pub translate: Vec3,
+  pub rotate: RenderRotation,
+  pub scale: Vec3,
+  pub camera: Option<RenderCamera>,
+}
+impl From<&RenderObjectUpdate<'_>> for RenderTransform {
+  fn from(update: &RenderObjectUpdate) -> Self {
+    Self {
+      translate: update.translate,
+      rotate: update.rotate.clone(),
+      scale: update.scale,
+      camera: update.camera.cloned(),
+    }
+  }
 }
 
 #[repr(C)]
@@ -287,11 +741,34 @@ pub struct RenderVertex {
   pub normal: [f32; 3],
 }
 
+// matches VertIn struct in debug shader
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct RenderDebugVertex {
+  pub position: [f32; 3],
+  pub color: [f32; 4],
+}
+
+// matches VertIn struct in sprite shader
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct RenderSpriteVertex {
+  pub position: [f32; 2],
+  pub uv: [f32; 2],
+  pub color: [f32; 4],
+}
+
 #[derive(Debug)]
 pub struct RenderObjectSetup<'a> {
   pub vertex_data: Vec<RenderVertex>,
   pub instances: u32,
   pub indices: Vec<u32>,
+  /// per-vertex tangents (xyz + handedness w), only used by tangent pipelines.
+  /// computed from vertex_data when left empty
+  pub tangents: Vec<[f32; 4]>,
+  /// per-vertex colors (rgba), only used by ShaderType::VertexColor.
+  /// white when the length doesn't match vertex_data
+  pub colors: Vec<[f32; 4]>,
   pub texture1: Option<Texture>,
   pub texture2: Option<Texture>,
   pub max_joints: usize,
@@ -302,6 +779,8 @@ impl Default for RenderObjectSetup<'_> {
     Self {
       vertex_data: Vec::new(),
       indices: Vec::new(),
+      tangents: Vec::new(),
+      colors: Vec::new(),
       instances: 1,
       texture1: None,
       texture2: None,
@@ -311,6 +790,37 @@ impl Default for RenderObjectSetup<'_> {
   }
 }
 
+/// options for ObjPipeline::new_with_setup, defaults match ObjPipeline::new
+#[derive(Debug, Clone, Copy)]
+pub struct RenderPipelineSetup {
+  pub blend: RenderBlendMode,
+  /// false for straight alpha textures (text, overlays),
+  /// whose soft edges can come out dithered with it on
+  pub alpha_to_coverage: bool,
+  /// extra per-vertex tangent buffer at @location(3) (vec4f, w = handedness),
+  /// for normal mapped custom shaders
+  pub tangents: bool,
+  /// LineList draws vertex pairs as lines (e.g. Primitives::grid)
+  pub topology: PrimitiveTopology,
+  /// uniform buffers a ShaderType::Custom shader reads at @group(1) @binding(0..n),
+  /// filled per object with RenderObjectUpdate::with_uniforms / with_uniform_struct
+  pub custom_uniforms: u32,
+  /// size in bytes of each custom uniform buffer, the largest struct the shader reads
+  pub custom_uniform_size: u64,
+}
+impl Default for RenderPipelineSetup {
+  fn default() -> Self {
+    Self {
+      blend: RenderBlendMode::AlphaBlend,
+      alpha_to_coverage: true,
+      tangents: false,
+      topology: PrimitiveTopology::TriangleList,
+      custom_uniforms: 0,
+      custom_uniform_size: 256,
+    }
+  }
+}
+
 // --- --- --- --- --- --- --- --- --- //
 // --- --- - PIPELINE HELPER - --- --- //
 // --- --- --- --- --- --- --- --- --- //
@@ -321,6 +831,16 @@ pub enum ShaderType<'a> {
   Default,
   FlatColor,
   Overlay,
+  /// default shader + shadow map sampling (see ObjPipeline::set_shadow_map)
+  Shadowed,
+  /// screen space lines with pixel width + feathered edges, use with Primitives::line_quads
+  Line,
+  /// overlay sampling texture 1 as a texture array (see build_texture_array),
+  /// layer picked with RenderObjectUpdate::with_layer
+  TextureArray,
+  /// flat color multiplied by a per-vertex color buffer at @location(3),
+  /// filled from RenderObjectSetup::colors
+  VertexColor,
   Custom(&'a str)
 }
 
@@ -329,6 +849,10 @@ pub fn build_shader_module(device: &Device, shader_type: ShaderType) -> ShaderMo
   let shader = match shader_type {
     ShaderType::FlatColor => include_str!("shaders/flat_color.wgsl"),
     ShaderType::Overlay => include_str!("shaders/overlay.wgsl"),
+    ShaderType::Shadowed => include_str!("shaders/shadowed.wgsl"),
+    ShaderType::Line => include_str!("shaders/line.wgsl"),
+    ShaderType::TextureArray => include_str!("shaders/texture_array.wgsl"),
+    ShaderType::VertexColor => include_str!("shaders/vertex_color.wgsl"),
     ShaderType::Custom(s) => s,
     _ => include_str!("shaders/default.wgsl")
   };
@@ -339,7 +863,88 @@ pub fn build_shader_module(device: &Device, shader_type: ShaderType) -> ShaderMo
   })
 }
 
-pub fn build_default_bind_group_layout(device: &Device) -> BindGroupLayout {
+/// group 1 layout for ShaderType::Shadowed (depth map, comparison sampler, light matrices)
+pub fn build_shadow_bind_group_layout(device: &Device) -> BindGroupLayout {
+  device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+    label: Some("shadow-bind-group-layout"),
+    entries: &[
+      BindGroupLayoutEntry {
+        binding: 0,
+        visibility: ShaderStages::FRAGMENT,
+        ty: BindingType::Texture {
+          sample_type: TextureSampleType::Depth,
+          view_dimension: TextureViewDimension::D2,
+          multisampled: false,
+        },
+        count: None,
+      },
+      BindGroupLayoutEntry {
+        binding: 1,
+        visibility: ShaderStages::FRAGMENT,
+        ty: BindingType::Sampler(SamplerBindingType::Comparison),
+        count: None,
+      },
+      BindGroupLayoutEntry {
+        binding: 2,
+        visibility: ShaderStages::FRAGMENT,
+        ty: BindingType::Buffer {
+          ty: BufferBindingType::Uniform,
+          has_dynamic_offset: false,
+          min_binding_size: None,
+        },
+        count: None,
+      },
+    ],
+  })
+}
+
+pub fn build_shadow_bind_group(
+  device: &Device,
+  layout: &BindGroupLayout,
+  shadow_map: &Texture,
+  shadow_buffer: &Buffer
+) -> BindGroup {
+  let view = shadow_map.create_view(&TextureViewDescriptor::default());
+  let sampler = device.create_sampler(&SamplerDescriptor {
+    label: Some("shadow-sampler"),
+    address_mode_u: AddressMode::ClampToEdge,
+    address_mode_v: AddressMode::ClampToEdge,
+    address_mode_w: AddressMode::ClampToEdge,
+    mag_filter: FilterMode::Linear,
+    min_filter: FilterMode::Linear,
+    compare: Some(CompareFunction::LessEqual),
+    ..Default::default()
+  });
+  device.create_bind_group(&BindGroupDescriptor {
+    label: Some("shadow-bind-group"),
+    layout,
+    entries: &[
+      BindGroupEntry { binding: 0, resource: BindingResource::TextureView(&view) },
+      BindGroupEntry { binding: 1, resource: BindingResource::Sampler(&sampler) },
+      BindGroupEntry {
+        binding: 2,
+        resource: BindingResource::Buffer(BufferBinding { buffer: shadow_buffer, offset: 0, size: None })
+      },
+    ],
+  })
+}
+
+/// depth texture for shadow maps, sampled with a comparison sampler
+pub fn build_depth_map(device: &Device, size: u32) -> Texture {
+  device.create_texture(&TextureDescriptor {
+    label: Some("shadow-map"),
+    size: Extent3d { width: size.max(1), height: size.max(1), depth_or_array_layers: 1 },
+    sample_count: 1,
+    mip_level_count: 1,
+    dimension: TextureDimension::D2,
+    format: TextureFormat::Depth32Float,
+    usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+    view_formats: &[]
+  })
+}
+
+/// texture_array declares texture 1 as texture_2d_array (see ShaderType::TextureArray)
+pub fn build_default_bind_group_layout(device: &Device, texture_array: bool) -> BindGroupLayout {
   let bind_group_entries: Vec<BindGroupLayoutEntry> = vec![
     // mvp matrix
     BindGroupLayoutEntry {
@@ -376,7 +981,7 @@ pub fn build_default_bind_group_layout(device: &Device) -> BindGroupLayout {
       visibility: ShaderStages::FRAGMENT,
       ty: BindingType::Texture {
         sample_type: TextureSampleType::Float { filterable: true },
-        view_dimension: TextureViewDimension::D2,
+        view_dimension: if texture_array { TextureViewDimension::D2Array } else { TextureViewDimension::D2 },
         multisampled: false,
       },
       count: None,
@@ -392,6 +997,28 @@ pub fn build_default_bind_group_layout(device: &Device) -> BindGroupLayout {
       },
       count: None,
     },
+    // shared lights buffer
+    BindGroupLayoutEntry {
+      binding: 5,
+      visibility: ShaderStages::FRAGMENT,
+      ty: BindingType::Buffer {
+        ty: BufferBindingType::Uniform,
+        has_dynamic_offset: false,
+        min_binding_size: None,
+      },
+      count: None,
+    },
+    // joint matrices (array<mat4x4f, max_joints>)
+    BindGroupLayoutEntry {
+      binding: 6,
+      visibility: ShaderStages::VERTEX,
+      ty: BindingType::Buffer {
+        ty: BufferBindingType::Uniform,
+        has_dynamic_offset: false,
+        min_binding_size: None,
+      },
+      count: None,
+    },
   ];
   device.create_bind_group_layout(&BindGroupLayoutDescriptor {
     label: Some("bind-group0-layout"),
@@ -399,11 +1026,16 @@ pub fn build_default_bind_group_layout(device: &Device) -> BindGroupLayout {
   })
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn build_default_bind_group(
   device: &Device,
   pipeline: &RenderPipeline,
+  light_buffer: &Buffer,
   texture1: &Option<Texture>,
-  texture2: &Option<Texture>
+  texture2: &Option<Texture>,
+  anisotropy: u16,
+  texture_array: bool,
+  max_joints: usize
 ) -> (BindGroup, Vec<Buffer>) {
   let limits = Limits::default();
   let min_stride = limits.min_uniform_buffer_offset_alignment;
@@ -422,6 +1054,14 @@ pub fn build_default_bind_group(
     mapped_at_creation: false,
   });
 
+  // create joints buffer (one matrix minimum so the binding is never empty)
+  let joints_buffer = device.create_buffer(&BufferDescriptor {
+    label: Some("joints-uniform-buffer"),
+    size: (std::mem::size_of::<[f32; 16]>() * max_joints.max(1)) as u64,
+    usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+    mapped_at_creation: false,
+  });
+
   // import textures
   let texture1_view: TextureView;
   let texture2_view: TextureView;
@@ -441,10 +1081,14 @@ pub fn build_default_bind_group(
     usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
     view_formats: &[]
   });
+  let texture1_desc = TextureViewDescriptor {
+    dimension: if texture_array { Some(TextureViewDimension::D2Array) } else { None },
+    ..Default::default()
+  };
   if let Some(tx) = texture1 {
-    texture1_view = tx.create_view(&TextureViewDescriptor::default());
+    texture1_view = tx.create_view(&texture1_desc);
   } else {
-    texture1_view = ftexture.create_view(&TextureViewDescriptor::default());
+    texture1_view = ftexture.create_view(&texture1_desc);
   }
   if let Some(tx) = texture2 {
     texture2_view = tx.create_view(&TextureViewDescriptor::default());
@@ -452,15 +1096,21 @@ pub fn build_default_bind_group(
     texture2_view = ftexture.create_view(&TextureViewDescriptor::default());
   }
 
-  // create sampler
+  // create sampler (filter between mip levels if any input texture has them)
+  let has_mips = [texture1, texture2].iter().any(|tx| {
+    tx.as_ref().is_some_and(|t| t.mip_level_count() > 1)
+  });
+  // anisotropic sampling requires linear filtering everywhere
+  let min_filter = if has_mips || anisotropy > 1 { FilterMode::Linear } else { FilterMode::Nearest };
   let sampler = device.create_sampler(&SamplerDescriptor {
     label: Some("texture-sampler"),
     address_mode_u: AddressMode::ClampToEdge,
     address_mode_v: AddressMode::ClampToEdge,
     address_mode_w: AddressMode::ClampToEdge,
     mag_filter: FilterMode::Linear,
-    min_filter: FilterMode::Nearest,
-    mipmap_filter: FilterMode::Nearest,
+    min_filter,
+    mipmap_filter: min_filter,
+    anisotropy_clamp: anisotropy.clamp(1, 16),
     ..Default::default()
   });
   // create bind entries
@@ -489,6 +1139,18 @@ pub fn build_default_bind_group(
       binding: 4,
       resource: BindingResource::TextureView(&texture2_view)
     },
+    BindGroupEntry {
+      binding: 5,
+      resource: BindingResource::Buffer(BufferBinding {
+        buffer: light_buffer, offset: 0, size: None
+      })
+    },
+    BindGroupEntry {
+      binding: 6,
+      resource: BindingResource::Buffer(BufferBinding {
+        buffer: &joints_buffer, offset: 0, size: None
+      })
+    },
   ];
 
   // create bind group
@@ -499,10 +1161,307 @@ pub fn build_default_bind_group(
   });
 
   // create output
-  (bind_group, vec![mvp_buffer, gen_buffer])
+  (bind_group, vec![mvp_buffer, gen_buffer, joints_buffer])
+}
+
+/// creates shared lights buffer (defaults to no lights)
+pub fn build_light_buffer(device: &Device) -> Buffer {
+  device.create_buffer(&BufferDescriptor {
+    label: Some("lights-uniform-buffer"),
+    size: std::mem::size_of::<RenderLightsC>() as u64,
+    usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+    mapped_at_creation: false,
+  })
+}
+
+/// creates a texture that can be rendered to and sampled from
+pub fn build_render_texture(device: &Device, width: u32, height: u32, format: TextureFormat) -> Texture {
+  let max_size = device.limits().max_texture_dimension_2d;
+  if width > max_size || height > max_size {
+    println!("ERR: Render texture {}x{} exceeds the device limit of {}px, clamping", width, height, max_size);
+  }
+  let (width, height) = (width.min(max_size), height.min(max_size));
+  device.create_texture(&TextureDescriptor {
+    label: Some("render-texture"),
+    size: Extent3d { width, height, depth_or_array_layers: 1 },
+    sample_count: 1,
+    mip_level_count: 1,
+    dimension: TextureDimension::D2,
+    format,
+    usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_SRC,
+    view_formats: &[]
+  })
+}
+
+/// creates an rgba8 texture from raw pixel data, optionally filling a full mip chain.
+/// images larger than the device's max_texture_dimension_2d are downscaled to fit
+pub fn build_texture(
+  device: &Device,
+  queue: &Queue,
+  width: u32,
+  height: u32,
+  data: &[u8],
+  with_mipmaps: bool
+) -> Texture {
+  if data.len() < width as usize * height as usize * 4 {
+    println!("ERR: Texture data is smaller than {}x{}, using a blank 1x1 texture", width, height);
+    return build_texture(device, queue, 1, 1, &[0; 4], false);
+  }
+  let max_size = device.limits().max_texture_dimension_2d;
+  let scaled;
+  let (width, height, data) = if width > max_size || height > max_size {
+    println!("ERR: Texture {}x{} exceeds the device limit of {}px, downscaling", width, height, max_size);
+    scaled = downscale_rgba(width, height, data, max_size);
+    (scaled.0, scaled.1, scaled.2.as_slice())
+  } else {
+    (width, height, data)
+  };
+  let size = Extent3d { width, height, depth_or_array_layers: 1 };
+  // floor(log2(max(w,h))) + 1
+  let mip_level_count = if with_mipmaps { 32 - width.max(height).max(1).leading_zeros() } else { 1 };
+  let mut usage = TextureUsages::COPY_DST | TextureUsages::TEXTURE_BINDING;
+  if mip_level_count > 1 { usage |= TextureUsages::RENDER_ATTACHMENT; }
+  let texture = device.create_texture(&TextureDescriptor {
+    label: Some("input-texture"),
+    size,
+    sample_count: 1,
+    mip_level_count,
+    dimension: TextureDimension::D2,
+    format: TextureFormat::Rgba8Unorm,
+    usage,
+    view_formats: &[]
+  });
+  update_texture(queue, &texture, data);
+  if mip_level_count > 1 {
+    generate_mipmaps(device, queue, &texture);
+  }
+
+  texture
+}
+
+/// overwrites mip 0 of an rgba8 texture with raw pixel data of the same size
+pub fn update_texture(queue: &Queue, texture: &Texture, data: &[u8]) {
+  let size = Extent3d { width: texture.width(), height: texture.height(), depth_or_array_layers: 1 };
+  if data.len() < (4 * size.width * size.height) as usize {
+    println!("ERR: Texture data is smaller than {}x{}, skipping update", size.width, size.height);
+    return;
+  }
+  queue.write_texture(
+    TexelCopyTextureInfo {
+      texture,
+      mip_level: 0,
+      origin: Origin3d::ZERO,
+      aspect: TextureAspect::All,
+    },
+    data,
+    TexelCopyBufferLayout {
+      offset: 0,
+      bytes_per_row: Some(4 * size.width),
+      rows_per_image: Some(size.height),
+    },
+    size,
+  );
 }
 
-pub fn build_primitive_state(cull_mode: Option<Face>, polygon_mode: PolygonMode) -> wgpu::PrimitiveState {
+/// creates an rgba8 texture array with one layer per entry of raw pixel data (e.g. animation frames),
+/// all layers must be width x height. oversized layers are downscaled like build_texture
+pub fn build_texture_array(device: &Device, queue: &Queue, width: u32, height: u32, layers: &[&[u8]]) -> Texture {
+  let limits = device.limits();
+  let max_size = limits.max_texture_dimension_2d;
+  let scaled: Vec<Vec<u8>>;
+  let scaled_layers: Vec<&[u8]>;
+  let (width, height, layers) = if width > max_size || height > max_size {
+    println!("ERR: Texture array {}x{} exceeds the device limit of {}px, downscaling", width, height, max_size);
+    let (w, h) = downscaled_size(width, height, max_size);
+    // layers that are too small stay empty + get skipped below
+    scaled = layers.iter().map(|l| {
+      if l.len() < width as usize * height as usize * 4 { return Vec::new(); }
+      downscale_rgba(width, height, l, max_size).2
+    }).collect();
+    scaled_layers = scaled.iter().map(|s| s.as_slice()).collect();
+    (w, h, scaled_layers.as_slice())
+  } else {
+    (width, height, layers)
+  };
+  let max_layers = limits.max_texture_array_layers as usize;
+  if layers.len() > max_layers {
+    println!("ERR: Texture array has {} layers, the device limit is {}, extra layers are dropped", layers.len(), max_layers);
+  }
+  let layers = &layers[..layers.len().min(max_layers)];
+  let size = Extent3d { width, height, depth_or_array_layers: layers.len().max(1) as u32 };
+  let texture = device.create_texture(&TextureDescriptor {
+    label: Some("input-texture-array"),
+    size,
+    sample_count: 1,
+    mip_level_count: 1,
+    dimension: TextureDimension::D2,
+    format: TextureFormat::Rgba8Unorm,
+    usage: TextureUsages::COPY_DST | TextureUsages::TEXTURE_BINDING,
+    view_formats: &[]
+  });
+  let layer_bytes = (4 * width * height) as usize;
+  for (i, data) in layers.iter().enumerate() {
+    if data.len() < layer_bytes {
+      println!("ERR: Texture array layer {} is smaller than {}x{}, skipping", i, width, height);
+      continue;
+    }
+    queue.write_texture(
+      TexelCopyTextureInfo {
+        texture: &texture,
+        mip_level: 0,
+        origin: Origin3d { x: 0, y: 0, z: i as u32 },
+        aspect: TextureAspect::All,
+      },
+      &data[..layer_bytes],
+      TexelCopyBufferLayout {
+        offset: 0,
+        bytes_per_row: Some(4 * width),
+        rows_per_image: Some(height),
+      },
+      Extent3d { width, height, depth_or_array_layers: 1 },
+    );
+  }
+
+  texture
+}
+
+/// box filters rgba8 pixels so neither side exceeds max_size, keeping the aspect ratio.
+/// returns the new (width, height, data), data is copied unchanged if it already fits
+pub fn downscale_rgba(width: u32, height: u32, data: &[u8], max_size: u32) -> (u32, u32, Vec<u8>) {
+  let (dw, dh) = downscaled_size(width, height, max_size);
+  if (dw, dh) == (width, height) || data.len() < width as usize * height as usize * 4 {
+    return (width, height, data.to_vec());
+  }
+  let mut out = vec![0; (dw * dh * 4) as usize];
+  for dy in 0..dh {
+    // source rows covered by this pixel
+    let y0 = (dy as u64 * height as u64 / dh as u64) as u32;
+    let y1 = (((dy + 1) as u64 * height as u64).div_ceil(dh as u64) as u32).max(y0 + 1);
+    for dx in 0..dw {
+      let x0 = (dx as u64 * width as u64 / dw as u64) as u32;
+      let x1 = (((dx + 1) as u64 * width as u64).div_ceil(dw as u64) as u32).max(x0 + 1);
+      let mut sum = [0u32; 4];
+      for sy in y0..y1 {
+        for sx in x0..x1 {
+          let i = ((sy * width + sx) * 4) as usize;
+          for (s, &c) in sum.iter_mut().zip(&data[i..i + 4]) { *s += c as u32; }
+        }
+      }
+      let n = (y1 - y0) * (x1 - x0);
+      let o = ((dy * dw + dx) * 4) as usize;
+      for (dst, s) in out[o..o + 4].iter_mut().zip(sum) { *dst = ((s + n / 2) / n) as u8; }
+    }
+  }
+  (dw, dh, out)
+}
+
+/// (width, height) scaled down so neither side exceeds max_size, the size downscale_rgba produces
+pub fn downscaled_size(width: u32, height: u32, max_size: u32) -> (u32, u32) {
+  let longest = width.max(height);
+  if longest <= max_size || max_size == 0 { return (width, height); }
+  let scale = max_size as f64 / longest as f64;
+  let dw = ((width as f64 * scale).round() as u32).clamp(1, max_size);
+  let dh = ((height as f64 * scale).round() as u32).clamp(1, max_size);
+  (dw, dh)
+}
+
+/// fills mip levels 1.. of a texture by repeatedly downsampling the previous level
+pub fn generate_mipmaps(device: &Device, queue: &Queue, texture: &Texture) {
+  let mip_count = texture.mip_level_count();
+  if mip_count < 2 { return; }
+  let shader = device.create_shader_module(ShaderModuleDescriptor {
+    label: Some("mipmap-shader"),
+    source: ShaderSource::Wgsl(format!("{}\n{}", FULLSCREEN_VERTEX_SHADER, include_str!("shaders/mipmap.wgsl")).into()),
+  });
+  let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+    label: Some("mipmap-pipeline"),
+    layout: None,
+    vertex: VertexState {
+      module: &shader,
+      entry_point: Some("vertex_main"),
+      buffers: &[],
+      compilation_options: Default::default(),
+    },
+    fragment: Some(FragmentState {
+      module: &shader,
+      entry_point: Some("fragment_main"),
+      targets: &[Some(ColorTargetState {
+        format: texture.format(),
+        blend: None,
+        write_mask: ColorWrites::ALL,
+      })],
+      compilation_options: Default::default(),
+    }),
+    primitive: PrimitiveState::default(),
+    depth_stencil: None,
+    multisample: MultisampleState::default(),
+    multiview: None,
+    cache: None,
+  });
+  let sampler = device.create_sampler(&SamplerDescriptor {
+    label: Some("mipmap-sampler"),
+    address_mode_u: AddressMode::ClampToEdge,
+    address_mode_v: AddressMode::ClampToEdge,
+    address_mode_w: AddressMode::ClampToEdge,
+    mag_filter: FilterMode::Linear,
+    min_filter: FilterMode::Linear,
+    mipmap_filter: FilterMode::Nearest,
+    ..Default::default()
+  });
+  // one view per mip level
+  let views: Vec<TextureView> = (0..mip_count).map(|level| {
+    texture.create_view(&TextureViewDescriptor {
+      label: Some("mipmap-view"),
+      base_mip_level: level,
+      mip_level_count: Some(1),
+      ..Default::default()
+    })
+  }).collect();
+
+  let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+    label: Some("mipmap-encoder")
+  });
+  for level in 1..mip_count as usize {
+    let bind_group = device.create_bind_group(&BindGroupDescriptor {
+      label: Some("mipmap-bind-group"),
+      layout: &pipeline.get_bind_group_layout(0),
+      entries: &[
+        BindGroupEntry { binding: 0, resource: BindingResource::Sampler(&sampler) },
+        BindGroupEntry { binding: 1, resource: BindingResource::TextureView(&views[level - 1]) },
+      ]
+    });
+    let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+      label: Some("mipmap-pass"),
+      color_attachments: &[Some(RenderPassColorAttachment {
+        view: &views[level],
+        resolve_target: None,
+        ops: Operations {
+          load: LoadOp::Clear(wgpu::Color::TRANSPARENT),
+          store: StoreOp::Store,
+        },
+      })],
+      depth_stencil_attachment: None,
+      timestamp_writes: None,
+      occlusion_query_set: None,
+    });
+    pass.set_pipeline(&pipeline);
+    pass.set_bind_group(0, &bind_group, &[]);
+    pass.draw(0..3, 0..1);
+  }
+  queue.submit(std::iter::once(encoder.finish()));
+}
+
+pub fn build_primitive_state(device: &Device, cull_mode: Option<Face>, polygon_mode: PolygonMode) -> wgpu::PrimitiveState {
+  // line + point modes are optional device features
+  let required = match polygon_mode {
+    PolygonMode::Line => Features::POLYGON_MODE_LINE,
+    PolygonMode::Point => Features::POLYGON_MODE_POINT,
+    _ => Features::empty(),
+  };
+  let polygon_mode = if device.features().contains(required) { polygon_mode } else {
+    println!("ERR: {:?} polygon mode is not supported by this device, using Fill", polygon_mode);
+    PolygonMode::Fill
+  };
   // translate polygon mode
   let topology: PrimitiveTopology = match polygon_mode {
     PolygonMode::Line => PrimitiveTopology::LineList,
@@ -517,6 +1476,79 @@ pub fn build_primitive_state(cull_mode: Option<Face>, polygon_mode: PolygonMode)
   }
 }
 
+/// calculates bounding sphere (center, radius) of vertices in model space
+pub fn bounding_sphere(vertices: &[RenderVertex]) -> (Vec3, f32) {
+  if vertices.is_empty() { return (Vec3::zero(), 0.0); }
+  let mut min = Vec3::from_array(vertices[0].position);
+  let mut max = min;
+  for v in vertices {
+    min.x = f32::min(min.x, v.position[0]);
+    min.y = f32::min(min.y, v.position[1]);
+    min.z = f32::min(min.z, v.position[2]);
+    max.x = f32::max(max.x, v.position[0]);
+    max.y = f32::max(max.y, v.position[1]);
+    max.z = f32::max(max.z, v.position[2]);
+  }
+  let center = (min + max) * 0.5;
+  let mut radius: f32 = 0.0;
+  for v in vertices {
+    let d = (Vec3::from_array(v.position) - center).magnitude();
+    if d > radius { radius = d; }
+  }
+  (center, radius)
+}
+
+/// checks if bounding sphere is at least partially within camera frustum
+pub fn sphere_in_frustum(mvp: &[f32; 48], center: Vec3, radius: f32, scale: Vec3) -> bool {
+  let mut model = [0.0; 16];
+  let mut view = [0.0; 16];
+  let mut proj = [0.0; 16];
+  model.copy_from_slice(&mvp[0..16]);
+  view.copy_from_slice(&mvp[16..32]);
+  proj.copy_from_slice(&mvp[32..48]);
+  // move sphere into world space
+  let c = Mat4::from_col_major(model).multiply_vec4(&Vec4::new(center.x, center.y, center.z, 1.0));
+  let max_scale = f32::max(f32::abs(scale.x), f32::max(f32::abs(scale.y), f32::abs(scale.z)));
+  let r = radius * max_scale;
+  // extract frustum planes from view-projection matrix
+  let vp = Mat4::from_col_major(Mat4::multiply(&proj, &view));
+  let r0 = Vec4::from_array(vp.row(0));
+  let r1 = Vec4::from_array(vp.row(1));
+  let r2 = Vec4::from_array(vp.row(2));
+  let r3 = Vec4::from_array(vp.row(3));
+  let planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r2, r3 - r2];
+  for p in planes {
+    let n = Vec3::new(p.x, p.y, p.z);
+    let n_len = n.magnitude();
+    if n_len < 0.00001 { continue; }
+    let d = n.dot(Vec3::new(c.x, c.y, c.z)) + p.w;
+    if d < -r * n_len { return false; }
+  }
+  true
+}
+
+/// (view, projection) matrices of a camera
+pub fn camera_matrices(cam: &RenderCamera) -> ([f32; 16], [f32; 16]) {
+  let view = Mat4::look_at(&cam.position, &cam.look_at, &cam.up);
+  let w2 = cam.target_size.x / 2.0;
+  let h2 = cam.target_size.y / 2.0;
+  let proj = match cam.cam_type {
+    1 => Mat4::ortho(-w2 / cam.zoom, w2 / cam.zoom, h2 / cam.zoom, -h2 / cam.zoom, cam.near, cam.far),
+    2 => Mat4::perspective(cam.zoomed_fov_y(), w2/h2, cam.near, cam.far),
+    _ => Mat4::identity().as_col_major_array()
+  };
+  (view, proj)
+}
+
+/// uploads a camera's view + projection matrices to a 32 float uniform buffer
+pub fn write_camera_buffer(queue: &Queue, buffer: &Buffer, cam: &RenderCamera) {
+  let (view, proj) = camera_matrices(cam);
+  let mut data = [0.0; 32];
+  data[..16].copy_from_slice(&view);
+  data[16..].copy_from_slice(&proj);
+  queue.write_buffer(buffer, 0, bytemuck::cast_slice(&data));
+}
+
 /// creates MVP matrix
 pub fn create_mvp(update: &RenderObjectUpdate) -> [f32; 48] {
   let cam = match update.camera {
@@ -531,18 +1563,8 @@ pub fn create_mvp(update: &RenderObjectUpdate) -> [f32; 48] {
   };
   let model_s = Mat4::scale(update.scale.x, update.scale.y, update.scale.z);
   let model = Mat4::multiply(&model_t, &Mat4::multiply(&model_s, &model_r));
-  // view matrix
-  let view_t = Mat4::translate(-cam.position.x, -cam.position.y, -cam.position.z);
-  let view_r = Mat4::view_rot(&cam.position, &cam.look_at, &cam.up);
-  let view = Mat4::multiply(&view_r, &view_t);
-  // projection matrix
-  let w2 = cam.target_size.x / 2.0;
-  let h2 = cam.target_size.y / 2.0;
-  let proj = match cam.cam_type {
-    1 => Mat4::ortho(-w2, w2, h2, -h2, cam.near, cam.far),
-    2 => Mat4::perspective(cam.fov_y, w2/h2, cam.near, cam.far),
-    _ => Mat4::identity().as_col_major_array()
-  };
+  // view + projection matrix
+  let (view, proj) = camera_matrices(cam);
   // merge together
   let mut mvp: [f32; 48] = [0.0; 48]; // 16 * 3 = 48
   for i in 0..48 {
@@ -552,3 +1574,210 @@ pub fn create_mvp(update: &RenderObjectUpdate) -> [f32; 48] {
   }
   mvp
 }
+
+/// flattens up to max_joints matrices for the joints buffer (buffers0[2])
+pub fn pack_joint_transforms(max_joints: usize, transforms: &[[f32; 16]]) -> Vec<f32> {
+  transforms.iter().take(max_joints).flatten().copied().collect()
+}
+
+#[cfg(test)]
+mod color_tests {
+  use super::*;
+
+  #[test]
+  fn hsv_round_trip() {
+    for &(h, s, v) in &[(0.0, 1.0, 1.0), (0.25, 0.5, 0.8), (0.6, 0.3, 0.4), (0.9, 0.75, 0.6)] {
+      let (h2, s2, v2) = RenderColor::hsv(h, s, v).to_hsv();
+      assert!(f32::abs(h - h2) < 1e-4 && f32::abs(s - s2) < 1e-4 && f32::abs(v - v2) < 1e-4);
+    }
+  }
+
+  #[test]
+  fn hsl_round_trip() {
+    for &(h, s, l) in &[(0.1, 1.0, 0.5), (0.4, 0.5, 0.3), (0.7, 0.2, 0.8)] {
+      let (h2, s2, l2) = RenderColor::hsl(h, s, l).to_hsl();
+      assert!(f32::abs(h - h2) < 1e-4 && f32::abs(s - s2) < 1e-4 && f32::abs(l - l2) < 1e-4);
+    }
+  }
+
+  #[test]
+  fn lerp_endpoints() {
+    let c = RenderColor::lerp(RenderColor::RED, RenderColor::BLUE, 0.0);
+    assert!(f32::abs(c.r - 1.0) < 1e-4 && f32::abs(c.b) < 1e-4);
+    let c = RenderColor::lerp(RenderColor::RED, RenderColor::BLUE, 1.0);
+    assert!(f32::abs(c.r) < 1e-4 && f32::abs(c.b - 1.0) < 1e-4);
+  }
+
+  #[test]
+  fn srgb_to_linear_upload() {
+    // sRGB mid gray is ~21.6% linear
+    let c = RenderColor::from_srgb_u8(128, 128, 128, 128);
+    assert!(f32::abs(c.r - 0.2158605) < 1e-4 && f32::abs(c.b - 0.2158605) < 1e-4);
+    assert!(f32::abs(c.a - 128.0 / 255.0) < 1e-6);
+    assert_eq!(RenderColor::from_srgb_u8(0, 0, 0, 255), RenderColor::BLACK);
+    let w = RenderColor::from_srgb_u8(255, 255, 255, 255);
+    assert!(f32::abs(w.g - 1.0) < 1e-6);
+  }
+}
+
+#[cfg(test)]
+mod camera_tests {
+  use super::*;
+
+  #[test]
+  fn world_to_screen_ortho() {
+    let cam = RenderCamera::new_ortho(1.0, 1000.0, vec2f!(100.0, 100.0));
+    let size = vec2f!(100.0, 100.0);
+    let center = cam.world_to_screen(vec3f!(0.0, 0.0, 0.0), size).unwrap();
+    assert!((center - vec2f!(50.0, 50.0)).magnitude() < 1e-3);
+    // world y up, screen y down
+    let corner = cam.world_to_screen(vec3f!(25.0, 25.0, 0.0), size).unwrap();
+    assert!((corner - vec2f!(75.0, 25.0)).magnitude() < 1e-3);
+    assert_eq!(cam.world_to_screen(vec3f!(0.0, 0.0, 200.0), size), None);
+  }
+
+  #[test]
+  fn world_to_screen_persp() {
+    let cam = RenderCamera::new_persp(45.0, 1.0, 1000.0, vec2f!(200.0, 100.0));
+    let size = vec2f!(200.0, 100.0);
+    let center = cam.world_to_screen(vec3f!(0.0, 0.0, -50.0), size).unwrap();
+    assert!((center - vec2f!(100.0, 50.0)).magnitude() < 1e-3);
+    let right = cam.world_to_screen(vec3f!(10.0, 0.0, -50.0), size).unwrap();
+    assert!(right.x > 100.0 && f32::abs(right.y - 50.0) < 1e-3);
+    assert_eq!(cam.world_to_screen(vec3f!(0.0, 0.0, 5.0), size), None);
+  }
+
+  #[test]
+  fn fit_aabb_persp() {
+    let size = vec2f!(200.0, 100.0);
+    let mut cam = RenderCamera::new_persp(45.0, 1.0, 100.0, size);
+    let aabb = Aabb3::new(vec3f!(400.0, -50.0, -50.0), vec3f!(600.0, 50.0, 50.0));
+    cam.fit_aabb(&aabb);
+    assert_eq!(cam.look_at, aabb.center());
+    for corner in [aabb.min, aabb.max, vec3f!(400.0, 50.0, 50.0), vec3f!(600.0, -50.0, 50.0)] {
+      let p = cam.world_to_screen(corner, size).unwrap();
+      assert!(p.x >= 0.0 && p.x <= size.x && p.y >= 0.0 && p.y <= size.y);
+    }
+    assert!(cam.far > Vec3::distance(cam.position, aabb.center()));
+  }
+
+  #[test]
+  fn camera_zoom() {
+    let size = vec2f!(100.0, 100.0);
+    let mut ortho = RenderCamera::new_ortho(1.0, 1000.0, size);
+    ortho.set_zoom(2.0);
+    let p = ortho.world_to_screen(vec3f!(25.0, 0.0, 0.0), size).unwrap();
+    assert!(f32::abs(p.x - 100.0) < 1e-3);
+    let mut persp = RenderCamera::new_persp(90.0, 1.0, 1000.0, size);
+    persp.position = vec3f!(0.0, 0.0, 10.0);
+    let before = persp.world_to_screen(vec3f!(2.0, 0.0, 0.0), size).unwrap();
+    persp.set_zoom(2.0);
+    let after = persp.world_to_screen(vec3f!(2.0, 0.0, 0.0), size).unwrap();
+    assert!(f32::abs((after.x - 50.0) - (before.x - 50.0) * 2.0) < 1e-3);
+  }
+
+  #[test]
+  fn camera_orbit() {
+    let mut cam = RenderCamera::new_persp(45.0, 1.0, 1000.0, vec2f!(100.0, 100.0));
+    cam.position = vec3f!(0.0, 0.0, 10.0);
+    cam.orbit(90.0, 0.0);
+    assert!(Vec3::distance(cam.position, vec3f!(10.0, 0.0, 0.0)) < 1e-3);
+    cam.orbit(0.0, 45.0);
+    assert!(cam.position.y > 0.0 && f32::abs(cam.position.magnitude() - 10.0) < 1e-3);
+    // clamped short of the pole
+    cam.orbit(0.0, 180.0);
+    assert!(cam.position.y < 10.0 && cam.position.x > 0.0);
+  }
+}
+#[cfg(test)]
+mod joints_tests {
+  use super::*;
+
+  #[test]
+  fn joints_pack_up_to_max() {
+    let transforms = vec![Mat4::identity().as_col_major_array(); 3];
+    // only max_joints matrices fit in the joints buffer
+    let packed = pack_joint_transforms(2, &transforms);
+    assert_eq!(packed.len(), 32);
+    assert_eq!(packed[0], 1.0);
+    assert!(pack_joint_transforms(4, &[]).is_empty());
+  }
+}
+#[cfg(test)]
+mod texture_tests {
+  use super::*;
+
+  #[test]
+  fn downscale_box_filter() {
+    // left 2x2 block white, right 2x2 block black
+    let mut data = vec![0u8; 4 * 2 * 4];
+    for y in 0..2 {
+      for x in 0..2 {
+        let i = (y * 4 + x) * 4;
+        data[i..i + 4].copy_from_slice(&[255; 4]);
+      }
+    }
+    let (w, h, out) = downscale_rgba(4, 2, &data, 2);
+    assert_eq!((w, h), (2, 1));
+    assert_eq!(out, vec![255, 255, 255, 255, 0, 0, 0, 0]);
+    // already within the limit
+    assert_eq!(downscale_rgba(4, 2, &data, 4), (4, 2, data.clone()));
+    // odd sizes keep the aspect ratio and stay within the limit
+    let (w, h, out) = downscale_rgba(9, 3, &[128; 9 * 3 * 4], 4);
+    assert_eq!((w, h), (4, 1));
+    assert!(out.iter().all(|&c| c == 128));
+    // short data is returned as is instead of being read out of bounds
+    assert_eq!(downscale_rgba(8, 8, &[0; 16], 4), (8, 8, vec![0; 16]));
+  }
+
+  #[test]
+  fn downscale_to_existing_size() {
+    // re-running the downscale with the result's longest side lands on the same size (Canvas2D::commit)
+    for (w, h, max) in [(9, 3, 4), (4097, 100, 4096), (3000, 5000, 2048), (7, 7, 2)] {
+      let (dw, dh) = downscaled_size(w, h, max);
+      assert!(dw <= max && dh <= max);
+      assert_eq!(downscaled_size(w, h, dw.max(dh)), (dw, dh));
+    }
+  }
+}
+#[cfg(test)]
+mod gen_buffer_tests {
+  use super::*;
+
+  #[test]
+  fn gen_user_slots_keep_builtins() {
+    let update = RenderObjectUpdate::default()
+      .with_color(RenderColor::RED)
+      .with_layer(3)
+      .with_gen_vec4(0, [1.0, 2.0, 3.0, 4.0])
+      .with_gen_f32(5, 9.0)
+      // out of range writes are ignored
+      .with_gen_vec4(10, [7.0; 4]);
+    assert_eq!(update.gen_buf[GEN_ALBEDO..GEN_ALBEDO + 4], [1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(update.gen_buf[GEN_LAYER], 3.0);
+    assert_eq!(update.gen_buf[GEN_USER..GEN_USER + 4], [1.0, 2.0, 3.0, 4.0]);
+    assert_eq!(update.gen_buf[GEN_USER + 5], 9.0);
+    assert!(update.gen_buf[60..64].iter().all(|&v| v == 0.0));
+  }
+
+  #[test]
+  fn custom_uniform_struct() {
+    #[repr(C)]
+    #[derive(Clone, Copy, Pod, Zeroable)]
+    struct Custom {
+      tint: [f32; 4],
+      count: u32,
+      strength: f32,
+      _pad: [f32; 2],
+    }
+    let custom = Custom { tint: [0.5; 4], count: 3, strength: 2.0, _pad: [0.0; 2] };
+    let update = RenderObjectUpdate::default().with_uniform_struct(1, &custom);
+    // binding 0 is left empty and skipped on upload
+    assert_eq!(update.uniforms.len(), 2);
+    assert!(update.uniforms[0].is_empty());
+    assert_eq!(update.uniforms[1], bytemuck::bytes_of(&custom));
+    assert_eq!(update.uniforms[1].len(), 32);
+    // the gen buffer is untouched
+    assert!(update.gen_buf.iter().all(|&v| v == 0.0));
+  }
+}
@@ -0,0 +1,217 @@
+use wgpu::{
+  vertex_attr_array, AddressMode, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout,
+  BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, BlendState, Buffer,
+  BufferAddress, BufferBindingType, BufferDescriptor, BufferUsages, ColorTargetState, ColorWrites, Device,
+  FilterMode, FragmentState, IndexFormat, MultisampleState, PipelineCompilationOptions, PipelineLayoutDescriptor,
+  PrimitiveState, Queue, RenderPass, RenderPipeline, RenderPipelineDescriptor, Sampler, SamplerBindingType,
+  SamplerDescriptor, ShaderModuleDescriptor, ShaderSource, ShaderStages, Texture, TextureFormat,
+  TextureSampleType, TextureViewDescriptor, TextureViewDimension, VertexBufferLayout, VertexState, VertexStepMode
+};
+
+use crate::utils::Vec4;
+use super::{write_camera_buffer, RenderCamera, RenderColor, RenderSpriteVertex};
+
+/// draws many textured quads from one atlas texture in a single draw call
+#[derive(Debug)]
+pub struct SpriteBatch {
+  pipeline: RenderPipeline,
+  layout: BindGroupLayout,
+  bind_group: BindGroup,
+  sampler: Sampler,
+  camera_buffer: Buffer,
+  vertex_buffer: Buffer,
+  index_buffer: Buffer,
+  max_sprites: usize,
+  vertices: Vec<RenderSpriteVertex>,
+  // sprite count of the last flush
+  drawn: u32,
+}
+impl SpriteBatch {
+  pub fn new(device: &Device, queue: &Queue, target_format: TextureFormat, atlas: &Texture, max_sprites: usize) -> Self {
+    let shader_mod = device.create_shader_module(ShaderModuleDescriptor {
+      label: Some("sprite-shader"),
+      source: ShaderSource::Wgsl(include_str!("shaders/sprite.wgsl").into()),
+    });
+    let layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+      label: Some("sprite-bind-group-layout"),
+      entries: &[
+        BindGroupLayoutEntry {
+          binding: 0,
+          visibility: ShaderStages::VERTEX,
+          ty: BindingType::Buffer {
+            ty: BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+          },
+          count: None,
+        },
+        BindGroupLayoutEntry {
+          binding: 1,
+          visibility: ShaderStages::FRAGMENT,
+          ty: BindingType::Sampler(SamplerBindingType::Filtering),
+          count: None,
+        },
+        BindGroupLayoutEntry {
+          binding: 2,
+          visibility: ShaderStages::FRAGMENT,
+          ty: BindingType::Texture {
+            sample_type: TextureSampleType::Float { filterable: true },
+            view_dimension: TextureViewDimension::D2,
+            multisampled: false,
+          },
+          count: None,
+        },
+      ],
+    });
+    let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+      label: Some("sprite-pipeline-layout"),
+      bind_group_layouts: &[&layout],
+      push_constant_ranges: &[]
+    });
+    let vertex_attr = vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x4];
+    let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+      label: Some("sprite-pipeline"),
+      layout: Some(&pipeline_layout),
+      vertex: VertexState {
+        module: &shader_mod,
+        entry_point: Some("vertex_main"),
+        buffers: &[VertexBufferLayout {
+          array_stride: std::mem::size_of::<RenderSpriteVertex>() as BufferAddress,
+          step_mode: VertexStepMode::Vertex,
+          attributes: &vertex_attr,
+        }],
+        compilation_options: PipelineCompilationOptions::default(),
+      },
+      fragment: Some(FragmentState {
+        module: &shader_mod,
+        entry_point: Some("fragment_main"),
+        targets: &[Some(ColorTargetState {
+          format: target_format,
+          blend: Some(BlendState::ALPHA_BLENDING),
+          write_mask: ColorWrites::ALL
+        })],
+        compilation_options: PipelineCompilationOptions::default(),
+      }),
+      multisample: MultisampleState::default(),
+      depth_stencil: None,
+      // no culling, quads may be flipped with negative sizes
+      primitive: PrimitiveState::default(),
+      multiview: None,
+      cache: None,
+    });
+
+    // nearest filtering keeps neighbouring atlas cells from bleeding into each other
+    let sampler = device.create_sampler(&SamplerDescriptor {
+      label: Some("sprite-sampler"),
+      address_mode_u: AddressMode::ClampToEdge,
+      address_mode_v: AddressMode::ClampToEdge,
+      address_mode_w: AddressMode::ClampToEdge,
+      mag_filter: FilterMode::Nearest,
+      min_filter: FilterMode::Nearest,
+      ..Default::default()
+    });
+    let camera_buffer = device.create_buffer(&BufferDescriptor {
+      label: Some("sprite-camera-buffer"),
+      size: std::mem::size_of::<[f32; 32]>() as u64,
+      usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+      mapped_at_creation: false,
+    });
+    let bind_group = build_sprite_bind_group(device, &layout, &camera_buffer, &sampler, atlas);
+    let max_sprites = max_sprites.max(1);
+    let vertex_buffer = device.create_buffer(&BufferDescriptor {
+      label: Some("sprite-vertex-buffer"),
+      size: (std::mem::size_of::<RenderSpriteVertex>() * 4 * max_sprites) as u64,
+      usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+      mapped_at_creation: false,
+    });
+    // quad indices never change, only the vertices do
+    let indices: Vec<u32> = (0..max_sprites as u32).flat_map(|i| {
+      let v = i * 4;
+      [v, v + 1, v + 2, v, v + 2, v + 3]
+    }).collect();
+    let index_buffer = device.create_buffer(&BufferDescriptor {
+      label: Some("sprite-index-buffer"),
+      size: (std::mem::size_of::<u32>() * indices.len()) as u64,
+      usage: BufferUsages::INDEX | BufferUsages::COPY_DST,
+      mapped_at_creation: false,
+    });
+    queue.write_buffer(&index_buffer, 0, bytemuck::cast_slice(&indices));
+
+    Self {
+      pipeline,
+      layout,
+      bind_group,
+      sampler,
+      camera_buffer,
+      vertex_buffer,
+      index_buffer,
+      max_sprites,
+      vertices: Vec::with_capacity(4 * max_sprites),
+      drawn: 0,
+    }
+  }
+  pub fn update_camera(&mut self, queue: &Queue, camera: &RenderCamera) {
+    write_camera_buffer(queue, &self.camera_buffer, camera);
+  }
+  /// swaps the atlas texture, uv rects of queued sprites are kept as is
+  pub fn set_atlas(&mut self, device: &Device, atlas: &Texture) {
+    self.bind_group = build_sprite_bind_group(device, &self.layout, &self.camera_buffer, &self.sampler, atlas);
+  }
+  /// clears sprites queued since the last flush
+  pub fn begin(&mut self) {
+    self.vertices.clear();
+  }
+  /// queues one quad, dest = (center x, center y, width, height) in camera space,
+  /// uv_rect = (min u, min v, max u, max v) within the atlas
+  pub fn draw(&mut self, dest: Vec4, uv_rect: Vec4, color: RenderColor) {
+    if self.vertices.len() >= 4 * self.max_sprites {
+      println!("ERR: Exceeded max sprites ({}), extra sprites are ignored", self.max_sprites);
+      return;
+    }
+    let (hw, hh) = (dest.z * 0.5, dest.w * 0.5);
+    let color = [color.r, color.g, color.b, color.a];
+    // top left, bottom left, bottom right, top right (v grows downwards)
+    let corners = [
+      ([dest.x - hw, dest.y + hh], [uv_rect.x, uv_rect.y]),
+      ([dest.x - hw, dest.y - hh], [uv_rect.x, uv_rect.w]),
+      ([dest.x + hw, dest.y - hh], [uv_rect.z, uv_rect.w]),
+      ([dest.x + hw, dest.y + hh], [uv_rect.z, uv_rect.y]),
+    ];
+    for (position, uv) in corners {
+      self.vertices.push(RenderSpriteVertex { position, uv, color });
+    }
+  }
+  /// uploads queued sprites, drawn by render until the next flush
+  pub fn flush(&mut self, queue: &Queue) {
+    if !self.vertices.is_empty() {
+      queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&self.vertices));
+    }
+    self.drawn = (self.vertices.len() / 4) as u32;
+  }
+  pub fn render(&self, pass: &mut RenderPass) {
+    if self.drawn == 0 { return; }
+    pass.set_pipeline(&self.pipeline);
+    pass.set_bind_group(0, &self.bind_group, &[]);
+    pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+    pass.set_index_buffer(self.index_buffer.slice(..), IndexFormat::Uint32);
+    pass.draw_indexed(0..self.drawn * 6, 0, 0..1);
+  }
+  pub fn destroy(&mut self) {
+    self.camera_buffer.destroy();
+    self.vertex_buffer.destroy();
+    self.index_buffer.destroy();
+  }
+}
+
+fn build_sprite_bind_group(device: &Device, layout: &BindGroupLayout, camera_buffer: &Buffer, sampler: &Sampler, atlas: &Texture) -> BindGroup {
+  let atlas_view = atlas.create_view(&TextureViewDescriptor::default());
+  device.create_bind_group(&BindGroupDescriptor {
+    label: Some("sprite-bind-group"),
+    layout,
+    entries: &[
+      BindGroupEntry { binding: 0, resource: camera_buffer.as_entire_binding() },
+      BindGroupEntry { binding: 1, resource: BindingResource::Sampler(sampler) },
+      BindGroupEntry { binding: 2, resource: BindingResource::TextureView(&atlas_view) },
+    ],
+  })
+}
\ No newline at end of file
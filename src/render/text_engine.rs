@@ -1,15 +1,57 @@
-use cosmic_text::{Attrs, Buffer, Color, Edit, Editor, Font, FontSystem, Metrics, Shaping, SwashCache};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use cosmic_text::{fontdb, Attrs, Buffer, Color, Edit, Editor, Family, Font, FontSystem, Metrics, Shaping, SwashCache};
 use wgpu::{
   Device, Extent3d, Origin3d, Queue, TexelCopyBufferLayout, TexelCopyTextureInfo, 
   Texture, TextureAspect, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages
 };
 
+use crate::utils::Vec2;
 use super::RenderColor;
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TextError {
+  /// font data could not be parsed
+  FontLoad,
+  /// text measured to a zero-sized texture (e.g. empty string without fixed size)
+  EmptyTexture,
+  /// texture would exceed the device's max_texture_dimension_2d (the limit)
+  TextureTooLarge(u32),
+}
+impl std::fmt::Display for TextError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      TextError::FontLoad => write!(f, "failed to load font data"),
+      TextError::EmptyTexture => write!(f, "text texture has zero width or height"),
+      TextError::TextureTooLarge(max) => write!(f, "text texture is larger than the device limit of {}px", max),
+    }
+  }
+}
+
+// section of text drawn with its own font + color
+#[derive(Debug, Clone, Copy)]
+pub struct TextSpan<'a> {
+  pub text: &'a str,
+  pub font_idx: usize,
+  pub color: [u8; 4],
+}
+
 #[derive(Debug)]
 pub struct TextEngine {
   font_system: FontSystem,
   swash_cache: SwashCache,
+  // family names of loaded fonts, None = system default
+  font_families: Vec<Option<String>>,
+  // multiply glyph colors by alpha, for RenderBlendMode::PremultipliedAlpha
+  premultiply: bool,
+  // center laid out lines inside fixed_height instead of top aligning
+  vertical_center: bool,
+  // extra tracking between glyphs in em, added on top of the font's kerning
+  letter_spacing: f32,
+  // characters no font could draw, logged once each in debug
+  missing_glyphs: HashSet<char>,
+  debug: bool,
 }
 impl TextEngine {
   pub fn new() -> Self {
@@ -19,29 +61,168 @@ impl TextEngine {
     Self {
       font_system,
       swash_cache,
+      font_families: vec![None],
+      premultiply: false,
+      vertical_center: false,
+      letter_spacing: 0.0,
+      missing_glyphs: HashSet::new(),
+      debug: false,
+    }
+  }
+  /// write premultiplied pixels, pair with RenderBlendMode::PremultipliedAlpha
+  /// to avoid dark edges on antialiased glyphs
+  pub fn set_premultiply(&mut self, premultiply: bool) {
+    self.premultiply = premultiply;
+  }
+  /// center text vertically in textures with a fixed_height (e.g. button labels)
+  pub fn set_vertical_center(&mut self, vertical_center: bool) {
+    self.vertical_center = vertical_center;
+  }
+  /// extra space between glyphs in em (e.g. 0.1 for loose headings), kerning + ligatures
+  /// from the font are always applied by the shaper
+  pub fn set_letter_spacing(&mut self, em: f32) {
+    self.letter_spacing = em;
+  }
+  /// log characters no font can draw (e.g. pass SystemAccess::debug)
+  pub fn set_debug(&mut self, debug: bool) {
+    self.debug = debug;
+  }
+  /// height of one laid out line, independent of which glyphs it contains
+  pub fn line_height(text_size: f32) -> f32 {
+    f32::ceil(text_size * 1.05)
+  }
+  /// loads font file data, returns font index for TextSpan (0 is the default font)
+  pub fn load_font(&mut self, data: Vec<u8>) -> Result<usize, TextError> {
+    let ids = self.font_system.db_mut().load_font_source(fontdb::Source::Binary(Arc::new(data)));
+    let family = ids.first()
+      .and_then(|id| self.font_system.db().face(*id))
+      .and_then(|face| face.families.first())
+      .map(|(name, _)| name.clone());
+    match family {
+      Some(name) => {
+        self.font_families.push(Some(name));
+        Ok(self.font_families.len() - 1)
+      }
+      None => Err(TextError::FontLoad)
     }
   }
   pub fn create_texture(
     &mut self, device: &Device, queue: &Queue,
     text: &str, text_size: f32, text_color: [u8; 4],
     fixed_width: Option<f32>, fixed_height: Option<f32>
-  ) -> Texture {
-    // create text buffer for cosmic-text
-    let mut text_buffer = Buffer::new(&mut self.font_system, Metrics::new(text_size, f32::ceil(text_size * 1.05)));
+  ) -> Result<Texture, TextError> {
+    let span = TextSpan { text, font_idx: 0, color: text_color };
+    self.create_texture_spans(device, queue, &[span], text_size, fixed_width, fixed_height, None)
+  }
+  /// same as create_texture, with an outline of (color, width in pixels) behind the glyphs
+  #[allow(clippy::too_many_arguments)]
+  pub fn create_outlined_texture(
+    &mut self, device: &Device, queue: &Queue,
+    text: &str, text_size: f32, text_color: [u8; 4], outline: (RenderColor, f32),
+    fixed_width: Option<f32>, fixed_height: Option<f32>
+  ) -> Result<Texture, TextError> {
+    let span = TextSpan { text, font_idx: 0, color: text_color };
+    self.create_texture_spans(device, queue, &[span], text_size, fixed_width, fixed_height, Some(outline))
+  }
+  /// size of the laid out text (shaped advances, wrapped at fixed_width),
+  /// same as the texture size create_texture uses without a fixed size
+  pub fn measure(&mut self, text: &str, text_size: f32, fixed_width: Option<f32>) -> Vec2 {
+    let span = TextSpan { text, font_idx: 0, color: [255; 4] };
+    self.measure_spans(&[span], text_size, fixed_width)
+  }
+  pub fn measure_spans(&mut self, spans: &[TextSpan], text_size: f32, fixed_width: Option<f32>) -> Vec2 {
+    let text_buffer = self.build_buffer(spans, text_size, fixed_width, None);
+    let (w, h) = layout_size(&text_buffer);
+    Vec2::new(w, h)
+  }
+  /// caret insertion index (in chars) nearest to x pixels from the left edge of a single line
+  pub fn index_at_x(&mut self, font_idx: usize, text: &str, text_size: f32, x: f32) -> usize {
+    let span = TextSpan { text, font_idx, color: [255; 4] };
+    let text_buffer = self.build_buffer(&[span], text_size, None, None);
+    if let Some(run) = text_buffer.layout_runs().next() {
+      // glyphs are in visual order, insert before the first glyph whose midpoint is past x
+      for glyph in run.glyphs.iter() {
+        if x < glyph.x + glyph.w * 0.5 {
+          return text[..glyph.start].chars().count();
+        }
+      }
+    }
+    text.chars().count()
+  }
+  /// x offset in pixels of the caret placed before char index on a single line
+  pub fn caret_x(&mut self, font_idx: usize, text: &str, text_size: f32, index: usize) -> f32 {
+    let span = TextSpan { text, font_idx, color: [255; 4] };
+    let text_buffer = self.build_buffer(&[span], text_size, None, None);
+    let byte = text.char_indices().nth(index).map_or(text.len(), |(i, _)| i);
+    let Some(run) = text_buffer.layout_runs().next() else { return 0.0; };
+    run.glyphs.iter()
+      .find(|g| g.start >= byte)
+      .map_or(run.line_w, |g| g.x)
+  }
+  // create text buffer for cosmic-text
+  fn build_buffer(&mut self, spans: &[TextSpan], text_size: f32, fixed_width: Option<f32>, fixed_height: Option<f32>) -> Buffer {
+    let mut text_buffer = Buffer::new(&mut self.font_system, Metrics::new(text_size, Self::line_height(text_size)));
     text_buffer.set_size(&mut self.font_system, fixed_width, fixed_height);
-    text_buffer.set_text(&mut self.font_system, text, &Attrs::new(), Shaping::Advanced);
+    let rich_text: Vec<(&str, Attrs)> = spans.iter()
+      .map(|s| (s.text, span_attrs(&self.font_families, s).letter_spacing(self.letter_spacing)))
+      .collect();
+    text_buffer.set_rich_text(&mut self.font_system, rich_text, &Attrs::new(), Shaping::Advanced, None);
+    text_buffer
+  }
+  /// draws multiple spans (e.g. heading + body fonts) into one texture
+  #[allow(clippy::too_many_arguments)]
+  pub fn create_texture_spans(
+    &mut self, device: &Device, queue: &Queue,
+    spans: &[TextSpan], text_size: f32,
+    fixed_width: Option<f32>, fixed_height: Option<f32>,
+    outline: Option<(RenderColor, f32)>
+  ) -> Result<Texture, TextError> {
+    let max_size = device.limits().max_texture_dimension_2d;
+    let (texture_size, pixel_buffer) = self.rasterize_spans(spans, text_size, fixed_width, fixed_height, outline, max_size)?;
+    // create wgpu texture + upload the pixels
+    let texture = device.create_texture(&TextureDescriptor {
+      size: texture_size,
+      mip_level_count: 1,
+      sample_count: 1,
+      dimension: TextureDimension::D2,
+      format: TextureFormat::Rgba8Unorm,
+      usage: TextureUsages::COPY_DST | TextureUsages::TEXTURE_BINDING,
+      label: Some("pixel_texture"),
+      view_formats: &[]
+    });
+    queue.write_texture(
+      TexelCopyTextureInfo {
+        texture: &texture,
+        mip_level: 0,
+        origin: Origin3d::ZERO,
+        aspect: TextureAspect::All,
+      },
+      &pixel_buffer,
+      TexelCopyBufferLayout {
+        offset: 0,
+        bytes_per_row: Some(4 * texture_size.width),
+        rows_per_image: Some(texture_size.height),
+      },
+      texture_size,
+    );
+
+    Ok(texture)
+  }
+  // texture size + rgba8 pixels of the laid out spans, the cpu side of create_texture_spans
+  fn rasterize_spans(
+    &mut self, spans: &[TextSpan], text_size: f32,
+    fixed_width: Option<f32>, fixed_height: Option<f32>,
+    outline: Option<(RenderColor, f32)>, max_size: u32
+  ) -> Result<(Extent3d, Vec<u8>), TextError> {
+    let text_buffer = self.build_buffer(spans, text_size, fixed_width, fixed_height);
+    let text_color = spans.first().map_or([255; 4], |s| s.color);
     // texture sizing
     let mut texture_size = Extent3d {
       width: 100,
       height: 100,
       depth_or_array_layers: 1,
     };
-    let mut auto_width: f32 = 0.0;
-    let mut auto_height: f32 = 0.0;
-    for run in text_buffer.layout_runs() {
-      if run.line_w > auto_width { auto_width = run.line_w; }
-      if run.line_height > 0.0 { auto_height += run.line_height; }
-    }
+    let (auto_width, auto_height) = layout_size(&text_buffer);
     texture_size.width = match fixed_width {
       Some(w) => f32::ceil(w) as u32,
       None => f32::ceil(auto_width) as u32
@@ -50,27 +231,49 @@ impl TextEngine {
       Some(h) => f32::ceil(h) as u32,
       None => f32::ceil(auto_height) as u32
     };
-    // create wgpu texture + bytedata buffer
-    let texture = device.create_texture(&TextureDescriptor {
-      size: texture_size,
-      mip_level_count: 1,
-      sample_count: 1,
-      dimension: TextureDimension::D2,
-      format: TextureFormat::Rgba8Unorm,
-      usage: TextureUsages::COPY_DST | TextureUsages::TEXTURE_BINDING,
-      label: Some("pixel_texture"),
-      view_formats: &[]
-    });
+    if texture_size.width == 0 || texture_size.height == 0 {
+      return Err(TextError::EmptyTexture);
+    }
+    if texture_size.width > max_size || texture_size.height > max_size {
+      return Err(TextError::TextureTooLarge(max_size));
+    }
     let mut pixel_buffer: Vec<u8> = vec![0; (texture_size.width * texture_size.height * 4) as usize];
+    let (tw, th) = (texture_size.width as i32, texture_size.height as i32);
+    let y_off = match (self.vertical_center, fixed_height) {
+      (true, Some(h)) => f32::round((h - auto_height).max(0.0) / 2.0) as i32,
+      _ => 0
+    };
+    // draw glyphs offset in 8 directions in outline color first
+    if let Some((outline_color, outline_width)) = outline {
+      let ow = f32::round(outline_width).max(1.0) as i32;
+      let oc: [u8; 4] = outline_color.into();
+      let offsets = [(-ow, -ow), (0, -ow), (ow, -ow), (-ow, 0), (ow, 0), (-ow, ow), (0, ow), (ow, ow)];
+      text_buffer.draw(
+        &mut self.font_system,
+        &mut self.swash_cache,
+        Color::rgba(text_color[0], text_color[1], text_color[2], text_color[3]),
+        |x, y, _w, _h, color| {
+          let a = (color.a() as u32 * oc[3] as u32 / 255) as u8;
+          for (dx, dy) in offsets {
+            blend_pixel(&mut pixel_buffer, tw, th, x + dx, y + y_off + dy, [oc[0], oc[1], oc[2], a]);
+          }
+        }
+      );
+    }
     text_buffer.draw(
       &mut self.font_system, 
       &mut self.swash_cache,
       Color::rgba(text_color[0], text_color[1], text_color[2], text_color[3]),
       |x, y, _w, _h, color| {
+        let y = y + y_off;
+        if outline.is_some() {
+          blend_pixel(&mut pixel_buffer, tw, th, x, y, [color.r(), color.g(), color.b(), color.a()]);
+          return;
+        }
         let idx = (y * texture_size.width as i32 + x) * 4;
         if idx < 0 { return; }
         let idx = idx as usize;
-        if idx > pixel_buffer.len() { return; }
+        if idx + 4 > pixel_buffer.len() { return; }
         // draw pixel into buffer
         pixel_buffer[idx] = color.r();
         pixel_buffer[idx + 1] = color.g();
@@ -78,23 +281,137 @@ impl TextEngine {
         pixel_buffer[idx + 3] = color.a();
       }
     );
+    // shaping already falls back through every loaded + system font,
+    // glyph 0 means none of them had the character
+    for run in text_buffer.layout_runs() {
+      for glyph in run.glyphs.iter().filter(|g| g.glyph_id == 0) {
+        let Some(chr) = run.text[glyph.start..glyph.end].chars().next() else { continue; };
+        if chr.is_whitespace() || chr.is_control() { continue; }
+        if self.missing_glyphs.insert(chr) && self.debug {
+          println!("No font has a glyph for {:?} (U+{:04X})", chr, chr as u32);
+        }
+        // visible .notdef box instead of a silent gap
+        let c = glyph.color_opt.map_or(text_color, |c| [c.r(), c.g(), c.b(), c.a()]);
+        let x0 = f32::round(glyph.x) as i32 + 1;
+        let x1 = (f32::round(glyph.x + glyph.w) as i32 - 2).max(x0 + 2);
+        let y0 = f32::round(run.line_top + run.line_height * 0.2) as i32 + y_off;
+        let y1 = f32::round(run.line_y) as i32 + y_off;
+        for x in x0..=x1 {
+          blend_pixel(&mut pixel_buffer, tw, th, x, y0, c);
+          blend_pixel(&mut pixel_buffer, tw, th, x, y1, c);
+        }
+        for y in (y0 + 1)..y1 {
+          blend_pixel(&mut pixel_buffer, tw, th, x0, y, c);
+          blend_pixel(&mut pixel_buffer, tw, th, x1, y, c);
+        }
+      }
+    }
+    if self.premultiply {
+      for px in pixel_buffer.chunks_exact_mut(4) {
+        let a = px[3] as u32;
+        for c in px.iter_mut().take(3) {
+          *c = ((*c as u32 * a + 127) / 255) as u8;
+        }
+      }
+    }
+    Ok((texture_size, pixel_buffer))
+  }
+}
 
-    queue.write_texture(
-      TexelCopyTextureInfo {
-        texture: &texture,
-        mip_level: 0,
-        origin: Origin3d::ZERO,
-        aspect: TextureAspect::All,
-      },
-      &pixel_buffer,
-      TexelCopyBufferLayout {
-        offset: 0,
-        bytes_per_row: Some(4 * texture_size.width),
-        rows_per_image: Some(texture_size.height),
-      },
-      texture_size,
-    );
+// widest line advance + summed line heights
+fn layout_size(text_buffer: &Buffer) -> (f32, f32) {
+  let mut width: f32 = 0.0;
+  let mut height: f32 = 0.0;
+  for run in text_buffer.layout_runs() {
+    if run.line_w > width { width = run.line_w; }
+    if run.line_height > 0.0 { height += run.line_height; }
+  }
+  (width, height)
+}
 
-    texture
+fn span_attrs<'a>(font_families: &'a [Option<String>], span: &TextSpan) -> Attrs<'a> {
+  let c = span.color;
+  let attrs = Attrs::new().color(Color::rgba(c[0], c[1], c[2], c[3]));
+  // missing fonts fall back to default
+  match font_families.get(span.font_idx) {
+    Some(Some(name)) => attrs.family(Family::Name(name)),
+    _ => attrs
   }
-}
\ No newline at end of file
+}
+
+// alpha-composites src over the pixel at (x, y)
+fn blend_pixel(buffer: &mut [u8], width: i32, height: i32, x: i32, y: i32, src: [u8; 4]) {
+  if x < 0 || y < 0 || x >= width || y >= height || src[3] == 0 { return; }
+  let idx = ((y * width + x) * 4) as usize;
+  let sa = src[3] as f32 / 255.0;
+  let da = buffer[idx + 3] as f32 / 255.0;
+  let out_a = sa + da * (1.0 - sa);
+  for c in 0..3 {
+    let s = src[c] as f32 * sa;
+    let d = buffer[idx + c] as f32 * da * (1.0 - sa);
+    buffer[idx + c] = f32::round((s + d) / out_a) as u8;
+  }
+  buffer[idx + 3] = f32::round(out_a * 255.0) as u8;
+}
+
+#[cfg(test)]
+mod text_engine_tests {
+  use super::*;
+
+  #[test]
+  fn text_kerning_and_tracking() {
+    let mut te = TextEngine::new();
+    let a = te.measure("A", 40.0, None).x;
+    let v = te.measure("V", 40.0, None).x;
+    // needs at least one system font to shape with
+    if a == 0.0 { return; }
+    let av = te.measure("AV", 40.0, None).x;
+    assert!(av < a + v, "kerned AV {} should be tighter than A + V {}", av, a + v);
+    // tracking is added per glyph on top of kerning
+    te.set_letter_spacing(0.1);
+    let tracked = te.measure("AV", 40.0, None).x;
+    assert!((tracked - av - 2.0 * 0.1 * 40.0).abs() < 0.5);
+  }
+
+  #[test]
+  fn text_measure_matches_texture() {
+    let mut te = TextEngine::new();
+    let text = "Hello world\nline 2";
+    let size = te.measure(text, 24.0, None);
+    // needs at least one system font to shape with
+    if size.x == 0.0 { return; }
+    let span = TextSpan { text, font_idx: 0, color: [255; 4] };
+    let (tx_size, pixels) = te.rasterize_spans(&[span], 24.0, None, None, None, 8192).unwrap();
+    assert_eq!((tx_size.width, tx_size.height), (size.x.ceil() as u32, size.y.ceil() as u32));
+    // glyphs are drawn inside the measured box, reaching its last row of text + right side
+    let lit: Vec<(u32, u32)> = pixels.chunks_exact(4).enumerate()
+      .filter(|(_, p)| p[3] > 0)
+      .map(|(i, _)| (i as u32 % tx_size.width, i as u32 / tx_size.width))
+      .collect();
+    assert!(lit.iter().any(|p| p.0 + 8 >= tx_size.width));
+    assert!(lit.iter().any(|p| p.1 > tx_size.height / 2));
+    // wrapping keeps the fixed width, the height still matches measure
+    let wrapped = te.measure(text, 24.0, Some(50.0));
+    let span = TextSpan { text, font_idx: 0, color: [255; 4] };
+    let (tx_size, _) = te.rasterize_spans(&[span], 24.0, Some(50.0), None, None, 8192).unwrap();
+    assert_eq!((tx_size.width, tx_size.height), (50, wrapped.y.ceil() as u32));
+    assert!(wrapped.y > size.y);
+  }
+
+  #[test]
+  fn text_caret_hit_testing() {
+    let mut te = TextEngine::new();
+    let text = "Hello world";
+    let width = te.measure(text, 24.0, None).x;
+    // needs at least one system font to shape with
+    if width == 0.0 { return; }
+    let len = text.chars().count();
+    for i in 0..=len {
+      let x = te.caret_x(0, text, 24.0, i);
+      assert_eq!(te.index_at_x(0, text, 24.0, x), i);
+    }
+    assert_eq!(te.caret_x(0, text, 24.0, len), width);
+    assert_eq!(te.index_at_x(0, text, 24.0, -10.0), 0);
+    assert_eq!(te.index_at_x(0, text, 24.0, width + 10.0), len);
+  }
+}
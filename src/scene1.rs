@@ -3,7 +3,7 @@ use winit::keyboard::KeyCode;
 
 use crate::{
   render::{
-    ObjPipeline, Primitives, RenderCamera, RenderColor, RenderObjectSetup,
+    ObjPipeline, Primitives, RenderCamera, RenderColor, RenderLight, RenderObjectSetup,
     RenderObjectUpdate, ShaderType, TextEngine
   }, utils::Vec3, vec3f, wrapper::{GpuAccess, MKBState, SceneBase, SystemAccess}
 };
@@ -13,6 +13,7 @@ pub struct Scene1 {
   overlay: Option<ObjPipeline>,
   overlay_camera: RenderCamera,
   obj_pipe: Option<ObjPipeline>,
+  grid_pipe: Option<ObjPipeline>,
   obj_camera: RenderCamera,
   text_engine: TextEngine,
   refresh_timeout: f32,
@@ -25,19 +26,25 @@ impl Scene1 {
     if self.refresh_timeout > 1.0 {
       self.refresh_timeout = 0.0;
       if let Some(objp) = &mut self.overlay {
-        let txt = format!("FPS: {:.2}", sys.fps());
-        let word_tx = self.text_engine.create_texture(
+        let txt = format!("FPS: {:.2}", sys.frame_stats().avg_fps());
+        let word_tx = self.text_engine.create_outlined_texture(
           &gpu.device, &gpu.queue, &txt,
-          26.0, RenderColor::rgb(40, 200, 0).into(), Some(150.0), Some(30.0)
+          26.0, RenderColor::rgb(40, 200, 0).into(), (RenderColor::BLACK, 1.0), Some(150.0), Some(30.0)
         );
-        objp.replace_texture(&gpu.device, 0, 1, word_tx);
+        match word_tx {
+          Ok(tx) => objp.replace_texture(&gpu.device, 0, 1, tx),
+          Err(e) => println!("ERR: {}", e)
+        }
+      }
+      if sys.debug {
+        println!("GPU pass times: {:?}", gpu.last_pass_times());
       }
     }
 
     // update fps position
     if let Some(p) = &mut self.overlay {
       p.update_object(0, &gpu.queue, RenderObjectUpdate::default()
-        .with_position(vec3f!(76.0 - sys.win_center().x, sys.win_center().y - 16.0, 0.0))
+        .with_position(vec3f!(76.0 - gpu.view_size().x * 0.5, gpu.view_size().y * 0.5 - 16.0, 0.0))
         .with_camera(&self.overlay_camera)
       );
     }
@@ -50,6 +57,7 @@ impl SceneBase for Scene1 {
       overlay: None,
       overlay_camera: RenderCamera::default(),
       obj_pipe: None,
+      grid_pipe: None,
       obj_camera: RenderCamera::default(),
       text_engine: TextEngine::new(),
       refresh_timeout: 2.0,
@@ -58,8 +66,9 @@ impl SceneBase for Scene1 {
   }
   fn init(&mut self, sys: &mut SystemAccess, gpu: &mut GpuAccess) {
     println!("Init scene 1");
+    self.text_engine.set_debug(sys.debug);
     self.overlay_camera = RenderCamera::new_ortho(1.0, 1000.0, sys.win_size());
-    let mut overlayp = ObjPipeline::new(&gpu.device, gpu.screen_format, ShaderType::Overlay, false);
+    let mut overlayp = ObjPipeline::new_with_alpha_to_coverage(&gpu.device, gpu.screen_format, ShaderType::Overlay, false, false);
     let (verts1, index1) = Primitives::rect_indexed(150.0, 30.0, 0.0);
     overlayp.add_object(&gpu.device, &gpu.queue, RenderObjectSetup {
       vertex_data: verts1,
@@ -71,6 +80,8 @@ impl SceneBase for Scene1 {
 
     self.obj_camera = RenderCamera::new_persp(45.0, 1.0, 1000.0, sys.win_size());
     let mut objp = ObjPipeline::new(&gpu.device, gpu.screen_format, ShaderType::Default, false);
+    objp.set_anisotropy(8, gpu.supports_anisotropy());
+    objp.set_batched_uploads(true);
     let (verts2, index2) = Primitives::cylinder(8.0, 12.0, 24);
     objp.add_object(&gpu.device, &gpu.queue, RenderObjectSetup {
       vertex_data: verts2,
@@ -78,18 +89,45 @@ impl SceneBase for Scene1 {
       camera: Some(&self.obj_camera),
       ..Default::default()
     });
+    objp.set_lights(&gpu.queue, &[
+      RenderLight::directional(vec3f!(-0.5, -1.0, -1.0), RenderColor::WHITE, 1.0).with_ambient(0.2)
+    ]);
     self.obj_pipe = Some(objp);
+
+    // ground reference grid
+    let mut gridp = ObjPipeline::new(&gpu.device, gpu.screen_format, ShaderType::Line, false);
+    gridp.set_batched_uploads(true);
+    for lines in [Primitives::grid(200.0, 20), Primitives::grid_axes(200.0)] {
+      let (verts, indices) = Primitives::line_quads(&lines);
+      gridp.add_object(&gpu.device, &gpu.queue, RenderObjectSetup {
+        vertex_data: verts,
+        indices,
+        camera: Some(&self.obj_camera),
+        ..Default::default()
+      });
+    }
+    self.grid_pipe = Some(gridp);
   }
-  fn resize(&mut self, sys: &mut SystemAccess, gpu: &mut GpuAccess, width: u32, height: u32) {
+  fn resize(&mut self, _sys: &mut SystemAccess, gpu: &mut GpuAccess, width: u32, height: u32) {
     gpu.resize_screen(width, height);
-    self.overlay_camera.target_size = sys.win_size();
-    self.obj_camera.target_size = sys.win_size();
+    if let Some(p) = &mut self.overlay { p.resize(width, height); }
+    if let Some(p) = &mut self.obj_pipe { p.resize(width, height); }
+    if let Some(p) = &mut self.grid_pipe { p.resize(width, height); }
+    self.overlay_camera.target_size = gpu.view_size();
+    self.obj_camera.target_size = gpu.view_size();
   }
   fn update(&mut self, sys: &mut SystemAccess, gpu: &mut GpuAccess) {
     self.lifetime += sys.time_delta_sec();
     if sys.kb_inputs().contains_key(&KeyCode::Escape) {
       sys.request_exit();
     }
+    if sys.kb_inputs().get(&KeyCode::F11) == Some(&MKBState::Pressed) {
+      sys.set_fullscreen(!sys.is_fullscreen());
+    }
+    // toggle fps overlay
+    if sys.kb_inputs().get(&KeyCode::F3) == Some(&MKBState::Pressed) && let Some(p) = &mut self.overlay {
+      p.set_pipeline_visible(!p.is_pipeline_visible());
+    }
 
     if sys.kb_inputs().get(&KeyCode::Digit1) == Some(&MKBState::Released) {
       sys.next_scene = 0;
@@ -97,6 +135,9 @@ impl SceneBase for Scene1 {
     if sys.kb_inputs().get(&KeyCode::Digit2) == Some(&MKBState::Released) {
       sys.next_scene = 1;
     }
+    if sys.kb_inputs().get(&KeyCode::Digit3) == Some(&MKBState::Released) {
+      sys.next_scene = 2;
+    }
 
     // update scene
     self.update_fps(sys, gpu);
@@ -108,11 +149,24 @@ impl SceneBase for Scene1 {
         .with_rotation(vec3f!(1.0, 0.8, 0.2), self.lifetime * 10.0)
       );
     }
+    if let Some(p) = &mut self.grid_pipe {
+      let lines = [(RenderColor::rgb(60, 60, 70), 1.0), (RenderColor::rgb(160, 160, 180), 2.0)];
+      for (i, (color, width)) in lines.into_iter().enumerate() {
+        p.update_object(i, &gpu.queue, RenderObjectUpdate::default()
+          .with_camera(&self.obj_camera)
+          .with_color(color)
+          .with_line_width(width, 1.0, sys.win_size())
+          .with_position(vec3f!(0.0, -20.0, -50.0))
+        );
+      }
+    }
 
     // render
     match gpu.begin_render() {
       Ok((mut encoder, surface)) => {
         let target = surface.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        if let Some(p) = &mut self.grid_pipe { p.flush_uploads(&gpu.device, &mut encoder); }
+        if let Some(p) = &mut self.obj_pipe { p.flush_uploads(&gpu.device, &mut encoder); }
         {
           let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("clear-render"),
@@ -124,8 +178,11 @@ impl SceneBase for Scene1 {
                 store: wgpu::StoreOp::Store
               }
             })],
+            timestamp_writes: gpu.pass_timestamps(),
             ..Default::default()
           });
+          gpu.set_viewport(&mut pass);
+          if let Some(p) = &self.grid_pipe { p.render(&mut pass); }
           if let Some(p) = &self.obj_pipe { p.render(&mut pass); }
           if let Some(p) = &self.overlay { p.render(&mut pass); }
         }
@@ -149,5 +206,9 @@ impl SceneBase for Scene1 {
       p.destroy();
       self.overlay = None;
     }
+    if let Some(p) = &mut self.grid_pipe {
+      p.destroy();
+      self.grid_pipe = None;
+    }
   }
 }
@@ -17,20 +17,84 @@ pub struct Scene2 {
   camera: RenderCamera,
   text_engine: TextEngine,
   refresh_timeout: f32,
+  input_text: String,
+  caret: usize,
+  // caret offset from the left edge of the text in pixels
+  caret_px: f32,
+  caret_timer: f32,
+  input_dirty: bool,
 }
 impl Scene2 {
+  fn update_text_input(&mut self, sys: &SystemAccess, gpu: &GpuAccess) {
+    let mut changed = self.input_dirty;
+    self.input_dirty = false;
+    // insert committed characters at caret
+    for chr in sys.text_input().chars() {
+      if chr.is_control() { continue; }
+      let idx = self.input_text.char_indices().nth(self.caret).map_or(self.input_text.len(), |(i, _)| i);
+      self.input_text.insert(idx, chr);
+      self.caret += 1;
+      changed = true;
+    }
+    // editing keys
+    if sys.kb_inputs().get(&KeyCode::Backspace) == Some(&MKBState::Pressed) && self.caret > 0 {
+      self.caret -= 1;
+      if let Some((i, _)) = self.input_text.char_indices().nth(self.caret) {
+        self.input_text.remove(i);
+      }
+      changed = true;
+    }
+    if sys.kb_inputs().get(&KeyCode::ArrowLeft) == Some(&MKBState::Pressed) && self.caret > 0 {
+      self.caret -= 1;
+      changed = true;
+    }
+    if sys.kb_inputs().get(&KeyCode::ArrowRight) == Some(&MKBState::Pressed)
+      && self.caret < self.input_text.chars().count() {
+      self.caret += 1;
+      changed = true;
+    }
+    // blink caret every half second, keep it solid while typing
+    if changed { self.caret_timer = 0.0; }
+    self.caret_timer += sys.time_delta_sec();
+    let caret_on = self.caret_timer % 1.0 < 0.5;
+
+    let Some(objp) = &mut self.overlay else { return; };
+    if changed {
+      self.caret_px = self.text_engine.caret_x(0, &self.input_text, 22.0, self.caret);
+      let word_tx = self.text_engine.create_texture(
+        &gpu.device, &gpu.queue, &self.input_text,
+        22.0, RenderColor::rgb(220, 220, 220).into(), Some(400.0), Some(30.0)
+      );
+      match word_tx {
+        Ok(tx) => objp.replace_texture(&gpu.device, 1, 1, tx),
+        Err(e) => println!("ERR: {}", e)
+      }
+    }
+    // text is top aligned in the input line, starting 1px from the window edge
+    let line_h = TextEngine::line_height(22.0);
+    objp.update_object(2, &gpu.queue, RenderObjectUpdate::default()
+      .with_position(vec3f!(2.0 + self.caret_px - sys.win_center().x, sys.win_center().y - 35.0 - line_h * 0.5, 0.0))
+      .with_color(RenderColor::rgb(220, 220, 220))
+      .with_visible(caret_on)
+      .with_camera(&self.camera)
+    );
+  }
+
   fn update_fps(&mut self, sys: &SystemAccess, gpu: &GpuAccess) {
     // update fps text
     self.refresh_timeout += sys.time_delta_sec();
     if self.refresh_timeout > 1.0 {
       self.refresh_timeout = 0.0;
       if let Some(objp) = &mut self.overlay {
-        let txt = format!("FPS: {:.2}", sys.fps());
+        let txt = format!("FPS: {:.2}", sys.frame_stats().avg_fps());
         let word_tx = self.text_engine.create_texture(
           &gpu.device, &gpu.queue, &txt,
           26.0, RenderColor::rgb(211, 233, 16).into(), Some(150.0), Some(30.0)
         );
-        objp.replace_texture(&gpu.device, 0, 1, word_tx);
+        match word_tx {
+          Ok(tx) => objp.replace_texture(&gpu.device, 0, 1, tx),
+          Err(e) => println!("ERR: {}", e)
+        }
       }
     }
 
@@ -40,6 +104,10 @@ impl Scene2 {
         .with_position(vec3f!(76.0 - sys.win_center().x, sys.win_center().y - 16.0, 0.0))
         .with_camera(&self.camera)
       );
+      p.update_object(1, &gpu.queue, RenderObjectUpdate::default()
+        .with_position(vec3f!(201.0 - sys.win_center().x, sys.win_center().y - 50.0, 0.0))
+        .with_camera(&self.camera)
+      );
     }
 
   }
@@ -51,12 +119,18 @@ impl SceneBase for Scene2 {
       camera: RenderCamera::default(),
       text_engine: TextEngine::new(),
       refresh_timeout: 2.0,
+      input_text: String::new(),
+      caret: 0,
+      caret_px: 0.0,
+      caret_timer: 0.0,
+      input_dirty: true,
     }
   }
   fn init(&mut self, sys: &mut SystemAccess, gpu: &mut GpuAccess) {
     println!("Init scene 2");
+    self.text_engine.set_debug(sys.debug);
     self.camera = RenderCamera::new_ortho(1.0, 1000.0, sys.win_size());
-    let mut objp = ObjPipeline::new(&gpu.device, gpu.screen_format, ShaderType::Overlay, false);
+    let mut objp = ObjPipeline::new_with_alpha_to_coverage(&gpu.device, gpu.screen_format, ShaderType::Overlay, false, false);
     let (verts1, index1) = Primitives::rect_indexed(150.0, 30.0, 0.0);
     objp.add_object(&gpu.device, &gpu.queue, RenderObjectSetup {
       vertex_data: verts1,
@@ -64,16 +138,37 @@ impl SceneBase for Scene2 {
       camera: Some(&self.camera),
       ..Default::default()
     });
+    // text input line
+    let (verts2, index2) = Primitives::rect_indexed(400.0, 30.0, 0.0);
+    objp.add_object(&gpu.device, &gpu.queue, RenderObjectSetup {
+      vertex_data: verts2,
+      indices: index2,
+      camera: Some(&self.camera),
+      ..Default::default()
+    });
+    // caret
+    let (verts3, index3) = Primitives::rect_indexed(2.0, TextEngine::line_height(22.0), 0.0);
+    objp.add_object(&gpu.device, &gpu.queue, RenderObjectSetup {
+      vertex_data: verts3,
+      indices: index3,
+      camera: Some(&self.camera),
+      ..Default::default()
+    });
     self.overlay = Some(objp);
+    self.input_dirty = true;
   }
   fn resize(&mut self, sys: &mut SystemAccess, gpu: &mut GpuAccess, width: u32, height: u32) {
     gpu.resize_screen(width, height);
+    if let Some(p) = &mut self.overlay { p.resize(width, height); }
     self.camera.target_size = sys.win_size();
   }
   fn update(&mut self, sys: &mut SystemAccess, gpu: &mut GpuAccess) {
     if sys.kb_inputs().contains_key(&KeyCode::Escape) {
       sys.request_exit();
     }
+    if sys.kb_inputs().get(&KeyCode::F11) == Some(&MKBState::Pressed) {
+      sys.set_fullscreen(!sys.is_fullscreen());
+    }
 
     if sys.kb_inputs().get(&KeyCode::Digit1) == Some(&MKBState::Released) {
       sys.next_scene = 0;
@@ -81,9 +176,13 @@ impl SceneBase for Scene2 {
     if sys.kb_inputs().get(&KeyCode::Digit2) == Some(&MKBState::Released) {
       sys.next_scene = 1;
     }
+    if sys.kb_inputs().get(&KeyCode::Digit3) == Some(&MKBState::Released) {
+      sys.next_scene = 2;
+    }
 
     // update scene
     self.update_fps(sys, gpu);
+    self.update_text_input(sys, gpu);
 
     // render
     match gpu.begin_render() {
@@ -0,0 +1,168 @@
+use wgpu::SurfaceError;
+use winit::keyboard::KeyCode;
+
+use crate::{
+  utils::{ease_out_quad, Vec2, Vec3, Vec4},
+  vec2f,
+  wrapper::{SceneBase, GpuAccess, MKBState, SystemAccess},
+  render::{build_texture, DebugDraw, ParticleSystem, RenderCamera, RenderColor, SpriteBatch},
+};
+
+// size of the generated particle sprite
+const DOT_SIZE: u32 = 32;
+
+#[derive(Debug)]
+pub struct Scene3 {
+  batch: Option<SpriteBatch>,
+  debug_draw: Option<DebugDraw>,
+  camera: RenderCamera,
+  fountain: ParticleSystem,
+  sparks: ParticleSystem,
+}
+impl SceneBase for Scene3 {
+  fn new() -> Self {
+    Self {
+      batch: None,
+      debug_draw: None,
+      camera: RenderCamera::default(),
+      fountain: ParticleSystem::new(vec2f!(0.0, -200.0), 2000)
+        .with_spawn_rate(400.0)
+        .with_lifetime(1.5, 2.5)
+        .with_velocity(vec2f!(-60.0, 250.0), vec2f!(60.0, 400.0))
+        .with_gravity(vec2f!(0.0, -300.0))
+        .with_color(RenderColor::rgb(40, 160, 255), RenderColor::rgba(255, 255, 255, 0))
+        .with_size(12.0, 4.0),
+      sparks: ParticleSystem::new(vec2f!(0.0, 100.0), 500)
+        .with_lifetime(0.4, 1.0)
+        .with_velocity(vec2f!(-300.0, -300.0), vec2f!(300.0, 300.0))
+        .with_color(RenderColor::rgb(255, 200, 40), RenderColor::rgba(255, 40, 0, 0))
+        .with_size(10.0, 0.0)
+        .with_easing(ease_out_quad),
+    }
+  }
+  fn init(&mut self, sys: &mut SystemAccess, gpu: &mut GpuAccess) {
+    println!("Init scene 3");
+    self.camera = RenderCamera::new_ortho(1.0, 1000.0, sys.win_size());
+    // soft white dot, tinted per particle
+    let mut data: Vec<u8> = Vec::with_capacity((DOT_SIZE * DOT_SIZE * 4) as usize);
+    let half = DOT_SIZE as f32 * 0.5;
+    for y in 0..DOT_SIZE {
+      for x in 0..DOT_SIZE {
+        let d = vec2f!(x as f32 + 0.5 - half, y as f32 + 0.5 - half).magnitude() / half;
+        let a = (1.0 - d).clamp(0.0, 1.0);
+        data.extend_from_slice(&[255, 255, 255, (a * 255.0) as u8]);
+      }
+    }
+    let atlas = build_texture(&gpu.device, &gpu.queue, DOT_SIZE, DOT_SIZE, &data, false);
+    let mut batch = SpriteBatch::new(&gpu.device, &gpu.queue, gpu.screen_format, &atlas, 2500);
+    batch.update_camera(&gpu.queue, &self.camera);
+    self.batch = Some(batch);
+    let mut debug_draw = DebugDraw::new(&gpu.device, gpu.screen_format);
+    debug_draw.update_camera(&gpu.queue, &self.camera);
+    self.debug_draw = Some(debug_draw);
+  }
+  fn resize(&mut self, sys: &mut SystemAccess, gpu: &mut GpuAccess, width: u32, height: u32) {
+    gpu.resize_screen(width, height);
+    self.camera.target_size = sys.win_size();
+    if let Some(b) = &mut self.batch { b.update_camera(&gpu.queue, &self.camera); }
+    if let Some(d) = &mut self.debug_draw { d.update_camera(&gpu.queue, &self.camera); }
+  }
+  fn update(&mut self, sys: &mut SystemAccess, gpu: &mut GpuAccess) {
+    if sys.kb_inputs().contains_key(&KeyCode::Escape) {
+      sys.request_exit();
+    }
+    if sys.kb_inputs().get(&KeyCode::F11) == Some(&MKBState::Pressed) {
+      sys.set_fullscreen(!sys.is_fullscreen());
+    }
+
+    if sys.kb_inputs().get(&KeyCode::Digit1) == Some(&MKBState::Released) {
+      sys.next_scene = 0;
+    }
+    if sys.kb_inputs().get(&KeyCode::Digit2) == Some(&MKBState::Released) {
+      sys.next_scene = 1;
+    }
+    if sys.kb_inputs().get(&KeyCode::Digit3) == Some(&MKBState::Released) {
+      sys.next_scene = 2;
+    }
+
+    // freeze time, step single frames while paused
+    if sys.kb_inputs().get(&KeyCode::KeyP) == Some(&MKBState::Pressed) {
+      sys.set_paused(!sys.is_paused());
+    }
+    if sys.kb_inputs().get(&KeyCode::Period) == Some(&MKBState::Pressed) {
+      sys.step_once();
+    }
+
+    // update scene
+    if sys.kb_inputs().get(&KeyCode::Space) == Some(&MKBState::Pressed) {
+      self.sparks.emit(200);
+    }
+    let dt = sys.time_delta_sec();
+    self.fountain.update(dt);
+    self.sparks.update(dt);
+    if let Some(b) = &mut self.batch {
+      let uv = Vec4::new(0.0, 0.0, 1.0, 1.0);
+      b.begin();
+      self.fountain.draw(b, uv);
+      self.sparks.draw(b, uv);
+      b.flush(&gpu.queue);
+    }
+    // emitter origins
+    if let Some(d) = &mut self.debug_draw {
+      if sys.debug {
+        let (f, s) = (self.fountain.origin, self.sparks.origin);
+        d.rect(Vec3::new(f.x, f.y, 0.0), vec2f!(20.0, 20.0), RenderColor::GREEN);
+        d.circle(Vec3::new(s.x, s.y, 0.0), 10.0, RenderColor::RED);
+        d.line(Vec3::new(s.x - 15.0, s.y, 0.0), Vec3::new(s.x + 15.0, s.y, 0.0), RenderColor::RED);
+      }
+      d.flush(&gpu.device, &gpu.queue);
+    }
+
+    // render
+    match gpu.begin_render() {
+      Ok((mut encoder, surface)) => {
+        let target = surface.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        {
+          let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("clear-render"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+              view: &target,
+              resolve_target: None,
+              ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(RenderColor::rgb(1, 2, 5).into()),
+                store: wgpu::StoreOp::Store
+              }
+            })],
+            ..Default::default()
+          });
+          if let Some(b) = &self.batch { b.render(&mut pass); }
+          if let Some(d) = &self.debug_draw { d.render(&mut pass); }
+        }
+        gpu.end_render(encoder, surface);
+      }
+      Err(SurfaceError::Lost | SurfaceError::Outdated) => {
+        println!("Err: surface was lost or outdated. Attempting to re-connect");
+        gpu.resize_screen(sys.win_size().x as u32, sys.win_size().y as u32);
+      }
+      Err(SurfaceError::OutOfMemory) => {
+        println!("Err: Out of memory. Exiting");
+        sys.request_exit();
+      }
+      Err(e) => {
+        println!("Err: {:?}", e);
+      }
+    }
+  }
+  fn cleanup(&mut self) {
+    if let Some(b) = &mut self.batch {
+      b.destroy();
+      self.batch = None;
+    }
+    if let Some(d) = &mut self.debug_draw {
+      d.destroy();
+      self.debug_draw = None;
+    }
+    self.fountain.clear();
+    self.sparks.clear();
+  }
+}
@@ -0,0 +1,152 @@
+use super::*;
+
+// --- --- --- --- --- --- --- //
+// ---  Easing Functions   --- //
+// --- --- --- --- --- --- --- //
+// all functions map t in [0,1] to a progress value, with f(0) = 0 and f(1) = 1
+
+pub fn linear(t: f32) -> f32 {
+  t
+}
+
+pub fn ease_in_quad(t: f32) -> f32 {
+  t * t
+}
+
+pub fn ease_out_quad(t: f32) -> f32 {
+  1.0 - (1.0 - t) * (1.0 - t)
+}
+
+pub fn ease_in_out_quad(t: f32) -> f32 {
+  if t < 0.5 { 2.0 * t * t } else { 1.0 - (-2.0 * t + 2.0).powi(2) / 2.0 }
+}
+
+pub fn ease_in_cubic(t: f32) -> f32 {
+  t * t * t
+}
+
+pub fn ease_out_cubic(t: f32) -> f32 {
+  1.0 - (1.0 - t).powi(3)
+}
+
+pub fn ease_in_out_cubic(t: f32) -> f32 {
+  if t < 0.5 { 4.0 * t * t * t } else { 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0 }
+}
+
+/// overshoots slightly past 1 before settling
+pub fn ease_out_back(t: f32) -> f32 {
+  let c1 = 1.70158;
+  let c3 = c1 + 1.0;
+  1.0 + c3 * (t - 1.0).powi(3) + c1 * (t - 1.0).powi(2)
+}
+
+/// springs past 1 and oscillates back
+pub fn ease_out_elastic(t: f32) -> f32 {
+  if t <= 0.0 { return 0.0; }
+  if t >= 1.0 { return 1.0; }
+  let c4 = (2.0 * PI) / 3.0;
+  2.0_f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * c4).sin() + 1.0
+}
+
+pub fn ease_out_bounce(t: f32) -> f32 {
+  let n1 = 7.5625;
+  let d1 = 2.75;
+  if t < 1.0 / d1 {
+    n1 * t * t
+  } else if t < 2.0 / d1 {
+    let t = t - 1.5 / d1;
+    n1 * t * t + 0.75
+  } else if t < 2.5 / d1 {
+    let t = t - 2.25 / d1;
+    n1 * t * t + 0.9375
+  } else {
+    let t = t - 2.625 / d1;
+    n1 * t * t + 0.984375
+  }
+}
+
+// --- --- --- --- --- --- --- //
+// ---        Tween        --- //
+// --- --- --- --- --- --- --- //
+
+/// values that can be blended for tweening
+pub trait Tweenable: Copy {
+  fn interpolate(a: Self, b: Self, t: f32) -> Self;
+}
+impl Tweenable for f32 {
+  fn interpolate(a: Self, b: Self, t: f32) -> Self {
+    a + (b - a) * t
+  }
+}
+impl Tweenable for Vec2 {
+  fn interpolate(a: Self, b: Self, t: f32) -> Self {
+    Vec2::lerp(a, b, t)
+  }
+}
+impl Tweenable for Vec3 {
+  fn interpolate(a: Self, b: Self, t: f32) -> Self {
+    Vec3::lerp(a, b, t)
+  }
+}
+
+/// interpolates from start to end over duration (in seconds) using an easing function
+#[derive(Debug, Clone, Copy)]
+pub struct Tween<T: Tweenable> {
+  pub start: T,
+  pub end: T,
+  pub duration: f32,
+  elapsed: f32,
+  easing: fn(f32) -> f32,
+}
+impl<T: Tweenable> Tween<T> {
+  pub fn new(start: T, end: T, duration: f32, easing: fn(f32) -> f32) -> Self {
+    Self { start, end, duration, elapsed: 0.0, easing }
+  }
+  /// advance by dt seconds and return the current value
+  pub fn update(&mut self, dt: f32) -> T {
+    self.elapsed = (self.elapsed + dt).min(self.duration);
+    self.value()
+  }
+  pub fn value(&self) -> T {
+    let t = if self.duration > 0.0 { self.elapsed / self.duration } else { 1.0 };
+    T::interpolate(self.start, self.end, (self.easing)(t))
+  }
+  pub fn finished(&self) -> bool {
+    self.elapsed >= self.duration
+  }
+  pub fn reset(&mut self) {
+    self.elapsed = 0.0;
+  }
+  /// restart from the current value towards a new end
+  pub fn retarget(&mut self, end: T) {
+    self.start = self.value();
+    self.end = end;
+    self.elapsed = 0.0;
+  }
+}
+
+#[cfg(test)]
+mod ease_tests {
+  use super::*;
+
+  #[test]
+  fn easing_endpoints() {
+    let fns: [fn(f32) -> f32; 10] = [
+      linear, ease_in_quad, ease_out_quad, ease_in_out_quad, ease_in_cubic,
+      ease_out_cubic, ease_in_out_cubic, ease_out_back, ease_out_elastic, ease_out_bounce
+    ];
+    for f in fns {
+      assert!(f(0.0).abs() < 0.0001);
+      assert!((f(1.0) - 1.0).abs() < 0.0001);
+    }
+  }
+
+  #[test]
+  fn tween_progress() {
+    let mut tw = Tween::new(0.0, 10.0, 2.0, linear);
+    assert_eq!(tw.update(1.0), 5.0);
+    assert!(!tw.finished());
+    assert_eq!(tw.update(5.0), 10.0);
+    assert!(tw.finished());
+  }
+}
\ No newline at end of file
@@ -1,6 +1,6 @@
 #![allow(dead_code)]
 
-use std::ops::{Add, AddAssign, Mul, Sub, SubAssign};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
 use bytemuck::{Pod, Zeroable};
 
 use super::PI;
@@ -355,6 +355,31 @@ impl Mat4 {
       1.0,
     ]
   }
+  // rotation matrix from a unit quaternion (x, y, z, w)
+  pub fn rotate_quat(q: &Vec4) -> [f32; 16] {
+    let (x, y, z, w) = (q.x, q.y, q.z, q.w);
+    [
+      1.0 - 2.0 * (y * y + z * z),
+      2.0 * (x * y + z * w),
+      2.0 * (x * z - y * w),
+      0.0,
+
+      2.0 * (x * y - z * w),
+      1.0 - 2.0 * (x * x + z * z),
+      2.0 * (y * z + x * w),
+      0.0,
+
+      2.0 * (x * z + y * w),
+      2.0 * (y * z - x * w),
+      1.0 - 2.0 * (x * x + y * y),
+      0.0,
+
+      0.0,
+      0.0,
+      0.0,
+      1.0
+    ]
+  }
   pub fn scale(x: f32, y: f32, z: f32) -> [f32; 16] {
     [
       x, 0.0, 0.0, 0.0,
@@ -476,6 +501,41 @@ impl Mat4 {
 
     dst
   }
+  // note: assumes matrix was composed as translate * rotate * scale
+  // returns (translation, rotation quaternion, scale)
+  pub fn decompose(&self) -> (Vec3, Vec4, Vec3) {
+    let translation = Vec3::new(self.a03, self.a13, self.a23);
+    let col0 = Vec3::new(self.a00, self.a10, self.a20);
+    let col1 = Vec3::new(self.a01, self.a11, self.a21);
+    let col2 = Vec3::new(self.a02, self.a12, self.a22);
+    let mut scale = Vec3::new(col0.magnitude(), col1.magnitude(), col2.magnitude());
+    // negative determinant means one axis is mirrored
+    if col0.dot(col1.cross(col2)) < 0.0 { scale.x = -scale.x; }
+    // normalized basis
+    let safe = |v: f32| if v.abs() < 0.00001 { 1.0 } else { v };
+    let r0 = col0 / safe(scale.x);
+    let r1 = col1 / safe(scale.y);
+    let r2 = col2 / safe(scale.z);
+    let (m00, m01, m02) = (r0.x, r1.x, r2.x);
+    let (m10, m11, m12) = (r0.y, r1.y, r2.y);
+    let (m20, m21, m22) = (r0.z, r1.z, r2.z);
+    // rotation matrix to quaternion
+    let trace = m00 + m11 + m22;
+    let rotation = if trace > 0.0 {
+      let s = f32::sqrt(trace + 1.0) * 2.0;
+      Vec4::new((m21 - m12) / s, (m02 - m20) / s, (m10 - m01) / s, 0.25 * s)
+    } else if m00 > m11 && m00 > m22 {
+      let s = f32::sqrt(1.0 + m00 - m11 - m22) * 2.0;
+      Vec4::new(0.25 * s, (m01 + m10) / s, (m02 + m20) / s, (m21 - m12) / s)
+    } else if m11 > m22 {
+      let s = f32::sqrt(1.0 + m11 - m00 - m22) * 2.0;
+      Vec4::new((m01 + m10) / s, 0.25 * s, (m12 + m21) / s, (m02 - m20) / s)
+    } else {
+      let s = f32::sqrt(1.0 + m22 - m00 - m11) * 2.0;
+      Vec4::new((m02 + m20) / s, (m12 + m21) / s, 0.25 * s, (m10 - m01) / s)
+    };
+    (translation, rotation, scale)
+  }
   pub fn view_rot(cam: &Vec3, target: &Vec3, up: &Vec3) ->  [f32; 16] {
     let fwd = (*cam - *target).normalize();
     let right = up.cross(fwd).normalize();
@@ -487,9 +547,15 @@ impl Mat4 {
       0.0, 0.0, 0.0, 1.0
     ]
   }
+  pub fn look_at(eye: &Vec3, target: &Vec3, up: &Vec3) -> [f32; 16] {
+    let view_t = Mat4::translate_inverse(eye.x, eye.y, eye.z);
+    let view_r = Mat4::view_rot(eye, target, up);
+    Mat4::multiply(&view_r, &view_t)
+  }
 }
 
 #[derive(Debug, Default, PartialEq, Clone, Copy, Pod, Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct Vec4 {
   pub x: f32,
@@ -501,6 +567,25 @@ impl Vec4 {
   pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
     Self { x, y, z, w }
   }
+  pub fn zero() -> Self {
+    Self { x: 0.0, y: 0.0, z: 0.0, w: 0.0 }
+  }
+  pub fn one() -> Self {
+    Self { x: 1.0, y: 1.0, z: 1.0, w: 1.0 }
+  }
+  pub fn unit_x() -> Self {
+    Self { x: 1.0, y: 0.0, z: 0.0, w: 0.0 }
+  }
+  pub fn unit_y() -> Self {
+    Self { x: 0.0, y: 1.0, z: 0.0, w: 0.0 }
+  }
+  pub fn unit_z() -> Self {
+    Self { x: 0.0, y: 0.0, z: 1.0, w: 0.0 }
+  }
+  /// also the identity quaternion
+  pub fn unit_w() -> Self {
+    Self { x: 0.0, y: 0.0, z: 0.0, w: 1.0 }
+  }
   pub fn from_array(arr: [f32; 4]) -> Self {
     Self { x: arr[0], y: arr[1], z: arr[2], w: arr[3] }
   }
@@ -526,6 +611,18 @@ impl Vec4 {
       w: f32::cos(a_rad / 2.0),
     }
   }
+  // spherical interpolation between unit quaternions along the shortest arc
+  pub fn quat_slerp(a: Vec4, b: Vec4, t: f32) -> Self {
+    let mut cos = a.x * b.x + a.y * b.y + a.z * b.z + a.w * b.w;
+    let b = if cos < 0.0 { cos = -cos; b * -1.0 } else { b };
+    // nearly parallel, lerp avoids dividing by sin(0)
+    if cos > 0.9995 {
+      return (a + (b - a) * t).normalize();
+    }
+    let theta = f32::acos(cos);
+    let sin = f32::sin(theta);
+    a * (f32::sin((1.0 - t) * theta) / sin) + b * (f32::sin(t * theta) / sin)
+  }
 }
 impl Add for Vec4 {
   type Output = Vec4;
@@ -555,6 +652,46 @@ impl SubAssign for Vec4 {
     self.w -= rhs.w;
   }
 }
+impl Mul<f32> for Vec4 {
+  type Output = Vec4;
+  fn mul(self, rhs: f32) -> Self::Output {
+    Vec4::new(self.x * rhs, self.y * rhs, self.z * rhs, self.w * rhs)
+  }
+}
+impl Mul<Vec4> for f32 {
+  type Output = Vec4;
+  fn mul(self, rhs: Vec4) -> Self::Output {
+    Vec4::new(self * rhs.x, self * rhs.y, self * rhs.z, self * rhs.w)
+  }
+}
+impl Mul<Vec4> for Vec4 {
+  type Output = Vec4;
+  fn mul(self, rhs: Vec4) -> Self::Output {
+    Vec4::new(self.x * rhs.x, self.y * rhs.y, self.z * rhs.z, self.w * rhs.w)
+  }
+}
+impl MulAssign<f32> for Vec4 {
+  fn mul_assign(&mut self, rhs: f32) {
+    self.x *= rhs;
+    self.y *= rhs;
+    self.z *= rhs;
+    self.w *= rhs;
+  }
+}
+impl Div<f32> for Vec4 {
+  type Output = Vec4;
+  fn div(self, rhs: f32) -> Self::Output {
+    Vec4::new(self.x / rhs, self.y / rhs, self.z / rhs, self.w / rhs)
+  }
+}
+impl DivAssign<f32> for Vec4 {
+  fn div_assign(&mut self, rhs: f32) {
+    self.x /= rhs;
+    self.y /= rhs;
+    self.z /= rhs;
+    self.w /= rhs;
+  }
+}
 
 #[macro_export]
 macro_rules! vec4f {
@@ -564,6 +701,7 @@ macro_rules! vec4f {
 }
 
 #[derive(Debug, Default, PartialEq, Clone, Copy, Pod, Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct Vec3 {
   pub x: f32,
@@ -577,6 +715,18 @@ impl Vec3 {
   pub fn zero() -> Self {
     Self { x:0.0, y:0.0, z:0.0 }
   }
+  pub fn one() -> Self {
+    Self { x: 1.0, y: 1.0, z: 1.0 }
+  }
+  pub fn unit_x() -> Self {
+    Self { x: 1.0, y: 0.0, z: 0.0 }
+  }
+  pub fn unit_y() -> Self {
+    Self { x: 0.0, y: 1.0, z: 0.0 }
+  }
+  pub fn unit_z() -> Self {
+    Self { x: 0.0, y: 0.0, z: 1.0 }
+  }
   pub fn from_vec2(v: Vec2) -> Self {
     Self { x: v.x, y: v.y, z: 0.0 }
   }
@@ -610,6 +760,21 @@ impl Vec3 {
   pub fn xy(&self) -> Vec2 {
     Vec2{ x: self.x, y: self.y }
   }
+  pub fn reflect(&self, normal: Vec3) -> Vec3 {
+    let n = normal.normalize();
+    *self - n * (2.0 * self.dot(n))
+  }
+  pub fn project_onto(&self, other: Vec3) -> Vec3 {
+    let d = other.dot(other);
+    if d < 0.00001 { return Vec3::new(0.0, 0.0, 0.0) };
+    other * (self.dot(other) / d)
+  }
+  pub fn lerp(a: Vec3, b: Vec3, t: f32) -> Vec3 {
+    a + (b - a) * t
+  }
+  pub fn distance(a: Vec3, b: Vec3) -> f32 {
+    (b - a).magnitude()
+  }
 }
 impl Add for Vec3 {
   type Output = Vec3;
@@ -637,6 +802,44 @@ impl SubAssign for Vec3 {
     self.z -= rhs.z;
   }
 }
+impl Mul<f32> for Vec3 {
+  type Output = Vec3;
+  fn mul(self, rhs: f32) -> Self::Output {
+    Vec3::new(self.x * rhs, self.y * rhs, self.z * rhs)
+  }
+}
+impl Mul<Vec3> for f32 {
+  type Output = Vec3;
+  fn mul(self, rhs: Vec3) -> Self::Output {
+    Vec3::new(self * rhs.x, self * rhs.y, self * rhs.z)
+  }
+}
+impl Mul<Vec3> for Vec3 {
+  type Output = Vec3;
+  fn mul(self, rhs: Vec3) -> Self::Output {
+    Vec3::new(self.x * rhs.x, self.y * rhs.y, self.z * rhs.z)
+  }
+}
+impl MulAssign<f32> for Vec3 {
+  fn mul_assign(&mut self, rhs: f32) {
+    self.x *= rhs;
+    self.y *= rhs;
+    self.z *= rhs;
+  }
+}
+impl Div<f32> for Vec3 {
+  type Output = Vec3;
+  fn div(self, rhs: f32) -> Self::Output {
+    Vec3::new(self.x / rhs, self.y / rhs, self.z / rhs)
+  }
+}
+impl DivAssign<f32> for Vec3 {
+  fn div_assign(&mut self, rhs: f32) {
+    self.x /= rhs;
+    self.y /= rhs;
+    self.z /= rhs;
+  }
+}
 
 #[macro_export]
 macro_rules! vec3f {
@@ -646,6 +849,7 @@ macro_rules! vec3f {
 }
 
 #[derive(Debug, Default, PartialEq, Clone, Copy, Pod, Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct Vec2 { pub x: f32, pub y: f32 }
 impl Vec2 {
@@ -655,6 +859,15 @@ impl Vec2 {
   pub fn zero() -> Self {
     Self { x: 0.0, y: 0.0 }
   }
+  pub fn one() -> Self {
+    Self { x: 1.0, y: 1.0 }
+  }
+  pub fn unit_x() -> Self {
+    Self { x: 1.0, y: 0.0 }
+  }
+  pub fn unit_y() -> Self {
+    Self { x: 0.0, y: 1.0 }
+  }
   pub fn from_tuple(t: (f32, f32)) -> Self {
     Vec2 {
       x: t.0,
@@ -681,6 +894,32 @@ impl Vec2 {
   pub fn dot(&self, rhs: Vec2) -> f32 {
     self.x * rhs.x + self.y * rhs.y
   }
+  pub fn rotate(&self, radians: f32) -> Vec2 {
+    let c = f32::cos(radians);
+    let s = f32::sin(radians);
+    Vec2::new(self.x * c - self.y * s, self.y * c + self.x * s)
+  }
+  pub fn angle(&self) -> f32 {
+    f32::atan2(self.y, self.x)
+  }
+  pub fn from_angle(radians: f32) -> Self {
+    Vec2::new(f32::cos(radians), f32::sin(radians))
+  }
+  pub fn lerp(a: Vec2, b: Vec2, t: f32) -> Vec2 {
+    a + (b - a) * t
+  }
+  pub fn reflect(&self, normal: Vec2) -> Vec2 {
+    let n = normal.normalize();
+    *self - n * (2.0 * self.dot(n))
+  }
+  pub fn project_onto(&self, other: Vec2) -> Vec2 {
+    let d = other.dot(other);
+    if d < 0.00001 { return Vec2::new(0.0, 0.0) };
+    other * (self.dot(other) / d)
+  }
+  pub fn distance(a: Vec2, b: Vec2) -> f32 {
+    (b - a).magnitude()
+  }
 }
 impl Add for Vec2 {
   type Output = Vec2;
@@ -709,17 +948,37 @@ impl SubAssign for Vec2 {
 impl Mul<f32> for Vec2 {
   type Output = Vec2;
   fn mul(self, rhs: f32) -> Self::Output {
-    let x = self.x * rhs;
-    let y = self.y * rhs;
-    Vec2 { x, y }
+    Vec2::new(self.x * rhs, self.y * rhs)
   }
 }
 impl Mul<Vec2> for f32 {
   type Output = Vec2;
   fn mul(self, rhs: Vec2) -> Self::Output {
-    let x = self * rhs.x;
-    let y = self * rhs.y;
-    Vec2 { x, y }
+    Vec2::new(self * rhs.x, self * rhs.y)
+  }
+}
+impl Mul<Vec2> for Vec2 {
+  type Output = Vec2;
+  fn mul(self, rhs: Vec2) -> Self::Output {
+    Vec2::new(self.x * rhs.x, self.y * rhs.y)
+  }
+}
+impl MulAssign<f32> for Vec2 {
+  fn mul_assign(&mut self, rhs: f32) {
+    self.x *= rhs;
+    self.y *= rhs;
+  }
+}
+impl Div<f32> for Vec2 {
+  type Output = Vec2;
+  fn div(self, rhs: f32) -> Self::Output {
+    Vec2::new(self.x / rhs, self.y / rhs)
+  }
+}
+impl DivAssign<f32> for Vec2 {
+  fn div_assign(&mut self, rhs: f32) {
+    self.x /= rhs;
+    self.y /= rhs;
   }
 }
 impl Into<[f32; 2]> for Vec2 {
@@ -778,6 +1037,28 @@ mod lin_alg_tests {
     assert_eq!(a, b);
   }
   #[test]
+  fn mat4_rotate_quat() {
+    let axis = Vec3::new(0.0, 1.0, 0.0);
+    let a = Mat4::rotate(&axis, 90.0);
+    let b = Mat4::rotate_quat(&Vec4::quat_from_axis_angle(axis, PI / 2.0));
+    for i in 0..16 { assert!((a[i] - b[i]).abs() < 1e-5); }
+  }
+  #[test]
+  fn quat_slerp_midpoint() {
+    let axis = Vec3::new(0.0, 0.0, 1.0);
+    let a = Vec4::quat_from_axis_angle(axis, 0.0);
+    let b = Vec4::quat_from_axis_angle(axis, PI / 2.0);
+    let mid = Vec4::quat_slerp(a, b, 0.5);
+    let ans = Vec4::quat_from_axis_angle(axis, PI / 4.0);
+    assert!((mid - ans).magnitude() < 1e-5);
+  }
+  #[test]
+  fn vec_axis_constants() {
+    assert_eq!(Vec3::unit_x().cross(Vec3::unit_y()), Vec3::unit_z());
+    assert_eq!(Vec2::unit_x() + Vec2::unit_y(), Vec2::one());
+    assert_eq!(Vec4::unit_x() + Vec4::unit_y() + Vec4::unit_z() + Vec4::unit_w(), Vec4::one());
+  }
+  #[test]
   fn mat4_transpose() {
     let o = Mat4::transpose(&[
       1.0, 2.0, 3.0, 4.0,
@@ -810,6 +1091,47 @@ mod lin_alg_tests {
     assert_eq!(o, ans);
   }
   #[test]
+  fn vec_scalar_ops() {
+    let mut a = Vec3::new(1.0, 2.0, 3.0) * 2.0;
+    assert_eq!(a, Vec3::new(2.0, 4.0, 6.0));
+    a /= 2.0;
+    assert_eq!(a, Vec3::new(1.0, 2.0, 3.0));
+    let b = Vec2::new(2.0, 3.0) * Vec2::new(4.0, 0.5);
+    assert_eq!(b, Vec2::new(8.0, 1.5));
+    let c = 0.5 * Vec4::new(2.0, 4.0, 6.0, 8.0);
+    assert_eq!(c, Vec4::new(1.0, 2.0, 3.0, 4.0));
+  }
+  #[test]
+  fn vec2_rotate() {
+    let v = Vec2::new(1.0, 0.0).rotate(PI / 2.0);
+    assert!((v.x - 0.0).abs() < 0.0001);
+    assert!((v.y - 1.0).abs() < 0.0001);
+    assert!((v.angle() - PI / 2.0).abs() < 0.0001);
+  }
+  #[test]
+  fn mat4_decompose() {
+    let axis = Vec3::new(1.0, 2.0, 0.5).normalize();
+    let t = Mat4::translate(3.0, -4.0, 5.0);
+    let r = Mat4::rotate(&axis, 70.0);
+    let s = Mat4::scale(2.0, 0.5, 1.5);
+    let trs = Mat4::multiply(&t, &Mat4::multiply(&r, &s));
+    let (tl, rot, sc) = Mat4::from_col_major(trs).decompose();
+    let q = Vec4::quat_from_axis_angle(axis, 70.0 * PI / 180.0);
+    assert!((tl - Vec3::new(3.0, -4.0, 5.0)).magnitude() < 0.0001);
+    assert!((sc - Vec3::new(2.0, 0.5, 1.5)).magnitude() < 0.0001);
+    assert!((rot - q).magnitude() < 0.0001);
+  }
+  #[test]
+  fn mat4_look_at() {
+    let eye = Vec3::new(10.0, 20.0, 200.0);
+    let target = Vec3::new(0.0, 5.0, 0.0);
+    let up = Vec3::new(0.0, 1.0, 0.0);
+    let view_t = Mat4::translate(-eye.x, -eye.y, -eye.z);
+    let view_r = Mat4::view_rot(&eye, &target, &up);
+    let manual = Mat4::multiply(&view_r, &view_t);
+    assert_eq!(Mat4::look_at(&eye, &target, &up), manual);
+  }
+  #[test]
   fn mvp_test() {
     // model
     let model_r = Mat4::rotate(&Vec3::new(0.0, 1.0, 0.0), 0.0);
@@ -8,5 +8,9 @@ mod lin_alg;
 pub use lin_alg::*;
 mod physics;
 pub use physics::*;
+mod ease;
+pub use ease::*;
+mod rng;
+pub use rng::*;
 mod sdf;
 pub use sdf::*;
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use super::*;
 use crate::vec2f;
 
@@ -50,9 +52,151 @@ pub fn cir_to_cir_collision_2d(
   out
 }
 
+// --- --- --- --- --- --- --- //
+// --- Collision Detection --- //
+// --- --- --- --- --- --- --- //
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+  pub min: Vec2,
+  pub max: Vec2,
+}
+impl Aabb {
+  pub fn new(min: Vec2, max: Vec2) -> Self {
+    Self { min, max }
+  }
+  pub fn from_center(center: Vec2, size: Vec2) -> Self {
+    let half = size * 0.5;
+    Self { min: center - half, max: center + half }
+  }
+  pub fn center(&self) -> Vec2 {
+    (self.min + self.max) * 0.5
+  }
+  pub fn half_size(&self) -> Vec2 {
+    (self.max - self.min) * 0.5
+  }
+}
+
+/// axis aligned box in 3d (mesh bounds)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb3 {
+  pub min: Vec3,
+  pub max: Vec3,
+}
+impl Aabb3 {
+  pub fn new(min: Vec3, max: Vec3) -> Self {
+    Self { min, max }
+  }
+  pub fn center(&self) -> Vec3 {
+    (self.min + self.max) * 0.5
+  }
+  pub fn half_size(&self) -> Vec3 {
+    (self.max - self.min) * 0.5
+  }
+}
+
+/// overlap between two shapes, normal points from the first shape towards the second
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact2D {
+  pub normal: Vec2,
+  pub depth: f32,
+}
+
+pub fn circle_vs_circle(p1: Vec2, r1: f32, p2: Vec2, r2: f32) -> Option<Contact2D> {
+  let diff = p2 - p1;
+  let dist = diff.magnitude();
+  if dist >= r1 + r2 { return None; }
+  // concentric circles have no meaningful direction, push along x
+  let normal = if dist > 0.0 { diff * (1.0 / dist) } else { vec2f!(1.0, 0.0) };
+  Some(Contact2D { normal, depth: r1 + r2 - dist })
+}
+
+pub fn aabb_vs_aabb(a: &Aabb, b: &Aabb) -> Option<Contact2D> {
+  let diff = b.center() - a.center();
+  let overlap_x = a.half_size().x + b.half_size().x - diff.x.abs();
+  let overlap_y = a.half_size().y + b.half_size().y - diff.y.abs();
+  if overlap_x <= 0.0 || overlap_y <= 0.0 { return None; }
+  // separate along the axis of least penetration
+  if overlap_x < overlap_y {
+    let sx = if diff.x < 0.0 { -1.0 } else { 1.0 };
+    Some(Contact2D { normal: vec2f!(sx, 0.0), depth: overlap_x })
+  } else {
+    let sy = if diff.y < 0.0 { -1.0 } else { 1.0 };
+    Some(Contact2D { normal: vec2f!(0.0, sy), depth: overlap_y })
+  }
+}
+
+/// impulse-based velocity changes (dv1, dv2) for two bodies colliding along normal.
+/// a mass of 0 or less is treated as immovable
+pub fn resolve_collision(
+  v1: Vec2, m1: f32, v2: Vec2, m2: f32, normal: Vec2, restitution: f32
+) -> (Vec2, Vec2) {
+  let inv_m1 = if m1 > 0.0 { 1.0 / m1 } else { 0.0 };
+  let inv_m2 = if m2 > 0.0 { 1.0 / m2 } else { 0.0 };
+  let vel_along_normal = (v2 - v1).dot(normal);
+  // already separating or both immovable
+  if vel_along_normal > 0.0 || inv_m1 + inv_m2 == 0.0 {
+    return (Vec2::zero(), Vec2::zero());
+  }
+  let j = -(1.0 + restitution) * vel_along_normal / (inv_m1 + inv_m2);
+  (normal * (-j * inv_m1), normal * (j * inv_m2))
+}
+
+// --- --- --- --- --- --- --- //
+// ---    Broad Phase      --- //
+// --- --- --- --- --- --- --- //
+/// uniform grid bucketing objects by the cells their bounding circle touches
+#[derive(Debug, Clone)]
+pub struct SpatialHash {
+  cell_size: f32,
+  cells: HashMap<(i32, i32), Vec<usize>>,
+}
+impl SpatialHash {
+  pub fn new(cell_size: f32) -> Self {
+    Self {
+      cell_size: cell_size.max(f32::EPSILON),
+      cells: HashMap::new(),
+    }
+  }
+  fn cell_of(&self, pos: Vec2) -> (i32, i32) {
+    ((pos.x / self.cell_size).floor() as i32, (pos.y / self.cell_size).floor() as i32)
+  }
+  pub fn insert(&mut self, id: usize, pos: Vec2, radius: f32) {
+    let r = vec2f!(radius, radius);
+    let (x0, y0) = self.cell_of(pos - r);
+    let (x1, y1) = self.cell_of(pos + r);
+    for x in x0..=x1 {
+      for y in y0..=y1 {
+        self.cells.entry((x, y)).or_default().push(id);
+      }
+    }
+  }
+  pub fn clear(&mut self) {
+    // keep allocations around for the next frame
+    for bucket in self.cells.values_mut() {
+      bucket.clear();
+    }
+  }
+  /// unique (lower id, higher id) pairs of objects sharing at least one cell
+  pub fn potential_pairs(&self) -> impl Iterator<Item = (usize, usize)> {
+    let mut pairs: Vec<(usize, usize)> = Vec::new();
+    for bucket in self.cells.values() {
+      for (i, a) in bucket.iter().enumerate() {
+        for b in &bucket[i + 1..] {
+          if a != b { pairs.push((*a.min(b), *a.max(b))); }
+        }
+      }
+    }
+    // objects spanning several cells show up more than once
+    pairs.sort_unstable();
+    pairs.dedup();
+    pairs.into_iter()
+  }
+}
+
 #[cfg(test)]
 mod physics_tests {
   use super::*;
+  use crate::vec3f;
   // use `cargo test physics_tests -- --nocapture` for logging
   #[test]
   fn signed_dist_rect() {
@@ -75,4 +219,103 @@ mod physics_tests {
     let d4 = signed_dist_to_rect(p4, c, size, None);
     assert_eq!(d4, 0.0);
   }
+
+  #[test]
+  fn signed_dist_polygon_star() {
+    let c = vec2f!(0.0, 0.0);
+    // hexagon: vertex at +x, flat edge at apothem r * cos(30)
+    let apothem = 10.0 * f32::cos(PI / 6.0);
+    assert!(f32::abs(signed_dist_to_polygon(vec2f!(12.0, 0.0), c, 10.0, 6, 0.0) - 2.0) < 1e-4);
+    assert!(f32::abs(signed_dist_to_polygon(vec2f!(0.0, apothem + 1.0), c, 10.0, 6, 0.0) - 1.0) < 1e-4);
+    assert!(signed_dist_to_polygon(c, c, 10.0, 6, 0.0) < 0.0);
+    // star: tips are on the outer radius
+    assert!(f32::abs(signed_dist_to_star(vec2f!(11.0, 0.0), c, 10.0, 5, 0.5, 0.0) - 1.0) < 1e-4);
+    assert!(signed_dist_to_star(vec2f!(4.0, 0.0), c, 10.0, 5, 0.5, 0.0) < 0.0);
+    // between tips, the inner vertex sits at r * inner_ratio
+    let dir = vec2f!(f32::cos(PI / 5.0), f32::sin(PI / 5.0));
+    assert!(signed_dist_to_star(dir * 4.9, c, 10.0, 5, 0.5, 0.0) < 0.0);
+    assert!(signed_dist_to_star(dir * 5.1, c, 10.0, 5, 0.5, 0.0) > 0.0);
+  }
+
+  #[test]
+  fn signed_dist_pie() {
+    let c = vec2f!(0.0, 0.0);
+    // quarter wedge in the +x/+y quadrant
+    assert!(signed_dist_to_pie(vec2f!(3.0, 3.0), c, 10.0, 0.0, 90.0) < 0.0);
+    assert!(f32::abs(signed_dist_to_pie(vec2f!(3.0, -2.0), c, 10.0, 0.0, 90.0) - 2.0) < 1e-4);
+    assert!(f32::abs(signed_dist_to_pie(vec2f!(0.0, 12.0), c, 10.0, 0.0, 90.0) - 2.0) < 1e-4);
+    assert!(f32::abs(signed_dist_to_pie(vec2f!(-3.0, -4.0), c, 10.0, 0.0, 90.0) - 5.0) < 1e-4);
+  }
+
+  #[test]
+  fn sdf_normals() {
+    let objs = vec![SDFObject::circle(vec2f!(0.0, 0.0), 5.0)];
+    let n = sdf_normal(vec2f!(8.0, 0.0), &objs);
+    assert!(f32::abs(n.x - 1.0) < 1e-3 && f32::abs(n.y) < 1e-3);
+    let g = sdf_gradient(vec2f!(0.0, -8.0), &objs);
+    assert!(f32::abs(g.magnitude() - 1.0) < 1e-2 && g.y < 0.0);
+  }
+
+  #[test]
+  fn signed_dist_3d() {
+    let objs = vec![
+      SDFObject3D::sphere(vec3f!(0.0, 0.0, 0.0), 2.0),
+      SDFObject3D::cuboid(vec3f!(10.0, 0.0, 0.0), vec3f!(1.0, 1.0, 1.0)),
+      SDFObject3D::plane(vec3f!(0.0, 1.0, 0.0), -5.0),
+    ];
+    assert_eq!(calculate_sdf3d(vec3f!(0.0, 0.0, 3.0), 100.0, &objs), 1.0);
+    assert_eq!(calculate_sdf3d(vec3f!(10.0, 0.0, 0.5), 100.0, &objs), -0.5);
+    assert_eq!(calculate_sdf3d(vec3f!(30.0, -4.0, 0.0), 100.0, &objs), 1.0);
+  }
+
+  #[test]
+  fn circle_collision() {
+    let hit = circle_vs_circle(vec2f!(0.0, 0.0), 2.0, vec2f!(3.0, 0.0), 2.0).unwrap();
+    assert_eq!(hit.normal, vec2f!(1.0, 0.0));
+    assert_eq!(hit.depth, 1.0);
+
+    let miss = circle_vs_circle(vec2f!(0.0, 0.0), 1.0, vec2f!(0.0, 3.0), 1.0);
+    assert!(miss.is_none());
+  }
+
+  #[test]
+  fn aabb_collision() {
+    let a = Aabb::from_center(vec2f!(0.0, 0.0), vec2f!(4.0, 4.0));
+    let b = Aabb::from_center(vec2f!(0.0, -3.0), vec2f!(4.0, 4.0));
+    let hit = aabb_vs_aabb(&a, &b).unwrap();
+    assert_eq!(hit.normal, vec2f!(0.0, -1.0));
+    assert_eq!(hit.depth, 1.0);
+
+    let c = Aabb::new(vec2f!(5.0, 5.0), vec2f!(6.0, 6.0));
+    assert!(aabb_vs_aabb(&a, &c).is_none());
+  }
+
+  #[test]
+  fn collision_impulse() {
+    // equal masses with full restitution swap velocities
+    let n = vec2f!(1.0, 0.0);
+    let (dv1, dv2) = resolve_collision(vec2f!(1.0, 0.0), 1.0, vec2f!(-1.0, 0.0), 1.0, n, 1.0);
+    assert_eq!(dv1, vec2f!(-2.0, 0.0));
+    assert_eq!(dv2, vec2f!(2.0, 0.0));
+
+    // separating bodies are left alone
+    let (dv1, dv2) = resolve_collision(vec2f!(-1.0, 0.0), 1.0, vec2f!(1.0, 0.0), 1.0, n, 1.0);
+    assert_eq!(dv1, Vec2::zero());
+    assert_eq!(dv2, Vec2::zero());
+  }
+
+  #[test]
+  fn spatial_hash_pairs() {
+    let mut grid = SpatialHash::new(10.0);
+    grid.insert(0, vec2f!(1.0, 1.0), 2.0);
+    grid.insert(1, vec2f!(4.0, 2.0), 2.0);
+    grid.insert(2, vec2f!(55.0, 55.0), 2.0);
+    // straddles a cell boundary with object 1
+    grid.insert(3, vec2f!(9.0, 2.0), 3.0);
+    let pairs: Vec<(usize, usize)> = grid.potential_pairs().collect();
+    assert_eq!(pairs, vec![(0, 1), (0, 3), (1, 3)]);
+
+    grid.clear();
+    assert_eq!(grid.potential_pairs().count(), 0);
+  }
 }
\ No newline at end of file
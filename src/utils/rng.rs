@@ -0,0 +1,97 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+// std SystemTime is unavailable in browsers
+#[cfg(target_arch = "wasm32")]
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use super::*;
+
+/// small seedable xorshift64* generator, deterministic across runs for the same seed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rng {
+  state: u64,
+}
+impl Rng {
+  pub fn new(seed: u64) -> Self {
+    // scramble seed with splitmix64 so nearby seeds diverge (and 0 is valid)
+    let mut z = seed.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^= z >> 31;
+    Self { state: if z == 0 { 0x9E3779B97F4A7C15 } else { z } }
+  }
+  /// seed from the system clock (not reproducible)
+  pub fn from_time() -> Self {
+    let nanos = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|d| d.as_nanos() as u64)
+      .unwrap_or(0);
+    Self::new(nanos)
+  }
+  pub fn next_u64(&mut self) -> u64 {
+    self.state ^= self.state >> 12;
+    self.state ^= self.state << 25;
+    self.state ^= self.state >> 27;
+    self.state.wrapping_mul(0x2545F4914F6CDD1D)
+  }
+  pub fn next_u32(&mut self) -> u32 {
+    (self.next_u64() >> 32) as u32
+  }
+  /// uniform in [0, 1)
+  pub fn next_f32(&mut self) -> f32 {
+    (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+  }
+  /// uniform in [min, max)
+  pub fn range(&mut self, min: f32, max: f32) -> f32 {
+    min + (max - min) * self.next_f32()
+  }
+  /// uniform in [min, max)
+  pub fn range_i32(&mut self, min: i32, max: i32) -> i32 {
+    if max <= min { return min; }
+    let span = (max as i64 - min as i64) as u64;
+    (min as i64 + (self.next_u64() % span) as i64) as i32
+  }
+  pub fn next_bool(&mut self) -> bool {
+    self.next_u64() >> 63 == 1
+  }
+  /// uniformly distributed point inside a circle
+  pub fn next_vec2_in_circle(&mut self, radius: f32) -> Vec2 {
+    let r = radius * self.next_f32().sqrt();
+    Vec2::from_angle(self.range(0.0, 2.0 * PI)) * r
+  }
+  /// random direction of length 1
+  pub fn next_unit_vec3(&mut self) -> Vec3 {
+    let z = self.range(-1.0, 1.0);
+    let xy = Vec2::from_angle(self.range(0.0, 2.0 * PI)) * (1.0 - z * z).sqrt();
+    Vec3::new(xy.x, xy.y, z)
+  }
+}
+
+#[cfg(test)]
+mod rng_tests {
+  use super::*;
+
+  #[test]
+  fn rng_deterministic() {
+    let mut a = Rng::new(42);
+    let mut b = Rng::new(42);
+    for _ in 0..100 {
+      assert_eq!(a.next_u64(), b.next_u64());
+    }
+    let mut c = Rng::new(43);
+    assert_ne!(a.next_u64(), c.next_u64());
+  }
+
+  #[test]
+  fn rng_ranges() {
+    let mut rng = Rng::new(7);
+    for _ in 0..1000 {
+      let f = rng.range(-2.0, 3.0);
+      assert!((-2.0..3.0).contains(&f));
+      let i = rng.range_i32(-5, 5);
+      assert!((-5..5).contains(&i));
+      let v = rng.next_vec2_in_circle(4.0);
+      assert!(v.magnitude() <= 4.0 + 0.0001);
+    }
+  }
+}
\ No newline at end of file
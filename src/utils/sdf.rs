@@ -2,9 +2,12 @@ use super::*;
 use crate::vec2f;
 
 #[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SDFObjectType {
   #[default]
   None, Circle, Rectangle, Triangle, RectAngled, Line, Pie,
+  Polygon { sides: u32 },
+  Star { points: u32, inner_ratio: f32 },
 }
 impl From<SDFObjectType> for u32 {
   fn from(value: SDFObjectType) -> Self {
@@ -14,12 +17,16 @@ impl From<SDFObjectType> for u32 {
       SDFObjectType::Triangle => 3,
       SDFObjectType::RectAngled => 4,
       SDFObjectType::Line => 5,
+      SDFObjectType::Pie => 6,
+      SDFObjectType::Polygon { .. } => 7,
+      SDFObjectType::Star { .. } => 8,
       _ => 0,
     }
   }
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SDFObject {
   pub obj_type: SDFObjectType,
   pub center: Vec2,
@@ -29,6 +36,8 @@ pub struct SDFObject {
   pub rotation: f32,
   pub line_thickness: f32,
   pub tri_size: (Vec2, Vec2),
+  // start + end angle in degrees, counter-clockwise from +x
+  pub pie_angles: (f32, f32),
 }
 impl Default for SDFObject {
   fn default() -> Self {
@@ -40,7 +49,8 @@ impl Default for SDFObject {
       corner_radius: 0.0,
       rotation: 0.0,
       line_thickness: 0.0,
-      tri_size: (Vec2::zero(), Vec2::zero())
+      tri_size: (Vec2::zero(), Vec2::zero()),
+      pie_angles: (0.0, 360.0),
     }
   }
 }
@@ -85,6 +95,36 @@ impl SDFObject {
       ..Default::default()
     }
   }
+  /// circle wedge from start_angle to end_angle (degrees, counter-clockwise from +x)
+  pub fn pie(pos: Vec2, r: f32, start_angle: f32, end_angle: f32) -> Self {
+    Self {
+      obj_type: SDFObjectType::Pie,
+      center: pos,
+      radius: r,
+      pie_angles: (start_angle, end_angle),
+      ..Default::default()
+    }
+  }
+  /// regular n-gon with a vertex at `angle` degrees from +x (e.g. 6 sides for a hexagon)
+  pub fn polygon(pos: Vec2, r: f32, sides: u32, angle: f32) -> Self {
+    Self {
+      obj_type: SDFObjectType::Polygon { sides: u32::max(sides, 3) },
+      center: pos,
+      radius: r,
+      rotation: angle,
+      ..Default::default()
+    }
+  }
+  /// star with outer radius r, inner vertices at r * inner_ratio
+  pub fn star(pos: Vec2, r: f32, points: u32, inner_ratio: f32, angle: f32) -> Self {
+    Self {
+      obj_type: SDFObjectType::Star { points: u32::max(points, 2), inner_ratio: f32::clamp(inner_ratio, 0.0, 1.0) },
+      center: pos,
+      radius: r,
+      rotation: angle,
+      ..Default::default()
+    }
+  }
   pub fn with_corner(mut self, radius: f32) -> Self {
     self.corner_radius = radius;
     self
@@ -109,10 +149,7 @@ pub fn signed_dist_to_rect(
   point: Vec2, rect_center: Vec2, rect_size: Vec2, rect_rotation: Option<f32>
 ) -> f32 {
   let rot_p = if let Some(r) = rect_rotation {
-    let rad = r.to_radians();
-    let x = (point.x - rect_center.x) * f32::cos(-rad) - (point.y - rect_center.y) * f32::sin(-rad) + rect_center.x;
-    let y = (point.y - rect_center.y) * f32::cos(-rad) + (point.x - rect_center.x) * f32::sin(-rad) + rect_center.y;
-    vec2f!(x, y)
+    (point - rect_center).rotate(-r.to_radians()) + rect_center
   } else { point };
   let mut abs_p = rot_p - rect_center;
   if abs_p.x < 0.0 { abs_p.x = -abs_p.x };
@@ -169,6 +206,47 @@ pub fn signed_dist_to_line(point: Vec2, p0: Vec2, p1: Vec2) -> f32 {
   (pa - ba * h).magnitude()
 }
 
+// circle clipped to the wedge between two angles (degrees)
+pub fn signed_dist_to_pie(point: Vec2, center: Vec2, r: f32, start_angle: f32, end_angle: f32) -> f32 {
+  let half = f32::clamp((end_angle - start_angle) * 0.5, 0.0, 180.0).to_radians();
+  let mid = (start_angle + end_angle) * 0.5;
+  // rotate wedge to be symmetric around +y
+  let mut p = (point - center).rotate((90.0 - mid).to_radians());
+  p.x = f32::abs(p.x);
+  let c = vec2f!(f32::sin(half), f32::cos(half));
+  let l = p.magnitude() - r;
+  let m = (p - c * f32::clamp(p.dot(c), 0.0, r)).magnitude();
+  let side = c.y * p.x - c.x * p.y;
+  f32::max(l, if side > 0.0 { m } else { -m })
+}
+
+// outer vertices at radius r, inner vertices at r * inner_ratio between them
+pub fn signed_dist_to_star(
+  point: Vec2, center: Vec2, r: f32, points: u32, inner_ratio: f32, rotation: f32
+) -> f32 {
+  let np = (point - center).rotate(-rotation.to_radians());
+  // fold into the sector between an outer vertex (angle 0) and the next inner vertex
+  let an = PI / points as f32;
+  let bn = f32::atan2(np.y, np.x).rem_euclid(2.0 * an) - an;
+  let bn = an - f32::abs(bn);
+  let p = vec2f!(f32::cos(bn), f32::sin(bn)) * np.magnitude();
+  let a = vec2f!(r, 0.0);
+  let b = vec2f!(f32::cos(an), f32::sin(an)) * (r * inner_ratio);
+  let ba = b - a;
+  let pa = p - a;
+  let h = f32::clamp(pa.dot(ba) / ba.dot(ba), 0.0, 1.0);
+  let d = (pa - ba * h).magnitude();
+  // inside when on the same side of the edge as the center
+  let side = ba.x * pa.y - ba.y * pa.x;
+  if side > 0.0 { -d } else { d }
+}
+
+pub fn signed_dist_to_polygon(point: Vec2, center: Vec2, r: f32, sides: u32, rotation: f32) -> f32 {
+  // a star with inner vertices on the edge midpoints
+  let inner_ratio = f32::cos(PI / sides as f32);
+  signed_dist_to_star(point, center, r, sides, inner_ratio, rotation)
+}
+
 pub fn signed_dist_with_corner(sd: f32, radius: f32) -> f32 {
   sd - radius
 }
@@ -198,6 +276,15 @@ pub fn calculate_sdf(p: Vec2, max_dist: f32, objs: &Vec<SDFObject>) -> f32 {
       SDFObjectType::Line => {
         d = signed_dist_to_line(p, obj.center, obj.rect_size);
       }
+      SDFObjectType::Pie => {
+        d = signed_dist_to_pie(p, obj.center, obj.radius, obj.pie_angles.0, obj.pie_angles.1);
+      }
+      SDFObjectType::Polygon { sides } => {
+        d = signed_dist_to_polygon(p, obj.center, obj.radius, sides, obj.rotation);
+      }
+      SDFObjectType::Star { points, inner_ratio } => {
+        d = signed_dist_to_star(p, obj.center, obj.radius, points, inner_ratio, obj.rotation);
+      }
       _ => ()
     }
     if obj.corner_radius > 0.0 {
@@ -239,6 +326,20 @@ pub fn calculate_sdf_alt(p: Vec2, max_dist: f32, merge_dist: f32, objs: &Vec<SDF
   f32::sqrt(sdf) - merge_dist
 }
 
+// central differences over calculate_sdf, magnitude ~1 away from shape seams
+pub fn sdf_gradient(p: Vec2, objs: &Vec<SDFObject>) -> Vec2 {
+  let e = 0.01;
+  let max_dist = f32::MAX;
+  let dx = calculate_sdf(p + vec2f!(e, 0.0), max_dist, objs) - calculate_sdf(p - vec2f!(e, 0.0), max_dist, objs);
+  let dy = calculate_sdf(p + vec2f!(0.0, e), max_dist, objs) - calculate_sdf(p - vec2f!(0.0, e), max_dist, objs);
+  vec2f!(dx, dy) / (2.0 * e)
+}
+
+/// direction pointing away from the closest surface
+pub fn sdf_normal(p: Vec2, objs: &Vec<SDFObject>) -> Vec2 {
+  sdf_gradient(p, objs).normalize()
+}
+
 pub fn ray_march_dist(origin: Vec2, dir: Vec2, max_dist: f32, objs: &Vec<SDFObject>) -> f32 {
   let ndir = dir.normalize();
   let mut p = origin;
@@ -254,3 +355,168 @@ pub fn ray_march_dist(origin: Vec2, dir: Vec2, max_dist: f32, objs: &Vec<SDFObje
   if ray_dist > max_dist { max_dist }
   else { ray_dist }
 }
+
+
+// --- 3d sdf objects (see render::SDF3DPipeline) ---
+
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SDFObject3DType {
+  #[default]
+  None, Sphere, Box, Plane,
+}
+impl From<SDFObject3DType> for u32 {
+  fn from(value: SDFObject3DType) -> Self {
+    match value {
+      SDFObject3DType::Sphere => 1,
+      SDFObject3DType::Box => 2,
+      SDFObject3DType::Plane => 3,
+      _ => 0,
+    }
+  }
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SDFObject3D {
+  pub obj_type: SDFObject3DType,
+  pub center: Vec3,
+  // sphere: x = radius, box: half size, plane: normal
+  pub size: Vec3,
+  // box: corner radius, plane: offset along normal
+  pub radius: f32,
+  // soft edge width centered on the surface + glow halo radius outside it
+  pub feather: f32,
+  pub glow: f32,
+}
+impl SDFObject3D {
+  pub fn sphere(pos: Vec3, r: f32) -> Self {
+    Self {
+      obj_type: SDFObject3DType::Sphere,
+      center: pos,
+      size: Vec3::new(r, r, r),
+      ..Default::default()
+    }
+  }
+  pub fn cuboid(pos: Vec3, half_size: Vec3) -> Self {
+    Self {
+      obj_type: SDFObject3DType::Box,
+      center: pos,
+      size: half_size,
+      ..Default::default()
+    }
+  }
+  pub fn plane(normal: Vec3, offset: f32) -> Self {
+    Self {
+      obj_type: SDFObject3DType::Plane,
+      size: normal.normalize(),
+      radius: offset,
+      ..Default::default()
+    }
+  }
+  pub fn with_corner(mut self, radius: f32) -> Self {
+    self.radius = radius;
+    self
+  }
+  pub fn with_feather(mut self, width: f32) -> Self {
+    self.feather = width;
+    self
+  }
+  pub fn with_glow(mut self, radius: f32) -> Self {
+    self.glow = radius;
+    self
+  }
+}
+
+pub fn signed_dist_to_sphere(point: Vec3, center: Vec3, radius: f32) -> f32 {
+  (point - center).magnitude() - radius
+}
+
+pub fn signed_dist_to_box(point: Vec3, center: Vec3, half_size: Vec3) -> f32 {
+  let p = point - center;
+  let q = Vec3::new(f32::abs(p.x), f32::abs(p.y), f32::abs(p.z)) - half_size;
+  let outer = Vec3::new(f32::max(q.x, 0.0), f32::max(q.y, 0.0), f32::max(q.z, 0.0)).magnitude();
+  let inner = f32::min(f32::max(q.x, f32::max(q.y, q.z)), 0.0);
+  outer + inner
+}
+
+// note: normal is expected to be normalized
+pub fn signed_dist_to_plane(point: Vec3, normal: Vec3, offset: f32) -> f32 {
+  point.dot(normal) - offset
+}
+
+pub fn calculate_sdf3d(p: Vec3, max_dist: f32, objs: &[SDFObject3D]) -> f32 {
+  let mut sdf = max_dist;
+  for obj in objs {
+    let d = match obj.obj_type {
+      SDFObject3DType::Sphere => signed_dist_to_sphere(p, obj.center, obj.size.x),
+      SDFObject3DType::Box => {
+        let inset = obj.size - Vec3::new(obj.radius, obj.radius, obj.radius);
+        signed_dist_to_box(p, obj.center, inset) - obj.radius
+      }
+      SDFObject3DType::Plane => signed_dist_to_plane(p, obj.size, obj.radius),
+      _ => max_dist
+    };
+    if d < sdf { sdf = d; }
+  }
+  sdf
+}
+
+// --- saved shape lists ---
+
+/// 2d + 3d sdf shapes loaded together, e.g. a ui layout kept in a file
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SDFScene {
+  pub objects: Vec<SDFObject>,
+  pub objects_3d: Vec<SDFObject3D>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum SDFSceneError {
+  /// file could not be read or written
+  Io(std::io::Error),
+  /// file contents are not a valid scene
+  Json(serde_json::Error),
+}
+#[cfg(feature = "serde")]
+impl std::fmt::Display for SDFSceneError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      SDFSceneError::Io(e) => write!(f, "failed to access scene file: {}", e),
+      SDFSceneError::Json(e) => write!(f, "failed to parse scene: {}", e),
+    }
+  }
+}
+
+#[cfg(feature = "serde")]
+impl SDFScene {
+  pub fn save_json(&self, path: impl AsRef<std::path::Path>) -> Result<(), SDFSceneError> {
+    let json = serde_json::to_string_pretty(self).map_err(SDFSceneError::Json)?;
+    std::fs::write(path, json).map_err(SDFSceneError::Io)
+  }
+  pub fn load_json(path: impl AsRef<std::path::Path>) -> Result<Self, SDFSceneError> {
+    let json = std::fs::read_to_string(path).map_err(SDFSceneError::Io)?;
+    serde_json::from_str(&json).map_err(SDFSceneError::Json)
+  }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod sdf_scene_tests {
+  use super::*;
+
+  #[test]
+  fn sdf_scene_json_roundtrip() {
+    let scene = SDFScene {
+      objects: vec![SDFObject::circle(vec2f!(4.0, 2.0), 3.0)],
+      objects_3d: vec![SDFObject3D::sphere(Vec3::unit_y(), 1.5)],
+    };
+    let path = std::env::temp_dir().join("wgpu_blueprint_sdf_scene.json");
+    scene.save_json(&path).unwrap();
+    let loaded = SDFScene::load_json(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(loaded, scene);
+    assert!(matches!(SDFScene::load_json(&path), Err(SDFSceneError::Io(_))));
+  }
+}
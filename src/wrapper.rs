@@ -1,15 +1,22 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
-use wgpu::{ Device, Queue, Surface, SurfaceConfiguration, TextureFormat };
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+// std Instant is unavailable in browsers
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+use gilrs::{Axis, Button, EventType, Gilrs};
+use wgpu::{ Device, PresentMode, Queue, Surface, SurfaceConfiguration, TextureFormat };
 use winit::{
   application::ApplicationHandler,
   dpi::{Position, PhysicalSize, PhysicalPosition},
-  event::{Ime, KeyEvent, MouseButton, MouseScrollDelta, StartCause, WindowEvent},
+  event::{DeviceEvent, DeviceId, Ime, KeyEvent, MouseButton, MouseScrollDelta, StartCause, WindowEvent},
   event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
-	keyboard::{PhysicalKey, KeyCode},
-  platform::windows::IconExtWindows,
-  window::{Icon, Window, WindowAttributes, WindowId}
+	keyboard::{PhysicalKey, KeyCode, ModifiersState},
+  window::{CursorGrabMode, Fullscreen, Icon, Window, WindowAttributes, WindowId}
 };
 
 use crate::utils::Vec2;
@@ -18,18 +25,230 @@ use crate::utils::Vec2;
 // --- --- ---- APP SETUP ---- --- --- //
 // --- --- --- --- --- --- --- --- --- //
 
+// reasons gpu setup can fail on unsupported machines
+#[derive(Debug)]
+pub enum GpuInitError {
+	NoAdapter,
+	DeviceRequest(wgpu::RequestDeviceError),
+	SurfaceCreation(wgpu::CreateSurfaceError),
+}
+impl std::fmt::Display for GpuInitError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			GpuInitError::NoAdapter => write!(f, "no compatible graphics adapter found (try updating graphics drivers)"),
+			GpuInitError::DeviceRequest(e) => write!(f, "graphics device request failed - {}", e),
+			GpuInitError::SurfaceCreation(e) => write!(f, "could not create window surface - {}", e),
+		}
+	}
+}
+
+const MAX_PROFILED_PASSES: u32 = 16;
+// frames of timestamps that can wait for the gpu before new ones are dropped
+const PROFILER_READBACKS: usize = 3;
+
+// one mappable copy of a frame's timestamps
+#[derive(Debug)]
+struct ProfilerReadback {
+	buffer: wgpu::Buffer,
+	// passes copied into the buffer, 0 while it is free
+	passes: u32,
+	// set by map_async once the gpu is done with the frame
+	ready: Arc<AtomicBool>,
+}
+
+// timestamp queries around render passes, read back a few frames later without blocking
+#[derive(Debug)]
+struct PassProfiler {
+	query_set: wgpu::QuerySet,
+	resolve_buffer: wgpu::Buffer,
+	readbacks: Vec<ProfilerReadback>,
+	// ring slot the next frame copies into
+	next: usize,
+	// slot copied into this frame, mapped after submit
+	copied: Option<usize>,
+	// nanoseconds per timestamp tick
+	period: f32,
+	passes: u32,
+	last_times: Vec<Duration>,
+}
+impl PassProfiler {
+	fn new(device: &Device, queue: &Queue) -> Self {
+		let size = (MAX_PROFILED_PASSES * 2) as u64 * std::mem::size_of::<u64>() as u64;
+		let readbacks = (0..PROFILER_READBACKS).map(|_| ProfilerReadback {
+			buffer: device.create_buffer(&wgpu::BufferDescriptor {
+				label: Some("timestamp-read-buffer"),
+				size,
+				usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+				mapped_at_creation: false,
+			}),
+			passes: 0,
+			ready: Arc::new(AtomicBool::new(false)),
+		}).collect();
+		Self {
+			query_set: device.create_query_set(&wgpu::QuerySetDescriptor {
+				label: Some("pass-timestamps"),
+				ty: wgpu::QueryType::Timestamp,
+				count: MAX_PROFILED_PASSES * 2,
+			}),
+			resolve_buffer: device.create_buffer(&wgpu::BufferDescriptor {
+				label: Some("timestamp-resolve-buffer"),
+				size,
+				usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+				mapped_at_creation: false,
+			}),
+			readbacks,
+			next: 0,
+			copied: None,
+			period: queue.get_timestamp_period(),
+			passes: 0,
+			last_times: Vec::new(),
+		}
+	}
+	fn next_pass(&mut self) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+		if self.passes >= MAX_PROFILED_PASSES { return None; }
+		let i = self.passes * 2;
+		self.passes += 1;
+		Some(wgpu::RenderPassTimestampWrites {
+			query_set: &self.query_set,
+			beginning_of_pass_write_index: Some(i),
+			end_of_pass_write_index: Some(i + 1),
+		})
+	}
+	fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+		if self.passes == 0 { return; }
+		// every readback is still waiting on the gpu, skip this frame
+		let slot = &mut self.readbacks[self.next];
+		if slot.passes > 0 { return; }
+		let n = self.passes * 2;
+		encoder.resolve_query_set(&self.query_set, 0..n, &self.resolve_buffer, 0);
+		let bytes = n as u64 * std::mem::size_of::<u64>() as u64;
+		encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &slot.buffer, 0, bytes);
+		slot.passes = self.passes;
+		self.copied = Some(self.next);
+	}
+	// maps this frame's copy + reads any earlier frame the gpu has finished
+	fn read_back(&mut self, device: &Device) {
+		if let Some(i) = self.copied.take() {
+			let slot = &self.readbacks[i];
+			let ready = slot.ready.clone();
+			let bytes = (slot.passes * 2) as u64 * std::mem::size_of::<u64>() as u64;
+			slot.buffer.slice(0..bytes).map_async(wgpu::MapMode::Read, move |res| {
+				if res.is_ok() { ready.store(true, Ordering::Release); }
+			});
+			self.next = (i + 1) % PROFILER_READBACKS;
+		}
+		device.poll(wgpu::Maintain::Poll);
+		// oldest first, so the newest finished frame ends up in last_times
+		for k in 0..PROFILER_READBACKS {
+			let slot = &mut self.readbacks[(self.next + k) % PROFILER_READBACKS];
+			if slot.passes == 0 || !slot.ready.load(Ordering::Acquire) { continue; }
+			let bytes = (slot.passes * 2) as u64 * std::mem::size_of::<u64>() as u64;
+			{
+				let data = slot.buffer.slice(0..bytes).get_mapped_range();
+				let stamps: &[u64] = bytemuck::cast_slice(&data);
+				self.last_times = stamps.chunks_exact(2).map(|t| {
+					let ticks = t[1].saturating_sub(t[0]);
+					Duration::from_nanos((ticks as f64 * self.period as f64) as u64)
+				}).collect();
+			}
+			slot.buffer.unmap();
+			slot.passes = 0;
+			slot.ready.store(false, Ordering::Release);
+		}
+		self.passes = 0;
+	}
+}
+
 #[derive(Debug)]
 pub struct GpuAccess<'a> {
 	pub device: Device,
 	pub queue: Queue,
-	pub screen_surface: Surface<'a>,
+	// None for headless gpu access
+	pub screen_surface: Option<Surface<'a>>,
 	pub screen_config: SurfaceConfiguration,
 	pub screen_format: TextureFormat,
+	pub present_modes: Vec<PresentMode>,
+	profiler: Option<PassProfiler>,
+	profiling_supported: bool,
+	anisotropy: bool,
+	adapter_info: wgpu::AdapterInfo,
+	// width / height of the letterboxed viewport, None fills the screen
+	aspect_ratio: Option<f32>,
 }
 #[allow(unused)]
 impl GpuAccess<'_> {
+	/// gpu access without a window, for offscreen rendering (e.g. image tests, thumbnails),
+	/// screen rendering is unavailable so draw into textures with render_to_textures (native only).
+	/// backends + power_preference work like the WinitConfig fields
+	pub fn new_headless(
+		width: u32, height: u32, format: TextureFormat,
+		backends: wgpu::Backends, power_preference: wgpu::PowerPreference
+	) -> Result<GpuAccess<'static>, GpuInitError> {
+		let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+			backends,
+			..Default::default()
+		});
+		let adapter = pollster::block_on(instance.request_adapter(
+			&wgpu::RequestAdapterOptions {
+				power_preference,
+				compatible_surface: None,
+				force_fallback_adapter: false,
+			},
+		));
+		let adapter = adapter.ok_or(GpuInitError::NoAdapter)?;
+		let (device, queue, profiling) = pollster::block_on(request_device(&adapter))?;
+		let screen_config = SurfaceConfiguration {
+			usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+			format,
+			width,
+			height,
+			present_mode: PresentMode::AutoNoVsync,
+			alpha_mode: wgpu::CompositeAlphaMode::Auto,
+			view_formats: vec![],
+			desired_maximum_frame_latency: 2,
+		};
+		Ok(GpuAccess {
+			device,
+			queue,
+			screen_surface: None,
+			screen_config,
+			screen_format: format,
+			present_modes: Vec::new(),
+			profiler: None,
+			profiling_supported: profiling,
+			anisotropy: supports_anisotropy(&adapter),
+			adapter_info: adapter.get_info(),
+			aspect_ratio: None,
+		})
+	}
+	/// optional features negotiated with the adapter
+	pub fn features(&self) -> wgpu::Features {
+		self.device.features()
+	}
+	/// name, backend + device type of the gpu in use (e.g. for bug reports)
+	pub fn adapter_info(&self) -> &wgpu::AdapterInfo {
+		&self.adapter_info
+	}
+	pub fn limits(&self) -> wgpu::Limits {
+		self.device.limits()
+	}
+	/// largest width/height of a 2d texture, bigger images are downscaled by build_texture
+	pub fn max_texture_size(&self) -> u32 {
+		self.device.limits().max_texture_dimension_2d
+	}
+	/// whether samplers may use anisotropy_clamp > 1 (see ObjPipeline::set_anisotropy)
+	pub fn supports_anisotropy(&self) -> bool {
+		self.anisotropy
+	}
+	pub fn is_headless(&self) -> bool {
+		self.screen_surface.is_none()
+	}
 	pub fn begin_render(&mut self) -> Result<(wgpu::CommandEncoder, wgpu::SurfaceTexture), wgpu::SurfaceError> {
-		let output = self.screen_surface.get_current_texture()?;
+		let Some(screen_surface) = &self.screen_surface else {
+			println!("ERR: Cannot render to screen with headless gpu access, render to a texture instead");
+			return Err(wgpu::SurfaceError::Other);
+		};
+		let output = screen_surface.get_current_texture()?;
 		let encoder = self.device.create_command_encoder(
       &wgpu::CommandEncoderDescriptor { label: Some("render-encoder") }
     );
@@ -51,14 +270,88 @@ impl GpuAccess<'_> {
 			..Default::default()
 		});
 	}
-	pub fn end_render(&self, encoder: wgpu::CommandEncoder, surface: wgpu::SurfaceTexture) {
+	pub fn end_render(&mut self, mut encoder: wgpu::CommandEncoder, surface: wgpu::SurfaceTexture) {
+		if let Some(prof) = &mut self.profiler { prof.resolve(&mut encoder); }
 		self.queue.submit(std::iter::once(encoder.finish()));
+		if let Some(prof) = &mut self.profiler { prof.read_back(&self.device); }
 		surface.present();
 	}
+	/// records gpu pass times (on by default with WinitConfig::debug), ignored without TIMESTAMP_QUERY
+	pub fn set_profiling(&mut self, enabled: bool) {
+		if !enabled {
+			self.profiler = None;
+		} else if self.profiling_supported && self.profiler.is_none() {
+			self.profiler = Some(PassProfiler::new(&self.device, &self.queue));
+		}
+	}
+	pub fn is_profiling(&self) -> bool {
+		self.profiler.is_some()
+	}
+	/// timestamp writes for the next render pass in this frame,
+	/// None unless profiling is on (see set_profiling) or once MAX_PROFILED_PASSES is reached
+	pub fn pass_timestamps(&mut self) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+		self.profiler.as_mut().and_then(|p| p.next_pass())
+	}
+	/// gpu time of each pass using pass_timestamps() in the latest frame the gpu has finished, in pass order
+	pub fn last_pass_times(&self) -> Vec<Duration> {
+		match &self.profiler {
+			Some(p) => p.last_times.clone(),
+			None => Vec::new()
+		}
+	}
 	pub fn resize_screen(&mut self, width: u32, height: u32) {
+		// minimized windows report a zero size, which is not a valid surface config
+		if width == 0 || height == 0 { return; }
 		self.screen_config.width = width;
 		self.screen_config.height = height;
-		self.screen_surface.configure(&self.device, &self.screen_config);
+		if let Some(s) = &self.screen_surface { s.configure(&self.device, &self.screen_config); }
+	}
+	/// locks rendering to width / height inside the screen, see WinitConfig::aspect_ratio
+	pub fn set_aspect_ratio(&mut self, aspect_ratio: Option<f32>) {
+		self.aspect_ratio = aspect_ratio.filter(|a| *a > 0.0);
+	}
+	pub fn aspect_ratio(&self) -> Option<f32> {
+		self.aspect_ratio
+	}
+	/// (x, y, width, height) in pixels of the largest centered rect matching the aspect ratio,
+	/// the whole screen without one
+	pub fn viewport(&self) -> [f32; 4] {
+		let w = self.screen_config.width as f32;
+		let h = self.screen_config.height as f32;
+		match self.aspect_ratio {
+			Some(a) if w > 0.0 && h > 0.0 => {
+				if w / h > a {
+					let vw = (h * a).round();
+					[((w - vw) * 0.5).floor(), 0.0, vw, h]
+				} else {
+					let vh = (w / a).round();
+					[0.0, ((h - vh) * 0.5).floor(), w, vh]
+				}
+			}
+			_ => [0.0, 0.0, w, h]
+		}
+	}
+	/// viewport size, use for camera target sizes when an aspect ratio is set
+	pub fn view_size(&self) -> Vec2 {
+		let [_, _, w, h] = self.viewport();
+		Vec2::new(w, h)
+	}
+	/// letterboxes the pass, draws after this stay inside viewport()
+	pub fn set_viewport(&self, pass: &mut wgpu::RenderPass) {
+		let [x, y, w, h] = self.viewport();
+		if w > 0.0 && h > 0.0 { pass.set_viewport(x, y, w, h, 0.0, 1.0); }
+	}
+	pub fn set_present_mode(&mut self, mode: PresentMode) {
+		if !present_mode_supported(mode, &self.present_modes) {
+			println!("ERR: Present mode {:?} is not supported by this surface", mode);
+			return;
+		}
+		self.screen_config.present_mode = mode;
+		// avoid configuring surface before first resize
+		let sized = self.screen_config.width > 0 && self.screen_config.height > 0;
+		if let (Some(s), true) = (&self.screen_surface, sized) {
+			s.configure(&self.device, &self.screen_config);
+		}
 	}
 }
 
@@ -74,7 +367,16 @@ pub struct MouseState {
   pos_delta: Vec2,
 	scroll: f32,
 	cursor_in: bool,
+	last_left_press: Instant,
+	left_press_start: Instant,
+	left_press_duration: Duration,
+	double_click: bool,
+	double_click_window: Duration,
+	grabbed: bool,
+	raw_accum: Vec2,
+	raw_delta: Vec2,
 }
+#[allow(dead_code)]
 impl MouseState {
   fn new() -> Self {
     Self {
@@ -85,6 +387,14 @@ impl MouseState {
       pos_delta: Vec2::new(0.0, 0.0),
 			scroll: 0.0,
 			cursor_in: true,
+			last_left_press: Instant::now(),
+			left_press_start: Instant::now(),
+			left_press_duration: Duration::from_micros(0),
+			double_click: false,
+			double_click_window: Duration::from_millis(400),
+			grabbed: false,
+			raw_accum: Vec2::zero(),
+			raw_delta: Vec2::zero(),
     }
   }
   fn frame_sync(&mut self) {
@@ -92,42 +402,297 @@ impl MouseState {
     let dy = self.instp.y - self.position.y;
     self.pos_delta = Vec2::new(dx, dy);
     self.position = self.instp;
+		// device motion is unbounded by window edges
+		self.raw_delta = self.raw_accum;
+		self.raw_accum = Vec2::zero();
+		// cursor position stops updating when grabbed, use device motion instead
+		if self.grabbed {
+			self.pos_delta = self.raw_delta;
+		}
+		// calculate click timing
+		let now = Instant::now();
+		self.double_click = false;
+		match self.left {
+			MKBState::Pressed => {
+				if now - self.last_left_press < self.double_click_window {
+					self.double_click = true;
+					// prevent a third click from registering as another double click
+					self.last_left_press = now.checked_sub(self.double_click_window).unwrap_or(now);
+				} else {
+					self.last_left_press = now;
+				}
+				self.left_press_start = now;
+				self.left_press_duration = Duration::from_micros(0);
+			}
+			MKBState::Down | MKBState::Released => {
+				self.left_press_duration = now - self.left_press_start;
+			}
+			MKBState::None => ()
+		}
   }
+	/// true on the frame the second left click lands within the double click window
+	pub fn double_click(&self) -> bool {
+		self.double_click
+	}
+	/// time the left button has been held (or was held, on the frame it is released)
+	pub fn press_duration(&self) -> Duration {
+		self.left_press_duration
+	}
+	/// mouse motion reported by the device since last frame, independent of cursor position
+	pub fn raw_delta(&self) -> Vec2 {
+		self.raw_delta
+	}
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct GamepadState {
+	buttons: HashMap<Button, MKBState>,
+	left_stick: Vec2,
+	right_stick: Vec2,
+	left_trigger: f32,
+	right_trigger: f32,
+	connected: bool,
+}
+#[allow(dead_code)]
+impl GamepadState {
+	fn new() -> Self {
+		Self {
+			buttons: HashMap::new(),
+			left_stick: Vec2::zero(),
+			right_stick: Vec2::zero(),
+			left_trigger: 0.0,
+			right_trigger: 0.0,
+			connected: false,
+		}
+	}
+	pub fn buttons(&self) -> &HashMap<Button, MKBState> {
+		&self.buttons
+	}
+	pub fn left_stick(&self) -> Vec2 {
+		self.left_stick
+	}
+	pub fn right_stick(&self) -> Vec2 {
+		self.right_stick
+	}
+	pub fn left_trigger(&self) -> f32 {
+		self.left_trigger
+	}
+	pub fn right_trigger(&self) -> f32 {
+		self.right_trigger
+	}
+	pub fn connected(&self) -> bool {
+		self.connected
+	}
+	fn frame_sync(&mut self) {
+		let mut rm_b: Vec<Button> = Vec::new();
+		for b in self.buttons.iter_mut() {
+			if *b.1 == MKBState::Pressed { *b.1 = MKBState::Down; }
+			else if *b.1 == MKBState::Released { rm_b.push(*b.0); }
+		}
+		for b in rm_b {
+			self.buttons.remove(&b);
+		}
+	}
+}
+
+// rolling frame time statistics
+const FRAME_STATS_WINDOW: usize = 120;
+
+#[derive(Debug, Clone)]
+pub struct FrameStats {
+	// frame times in seconds, oldest first
+	samples: VecDeque<f32>,
+	window: usize,
+}
+#[allow(dead_code)]
+impl FrameStats {
+	fn new(window: usize) -> Self {
+		Self {
+			samples: VecDeque::with_capacity(window),
+			window: window.max(1),
+		}
+	}
+	fn push(&mut self, dt: Duration) {
+		if self.samples.len() >= self.window { self.samples.pop_front(); }
+		self.samples.push_back(dt.as_secs_f32());
+	}
+	/// average frame time over the window in milliseconds
+	pub fn frame_time_ms(&self) -> f32 {
+		if self.samples.is_empty() { return 0.0; }
+		self.samples.iter().sum::<f32>() / self.samples.len() as f32 * 1000.0
+	}
+	pub fn min_frame_ms(&self) -> f32 {
+		self.samples.iter().copied().fold(f32::INFINITY, f32::min) * 1000.0
+	}
+	pub fn max_frame_ms(&self) -> f32 {
+		self.samples.iter().copied().fold(0.0, f32::max) * 1000.0
+	}
+	pub fn avg_fps(&self) -> f32 {
+		let ms = self.frame_time_ms();
+		if ms > 0.0 { 1000.0 / ms } else { 0.0 }
+	}
+	/// average fps of the slowest 1% of frames in the window
+	pub fn one_percent_low(&self) -> f32 {
+		if self.samples.is_empty() { return 0.0; }
+		let mut sorted: Vec<f32> = self.samples.iter().copied().collect();
+		sorted.sort_by(|a, b| b.total_cmp(a));
+		let n = usize::max(sorted.len() / 100, 1);
+		let avg = sorted[..n].iter().sum::<f32>() / n as f32;
+		if avg > 0.0 { 1.0 / avg } else { 0.0 }
+	}
+}
+
+// time a key has been held, for SystemAccess::held_secs + key repeat
+#[derive(Debug, Default, Clone, Copy)]
+struct KeyTimer {
+	held: f32,
+	repeats: u32,
 }
 
 #[allow(dead_code)]
 #[derive(Debug)]
 pub struct SystemAccess {
 	input_cache: HashMap<KeyCode, MKBState>,
+	key_timers: HashMap<KeyCode, KeyTimer>,
+	// (delay, interval) in seconds
+	key_repeat: Option<(f32, f32)>,
+	modifiers: ModifiersState,
 	mouse_cache: MouseState,
+	gamepad_cache: GamepadState,
   frame_delta: Duration,
 	last_frame: Instant,
+	fixed_delta: Duration,
+	fixed_accum: Duration,
+	frame_stats: FrameStats,
+	// paused frames report a zero time delta, step_request lets one frame through
+	paused: bool,
+	step_request: bool,
   window_size: (u32, u32),
+	// zero sized window, updates + rendering are skipped until restored
+	minimized: bool,
 	pub debug: bool,
 	cur_scene: usize,
 	pub next_scene: usize,
 	exit: bool,
+	cursor_grab: Option<bool>,
+	cursor_visible: Option<bool>,
+	fullscreen: bool,
+	fullscreen_request: Option<bool>,
+	title_request: Option<String>,
+	icon_request: Option<Option<String>>,
+	text_cache: String,
+	// IME preedit in progress, its key presses are not text yet
+	ime_composing: bool,
+	dropped_files: Vec<PathBuf>,
+	hovered_files: Vec<PathBuf>,
 }
 #[allow(dead_code)]
 impl SystemAccess {
 	pub fn kb_inputs(&self) -> &HashMap<KeyCode, MKBState> {
 		&self.input_cache
 	}
+	/// shift/ctrl/alt/super state, updated before each update call
+	pub fn modifiers(&self) -> ModifiersState {
+		self.modifiers
+	}
+	/// true on the frame the last key of the combo goes down while the rest are held,
+	/// e.g. chord(&[KeyCode::ControlLeft, KeyCode::KeyS])
+	pub fn chord(&self, keys: &[KeyCode]) -> bool {
+		let mut pressed = false;
+		for k in keys {
+			match self.input_cache.get(k) {
+				Some(MKBState::Pressed) => pressed = true,
+				Some(MKBState::Down) => (),
+				_ => return false
+			}
+		}
+		pressed
+	}
+	/// seconds a key has been held down, 0 if it is not held
+	pub fn held_secs(&self, key: KeyCode) -> f32 {
+		self.key_timers.get(&key).map_or(0.0, |t| t.held)
+	}
+	/// re-report held keys as Pressed after delay, then every interval (e.g. menu navigation),
+	/// interval <= 0 turns repeating off
+	pub fn repeat_after(&mut self, delay: f32, interval: f32) {
+		self.key_repeat = if interval > 0.0 { Some((delay.max(0.0), interval)) } else { None };
+	}
+	// advances held timers + emits repeat presses, called before each update
+	fn key_frame_sync(&mut self) {
+		let dt = self.frame_delta.as_secs_f32();
+		self.key_timers.retain(|k, _| self.input_cache.contains_key(k));
+		for (key, state) in self.input_cache.iter_mut() {
+			match state {
+				MKBState::Pressed => { self.key_timers.insert(*key, KeyTimer::default()); }
+				MKBState::Down => {
+					let timer = self.key_timers.entry(*key).or_default();
+					timer.held += dt;
+					let due = self.key_repeat.is_some_and(|(delay, interval)| {
+						timer.held >= delay + timer.repeats as f32 * interval
+					});
+					if due {
+						timer.repeats += 1;
+						*state = MKBState::Pressed;
+					}
+				}
+				_ => ()
+			}
+		}
+	}
 	pub fn m_inputs(&self) -> &MouseState {
 		&self.mouse_cache
 	}
+	pub fn gamepad_inputs(&self) -> &GamepadState {
+		&self.gamepad_cache
+	}
+	/// characters typed or committed through IME since the last frame
+	pub fn text_input(&self) -> &str {
+		&self.text_cache
+	}
+	/// files dropped onto the window since the last frame
+	pub fn dropped_files(&self) -> &[PathBuf] {
+		&self.dropped_files
+	}
+	/// files currently being dragged over the window
+	pub fn hovered_files(&self) -> &[PathBuf] {
+		&self.hovered_files
+	}
 	pub fn time_delta(&self) -> Duration {
 		self.frame_delta
 	}
 	pub fn time_delta_sec(&self) -> f32 {
 		self.frame_delta.as_secs_f32()
 	}
+	/// progress towards the next fixed update (0-1), for interpolating between fixed states
+	pub fn fixed_alpha(&self) -> f32 {
+		self.fixed_accum.as_secs_f32() / self.fixed_delta.as_secs_f32()
+	}
+	/// freezes time: update keeps running (and rendering) with a zero time delta,
+	/// fixed_update is skipped. frame_stats still report real frame times
+	pub fn set_paused(&mut self, paused: bool) {
+		self.paused = paused;
+		self.step_request = false;
+	}
+	pub fn is_paused(&self) -> bool {
+		self.paused
+	}
+	/// while paused, advances the next frame by one fixed step (one fixed_update call)
+	pub fn step_once(&mut self) {
+		if self.paused { self.step_request = true; }
+	}
 	pub fn fps(&self) -> f32 {
 		1.0 / self.frame_delta.as_secs_f32()
 	}
+	/// averaged fps + frame times over the last FRAME_STATS_WINDOW frames
+	pub fn frame_stats(&self) -> &FrameStats {
+		&self.frame_stats
+	}
 	pub fn win_size(&self) -> Vec2 {
 		Vec2::from_u32_tuple(self.window_size)
 	}
+	pub fn is_minimized(&self) -> bool {
+		self.minimized
+	}
 	pub fn win_center(&self) -> Vec2 {
 		let x = self.window_size.0 as f32 / 2.0;
 		let y = self.window_size.1 as f32 / 2.0;
@@ -136,6 +701,29 @@ impl SystemAccess {
 	pub fn request_exit(&mut self) {
 		self.exit = true;
 	}
+	pub fn set_cursor_grab(&mut self, grab: bool) {
+		self.cursor_grab = Some(grab);
+	}
+	pub fn set_cursor_visible(&mut self, visible: bool) {
+		self.cursor_visible = Some(visible);
+	}
+	pub fn set_double_click_window(&mut self, window: Duration) {
+		self.mouse_cache.double_click_window = window;
+	}
+	/// switches between borderless fullscreen and windowed mode
+	pub fn set_fullscreen(&mut self, fullscreen: bool) {
+		self.fullscreen_request = Some(fullscreen);
+	}
+	pub fn is_fullscreen(&self) -> bool {
+		self.fullscreen
+	}
+	pub fn set_title(&mut self, title: String) {
+		self.title_request = Some(title);
+	}
+	/// replaces the window icon with the icon file at path (None clears it)
+	pub fn set_icon(&mut self, path: Option<String>) {
+		self.icon_request = Some(path);
+	}
 }
 
 #[allow(unused)]
@@ -146,6 +734,8 @@ pub trait SceneBase {
 	fn init(&mut self, sys: &mut SystemAccess, gpu: &mut GpuAccess);
 	/// actions to take when screen resizes (asynchronous with update call)
 	fn resize(&mut self, sys: &mut SystemAccess, gpu: &mut GpuAccess, width: u32, height: u32) {}
+	/// actions to take at a fixed rate (dt in seconds), zero or more times before each update
+	fn fixed_update(&mut self, sys: &mut SystemAccess, gpu: &mut GpuAccess, dt: f32) {}
 	/// actions to take per frame
 	fn update(&mut self, sys: &mut SystemAccess, gpu: &mut GpuAccess);
   /// actions to take after exiting event loop
@@ -166,11 +756,23 @@ pub struct WinitConfig {
 	pub size: (u32, u32),
 	pub min_size: (u32, u32),
 	pub position: (i32, i32),
+	/// frame rate limit, None still caps at MAX_UNCAPPED_FPS so idle scenes don't spin a core
 	pub max_fps: Option<u32>,
+	pub fixed_update_rate: u32,
 	pub title: String,
 	pub icon: Option<String>,
 	pub debug: bool,
 	pub resizable: bool,
+	pub fullscreen: bool,
+	pub present_mode: Option<PresentMode>,
+	// e.g. force Backends::VULKAN to work around dx12 driver bugs
+	pub backends: wgpu::Backends,
+	pub power_preference: wgpu::PowerPreference,
+	/// fixed width / height for rendering, letterboxed inside the window (see GpuAccess::set_viewport)
+	pub aspect_ratio: Option<f32>,
+	/// redraws to wait after the last resize event before scenes are resized, 0 resizes on every event.
+	/// coalesces the events of a window drag instead of recreating screen sized textures for each
+	pub resize_debounce: u32,
 }
 impl Default for WinitConfig {
 	fn default() -> Self {
@@ -179,146 +781,351 @@ impl Default for WinitConfig {
 			min_size: (400, 300),
 			position: (100, 100),
 			max_fps: None,
+			fixed_update_rate: 60,
 			title: "Blueprint".to_owned(),
 			icon: None,
 			debug: false,
 			resizable: true,
+			fullscreen: false,
+			present_mode: None,
+			backends: wgpu::Backends::PRIMARY,
+			power_preference: wgpu::PowerPreference::default(),
+			aspect_ratio: None,
+			resize_debounce: 1,
 		}
 	}
 }
 
+const MAX_FIXED_STEPS: u32 = 8;
+// frame limit for max_fps: None, the event loop sleeps between frames instead of polling
+const MAX_UNCAPPED_FPS: u32 = 1000;
+
+#[cfg(target_arch = "wasm32")]
+type PendingGpu = std::rc::Rc<std::cell::RefCell<Option<Result<GpuAccess<'static>, GpuInitError>>>>;
+
 #[derive(Debug)]
 struct WinitApp<'a> {
 	wait_duration: Duration,
+	present_mode: PresentMode,
+	backends: wgpu::Backends,
+	power_preference: wgpu::PowerPreference,
+	aspect_ratio: Option<f32>,
+	resize_debounce: u32,
+	// latest window size + frames since it arrived, waiting for resize_debounce
+	pending_resize: Option<((u32, u32), u32)>,
 	window_attributes: WindowAttributes,
 	gpu: Option<GpuAccess<'a>>,
+	// async gpu setup result on the web
+	#[cfg(target_arch = "wasm32")]
+	pending_gpu: Option<PendingGpu>,
 	windows: HashMap<WindowId, Arc<Window>>,
+	gilrs: Option<Gilrs>,
 	// custom app definition
 	sys: SystemAccess,
 	scenes: Vec<Box<dyn SceneBase>>,
 }
 impl<'a> WinitApp<'a> {
   fn new(config: WinitConfig, scenes: Vec<Box<dyn SceneBase>>) -> Self {
-		// convert fps to wait duration
-		let mms = if let Some(n) = config.max_fps { 1000000 / n } else { 0 };
+		// convert fps to the minimum time between frame starts
+		let frame_time = Duration::from_secs(1) / config.max_fps.unwrap_or(MAX_UNCAPPED_FPS).clamp(1, MAX_UNCAPPED_FPS);
 		// create window attributes
-		let icon = match &config.icon {
-			Some(str) => {
-				match Icon::from_path(str, None) {
-					Ok(ico) => Some(ico),
-					Err(e) => {
-						println!("Failed to open icon: {:?}", e);
-						None
-					}
-				}
-			},
-			None => None
-		};
+		let icon = config.icon.as_deref().and_then(load_icon);
 		let window_attributes = Window::default_attributes()
 			.with_min_inner_size(PhysicalSize::new(config.min_size.0, config.min_size.1))
 			.with_inner_size(PhysicalSize::new(config.size.0, config.size.1))
 			.with_resizable(config.resizable)
+			.with_fullscreen(if config.fullscreen { Some(Fullscreen::Borderless(None)) } else { None })
 			.with_window_icon(icon)
 			.with_position(Position::Physical(
 				PhysicalPosition { x: config.position.0, y: config.position.1 }
 			))
 			.with_title(config.title.as_str());
+		// add canvas to the page body
+		#[cfg(target_arch = "wasm32")]
+		let window_attributes = {
+			use winit::platform::web::WindowAttributesExtWebSys;
+			window_attributes.with_append(true)
+		};
 		// create shared data between winit and user app
 		let sys = SystemAccess {
 			input_cache: HashMap::new(),
+			key_timers: HashMap::new(),
+			key_repeat: None,
+			modifiers: ModifiersState::empty(),
 			mouse_cache: MouseState::new(),
+			gamepad_cache: GamepadState::new(),
 			frame_delta: Duration::from_micros(0),
 			last_frame: Instant::now(),
+			fixed_delta: Duration::from_secs(1) / config.fixed_update_rate.max(1),
+			fixed_accum: Duration::from_micros(0),
+			frame_stats: FrameStats::new(FRAME_STATS_WINDOW),
+			paused: false,
+			step_request: false,
 			window_size: config.size,
+			minimized: false,
 			debug: config.debug,
 			cur_scene: 0,
 			next_scene: 0,
 			exit: false,
+			cursor_grab: None,
+			cursor_visible: None,
+			fullscreen: config.fullscreen,
+			fullscreen_request: None,
+			title_request: None,
+			icon_request: None,
+			text_cache: String::new(),
+			ime_composing: false,
+			dropped_files: Vec::new(),
+			hovered_files: Vec::new(),
 		};
     Self {
 			window_attributes,
-			wait_duration: Duration::from_micros(mms.into()),
+			wait_duration: frame_time,
+			present_mode: config.present_mode.unwrap_or(PresentMode::AutoNoVsync),
+			backends: config.backends,
+			power_preference: config.power_preference,
+			aspect_ratio: config.aspect_ratio,
+			resize_debounce: config.resize_debounce,
+			pending_resize: None,
 			gpu: None,
+			#[cfg(target_arch = "wasm32")]
+			pending_gpu: None,
 			windows: HashMap::new(),
+			gilrs: match Gilrs::new() {
+				Ok(g) => Some(g),
+				Err(e) => {
+					println!("Failed to initialize gamepad support: {:?}", e);
+					None
+				}
+			},
 			sys,
 			scenes,
     }
   }
+	fn finish_init(&mut self, event_loop: &ActiveEventLoop, gpu: Result<GpuAccess<'a>, GpuInitError>) {
+		match gpu {
+			Ok(g) => self.gpu = Some(g),
+			Err(e) => {
+				println!("Failed to initialize graphics: {}", e);
+				event_loop.exit();
+				return;
+			}
+		}
+		let gpu = self.gpu.as_mut().unwrap();
+		gpu.set_aspect_ratio(self.aspect_ratio);
+		for scene in &mut self.scenes {
+			scene.init(&mut self.sys, gpu);
+		}
+		// resize events may have arrived before the gpu was ready
+		#[cfg(target_arch = "wasm32")]
+		if self.sys.cur_scene < self.scenes.len() {
+			let (w, h) = self.sys.window_size;
+			self.scenes[self.sys.cur_scene].resize(&mut self.sys, gpu, w, h);
+		}
+	}
 	fn cur_window(&self, id: &WindowId) -> Option<&Arc<Window>> {
 		self.windows.get(id)
 	}
-	async fn wgpu_init(&mut self, win: Arc<Window>) {
-		let size = win.inner_size();
-
-		// The instance is a handle to our GPU
-    // Backends::all => Vulkan + Metal + DX12 + Browser WebGPU
-    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
-			backends: wgpu::Backends::PRIMARY,
-			..Default::default()
-		});
-    let surface = instance.create_surface(win).unwrap();
+	fn apply_window_requests(&mut self) {
+		if let Some(fullscreen) = self.sys.fullscreen_request.take() {
+			let mode = if fullscreen { Some(Fullscreen::Borderless(None)) } else { None };
+			for win in self.windows.values() {
+				win.set_fullscreen(mode.clone());
+			}
+			self.sys.fullscreen = fullscreen;
+		}
+		if let Some(title) = self.sys.title_request.take() {
+			for win in self.windows.values() {
+				win.set_title(&title);
+			}
+		}
+		if let Some(path) = self.sys.icon_request.take() {
+			let icon = path.as_deref().and_then(load_icon);
+			for win in self.windows.values() {
+				win.set_window_icon(icon.clone());
+			}
+		}
 
-    // handle for graphics card
-    let adapter = instance.request_adapter(
-      &wgpu::RequestAdapterOptions {
-				power_preference: wgpu::PowerPreference::default(),
-				compatible_surface: Some(&surface),
-				force_fallback_adapter: false,
-      },
-    ).await.unwrap();
-
-		// grab device & queue from adapter
-    let (device, queue) = adapter.request_device(
-      &wgpu::DeviceDescriptor {
-        required_features: wgpu::Features::POLYGON_MODE_LINE | wgpu::Features::POLYGON_MODE_POINT,
-        required_limits: wgpu::Limits::default(),
-        label: None,
-        memory_hints: Default::default(),
-      },
-      None, // Trace path
-    ).await.unwrap();
-
-		// define surface format for window
-		let surface_caps = surface.get_capabilities(&adapter);
-		let surface_format = if surface_caps.formats.contains(&TextureFormat::Rgba8UnormSrgb) {
-			TextureFormat::Rgba8UnormSrgb
-		} else if surface_caps.formats.contains(&TextureFormat::Rgba8Unorm) {
-			TextureFormat::Rgba8Unorm
-		} else {
-			surface_caps.formats.iter()
-				.find(|f| f.is_srgb())
-				.copied()
-				.unwrap_or(surface_caps.formats[0])
+		if let Some(grab) = self.sys.cursor_grab.take() {
+			for win in self.windows.values() {
+				let res = if grab {
+					// not all platforms support locking, fall back to confining
+					win.set_cursor_grab(CursorGrabMode::Locked)
+						.or_else(|_| win.set_cursor_grab(CursorGrabMode::Confined))
+				} else {
+					win.set_cursor_grab(CursorGrabMode::None)
+				};
+				match res {
+					Ok(_) => self.sys.mouse_cache.grabbed = grab,
+					Err(e) => println!("ERR: Failed to set cursor grab - {}", e)
+				}
+			}
+		}
+		if let Some(visible) = self.sys.cursor_visible.take() {
+			for win in self.windows.values() {
+				win.set_cursor_visible(visible);
+			}
+		}
+	}
+	fn poll_gamepad(&mut self) {
+		let gilrs = match &mut self.gilrs {
+			Some(g) => g,
+			None => return
 		};
+		let pad = &mut self.sys.gamepad_cache;
+		while let Some(evt) = gilrs.next_event() {
+			match evt.event {
+				EventType::ButtonPressed(btn, _) => {
+					pad.buttons.insert(btn, MKBState::Pressed);
+				}
+				EventType::ButtonReleased(btn, _) => {
+					pad.buttons.insert(btn, MKBState::Released);
+				}
+				EventType::ButtonChanged(Button::LeftTrigger2, v, _) => pad.left_trigger = v,
+				EventType::ButtonChanged(Button::RightTrigger2, v, _) => pad.right_trigger = v,
+				EventType::AxisChanged(axis, v, _) => {
+					match axis {
+						Axis::LeftStickX => pad.left_stick.x = v,
+						Axis::LeftStickY => pad.left_stick.y = v,
+						Axis::RightStickX => pad.right_stick.x = v,
+						Axis::RightStickY => pad.right_stick.y = v,
+						_ => ()
+					}
+				}
+				EventType::Connected => {
+					if self.sys.debug {
+						println!("Connected gamepad {:?}", evt.id);
+					}
+					pad.connected = true;
+				}
+				EventType::Disconnected => {
+					if self.sys.debug {
+						println!("Disconnected gamepad {:?}", evt.id);
+					}
+					*pad = GamepadState::new();
+					pad.connected = gilrs.gamepads().next().is_some();
+				}
+				_ => ()
+			}
+		}
+	}
+}
 
-		if self.sys.debug {
-			println!("Surface format: {:?}", surface_format);
-		}
-
-		let config = SurfaceConfiguration {
-      usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-      format: surface_format,
-      width: size.width,
-      height: size.height,
-      present_mode: wgpu::PresentMode::AutoNoVsync,
-      alpha_mode: surface_caps.alpha_modes[0],
-      view_formats: vec![],
-      desired_maximum_frame_latency: 2,
-    };
-		// invoked via resize call
-		// surface.configure(&device, &config);
+// creates gpu access bound to the window surface
+async fn wgpu_init(
+	win: Arc<Window>,
+	backends: wgpu::Backends,
+	power_preference: wgpu::PowerPreference,
+	present_mode: PresentMode,
+	debug: bool
+) -> Result<GpuAccess<'static>, GpuInitError> {
+	let size = win.inner_size();
 
-		if self.sys.debug {
-			println!("Sucessfully linked gpu: {:?}", adapter.get_info());
-		}
-		self.gpu = Some(GpuAccess {
-			device,
-			queue,
-			screen_surface: surface,
-			screen_config: config,
-			screen_format: surface_format,
-		});
+	// The instance is a handle to our GPU
+  // Backends::all => Vulkan + Metal + DX12 + Browser WebGPU
+  let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+		backends,
+		..Default::default()
+	});
+  let surface = instance.create_surface(win).map_err(GpuInitError::SurfaceCreation)?;
+
+  // handle for graphics card
+  let adapter = instance.request_adapter(
+    &wgpu::RequestAdapterOptions {
+			power_preference,
+			compatible_surface: Some(&surface),
+			force_fallback_adapter: false,
+    },
+  ).await.ok_or(GpuInitError::NoAdapter)?;
+
+	// grab device & queue from adapter
+	let (device, queue, profiling) = request_device(&adapter).await?;
+
+	// define surface format for window
+	let surface_caps = surface.get_capabilities(&adapter);
+	let surface_format = if surface_caps.formats.contains(&TextureFormat::Rgba8UnormSrgb) {
+		TextureFormat::Rgba8UnormSrgb
+	} else if surface_caps.formats.contains(&TextureFormat::Rgba8Unorm) {
+		TextureFormat::Rgba8Unorm
+	} else {
+		surface_caps.formats.iter()
+			.find(|f| f.is_srgb())
+			.copied()
+			.unwrap_or(surface_caps.formats[0])
+	};
+
+	if debug {
+		println!("Surface format: {:?}", surface_format);
+	}
+
+	// unsupported modes would panic on the first configure
+	let present_mode = if present_mode_supported(present_mode, &surface_caps.present_modes) { present_mode } else {
+		println!("ERR: Present mode {:?} is not supported by this surface, using AutoNoVsync", present_mode);
+		PresentMode::AutoNoVsync
+	};
+	let config = SurfaceConfiguration {
+    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+    format: surface_format,
+    width: size.width,
+    height: size.height,
+    present_mode,
+    alpha_mode: surface_caps.alpha_modes[0],
+    view_formats: vec![],
+    desired_maximum_frame_latency: 2,
+  };
+	// invoked via resize call
+	// surface.configure(&device, &config);
+
+	if debug {
+		println!("Sucessfully linked gpu: {:?}", adapter.get_info());
+		println!("Device limits: {:?}", device.limits());
+	}
+	if adapter.get_info().device_type == wgpu::DeviceType::Cpu {
+		println!("ERR: Running on a software adapter, expect poor performance");
 	}
+	// pass profiling is opt in outside of debug, see GpuAccess::set_profiling
+	let profiler = if profiling && debug { Some(PassProfiler::new(&device, &queue)) } else { None };
+	Ok(GpuAccess {
+		device,
+		queue,
+		screen_surface: Some(surface),
+		screen_config: config,
+		screen_format: surface_format,
+		present_modes: surface_caps.present_modes,
+		profiler,
+		profiling_supported: profiling,
+		anisotropy: supports_anisotropy(&adapter),
+		adapter_info: adapter.get_info(),
+		aspect_ratio: None,
+	})
+}
+
+// auto modes are always supported (with fallbacks)
+fn present_mode_supported(mode: PresentMode, supported: &[PresentMode]) -> bool {
+	mode == PresentMode::AutoVsync || mode == PresentMode::AutoNoVsync || supported.contains(&mode)
+}
+
+fn supports_anisotropy(adapter: &wgpu::Adapter) -> bool {
+	adapter.get_downlevel_capabilities().flags.contains(wgpu::DownlevelFlags::ANISOTROPIC_FILTERING)
+}
+
+// shared by windowed + headless setup, returns whether pass profiling is supported
+async fn request_device(adapter: &wgpu::Adapter) -> Result<(Device, Queue, bool), GpuInitError> {
+	// only request what the adapter supports (e.g. webgpu lacks polygon modes),
+	// pipelines check device.features() and fall back where needed
+	let desired = wgpu::Features::POLYGON_MODE_LINE | wgpu::Features::POLYGON_MODE_POINT | wgpu::Features::TIMESTAMP_QUERY;
+	let features = adapter.features() & desired;
+	let profiling = features.contains(wgpu::Features::TIMESTAMP_QUERY);
+	let (device, queue) = adapter.request_device(
+		&wgpu::DeviceDescriptor {
+			required_features: features,
+			required_limits: wgpu::Limits::default(),
+			label: None,
+			memory_hints: Default::default(),
+		},
+		None, // Trace path
+	).await.map_err(GpuInitError::DeviceRequest)?;
+	Ok((device, queue, profiling))
 }
 impl<'a> ApplicationHandler for WinitApp<'a> {
   // initialization
@@ -332,18 +1139,31 @@ impl<'a> ApplicationHandler for WinitApp<'a> {
 		if self.sys.debug {
 			println!("Starting event loop");
 		}
+		#[cfg(target_arch = "wasm32")]
+		if self.pending_gpu.is_some() { return; }
 		match event_loop.create_window(self.window_attributes.clone()) {
 			Ok(win) => {
 				win.set_ime_allowed(true);
 				let window_handle = Arc::new(win);
-				pollster::block_on(self.wgpu_init(window_handle.clone()));
+				let init = wgpu_init(
+					window_handle.clone(), self.backends, self.power_preference, self.present_mode, self.sys.debug
+				);
 				if self.sys.debug {
 					println!("Successfully launched window {:?}", window_handle.id());
 				}
 				self.windows.insert(window_handle.id(), window_handle);
-				for scene in &mut self.scenes {
-					scene.init(&mut self.sys, self.gpu.as_mut().unwrap());
+				// browsers cannot block on async, finish setup once the gpu is ready
+				#[cfg(target_arch = "wasm32")]
+				{
+					let slot = std::rc::Rc::new(std::cell::RefCell::new(None));
+					let result = slot.clone();
+					wasm_bindgen_futures::spawn_local(async move {
+						*result.borrow_mut() = Some(init.await);
+					});
+					self.pending_gpu = Some(slot);
 				}
+				#[cfg(not(target_arch = "wasm32"))]
+				self.finish_init(event_loop, pollster::block_on(init));
 			}
 			Err(e) => {
 				println!("Failed to create window: {}", e);
@@ -353,10 +1173,16 @@ impl<'a> ApplicationHandler for WinitApp<'a> {
 	}
   // system updates
   fn new_events(&mut self, _event_loop: &ActiveEventLoop, _cause: StartCause) {
+		#[cfg(target_arch = "wasm32")]
+		if let Some(gpu) = self.pending_gpu.as_ref().and_then(|slot| slot.borrow_mut().take()) {
+			self.pending_gpu = None;
+			self.finish_init(_event_loop, gpu);
+		}
+		self.poll_gamepad();
     // calculate time data
 		let now = Instant::now();
 		self.sys.frame_delta = now - self.sys.last_frame;
-		if self.sys.frame_delta > self.wait_duration {
+		if self.sys.frame_delta >= self.wait_duration {
 			self.sys.last_frame = now;
 			for win in &self.windows {
 				win.1.request_redraw();
@@ -372,16 +1198,22 @@ impl<'a> ApplicationHandler for WinitApp<'a> {
 			}
 			WindowEvent::Resized( phys_size, .. ) => {
 				self.sys.window_size = phys_size.into();
+				self.sys.minimized = phys_size.width == 0 || phys_size.height == 0;
 				if self.sys.debug {
 					println!("Resized window {:?} - ({}, {})", win_id, phys_size.width, phys_size.height);
 				}
-				if let Some(r) = &mut self.gpu {
-					if self.sys.cur_scene < self.scenes.len() {
-						self.scenes[self.sys.cur_scene].resize(&mut self.sys, r, phys_size.width, phys_size.height);
-					}
+				if self.resize_debounce > 0 {
+					// applied in RedrawRequested once events settle
+					self.pending_resize = Some((phys_size.into(), 0));
 				}
+				else if let (Some(r), false) = (&mut self.gpu, self.sys.minimized) && self.sys.cur_scene < self.scenes.len() {
+					self.scenes[self.sys.cur_scene].resize(&mut self.sys, r, phys_size.width, phys_size.height);
+				}
+			}
+			WindowEvent::ModifiersChanged(m) => {
+				self.sys.modifiers = m.state();
 			}
-			WindowEvent::KeyboardInput { event: KeyEvent { physical_key: key, state, repeat, .. }, .. } => {
+			WindowEvent::KeyboardInput { event: KeyEvent { physical_key: key, state, repeat, text, .. }, .. } => {
 				// add key to input cache
 				if let PhysicalKey::Code(x) = key {
 					if state.is_pressed() && !repeat {
@@ -391,6 +1223,10 @@ impl<'a> ApplicationHandler for WinitApp<'a> {
 						self.sys.input_cache.insert(x, MKBState::Released);
 					}
 				}
+				// plain keys only arrive here, not as IME commits
+				if let (Some(t), true, false) = (text, state.is_pressed(), self.sys.ime_composing) {
+					self.sys.text_cache.extend(t.chars().filter(|c| !c.is_control()));
+				}
 			}
 			WindowEvent::MouseInput { state, button, .. } => {
         if button == MouseButton::Left {
@@ -428,6 +1264,16 @@ impl<'a> ApplicationHandler for WinitApp<'a> {
 			WindowEvent::CursorEntered { .. } => {
 				self.sys.mouse_cache.cursor_in = true;
 			}
+			WindowEvent::HoveredFile(path) => {
+				self.sys.hovered_files.push(path);
+			}
+			WindowEvent::HoveredFileCancelled => {
+				self.sys.hovered_files.clear();
+			}
+			WindowEvent::DroppedFile(path) => {
+				self.sys.hovered_files.clear();
+				self.sys.dropped_files.push(path);
+			}
 			WindowEvent::Ime(ime) => {
 				match ime {
 					Ime::Enabled => {
@@ -439,17 +1285,53 @@ impl<'a> ApplicationHandler for WinitApp<'a> {
 							None => println!("ERR: Could not find window for IME")
 						}
 					}
+					Ime::Preedit(txt, _) => {
+						self.sys.ime_composing = !txt.is_empty();
+					}
 					Ime::Commit(chr) => {
-						println!("Committing character {chr}");
+						self.sys.ime_composing = false;
+						self.sys.text_cache.push_str(&chr);
+					}
+					Ime::Disabled => {
+						self.sys.ime_composing = false;
 					}
-					_ => ()
 				}
 			}
 			WindowEvent::RedrawRequested => {
 				// app  update actions
-				if let Some(r) = &mut self.gpu {
+				if let (Some(r), false) = (&mut self.gpu, self.sys.minimized) {
+					if let Some(((w, h), frames)) = self.pending_resize {
+						if frames + 1 >= self.resize_debounce {
+							self.pending_resize = None;
+							if self.sys.cur_scene < self.scenes.len() {
+								self.scenes[self.sys.cur_scene].resize(&mut self.sys, r, w, h);
+							}
+						} else {
+							self.pending_resize = Some(((w, h), frames + 1));
+						}
+					}
 					self.sys.mouse_cache.frame_sync();
+					self.sys.key_frame_sync();
+					self.sys.frame_stats.push(self.sys.frame_delta);
+					if self.sys.paused {
+						// the leftover accumulator is always below one step, so this runs exactly one fixed_update
+						self.sys.frame_delta = if self.sys.step_request { self.sys.fixed_delta } else { Duration::from_micros(0) };
+						self.sys.step_request = false;
+					}
 					if self.sys.cur_scene < self.scenes.len() {
+						// catch up on fixed updates, dropping time if too far behind
+						self.sys.fixed_accum += self.sys.frame_delta;
+						let fixed_dt = self.sys.fixed_delta;
+						let mut steps = 0;
+						while self.sys.fixed_accum >= fixed_dt {
+							self.sys.fixed_accum -= fixed_dt;
+							steps += 1;
+							if steps > MAX_FIXED_STEPS {
+								self.sys.fixed_accum = Duration::from_micros(0);
+								break;
+							}
+							self.scenes[self.sys.cur_scene].fixed_update(&mut self.sys, r, fixed_dt.as_secs_f32());
+						}
 						self.scenes[self.sys.cur_scene].update(&mut self.sys, r);
 					}
 					// respond to app requests
@@ -467,6 +1349,8 @@ impl<'a> ApplicationHandler for WinitApp<'a> {
 					}
 				}
 
+				self.apply_window_requests();
+
 				// clean up input cache
 				let mut rm_k: Vec<KeyCode> = Vec::new();
 				for k in &mut self.sys.input_cache.iter_mut() {
@@ -477,6 +1361,13 @@ impl<'a> ApplicationHandler for WinitApp<'a> {
 					self.sys.input_cache.remove(&k);
 				}
 
+				// clean up text + dropped file caches
+				self.sys.text_cache.clear();
+				self.sys.dropped_files.clear();
+
+				// clean up gamepad cache
+				self.sys.gamepad_cache.frame_sync();
+
 				// clean up mouse cache
 				self.sys.mouse_cache.scroll = 0.0;
 				if self.sys.mouse_cache.left == MKBState::Pressed {
@@ -490,14 +1381,19 @@ impl<'a> ApplicationHandler for WinitApp<'a> {
 					self.sys.mouse_cache.right = MKBState::None;
 				}
 
-				// wait until
-				if self.wait_duration > Duration::from_micros(0) {
-					event_loop.set_control_flow(ControlFlow::WaitUntil(Instant::now() + self.wait_duration));
-				}
+				// sleep until the next frame is due, time spent on this frame counts towards it
+				event_loop.set_control_flow(ControlFlow::WaitUntil(self.sys.last_frame + self.wait_duration));
 			}
 			_ => (),
 		}
   }
+	// raw device inputs
+	fn device_event(&mut self, _event_loop: &ActiveEventLoop, _device_id: DeviceId, event: DeviceEvent) {
+		if let DeviceEvent::MouseMotion { delta } = event {
+			self.sys.mouse_cache.raw_accum.x += delta.0 as f32;
+			self.sys.mouse_cache.raw_accum.y += delta.1 as f32;
+		}
+	}
 	// note: not all devices support suspend events
 	fn suspended(&mut self, _evt_loop: &ActiveEventLoop) {
 		if self.sys.debug {
@@ -518,12 +1414,34 @@ impl<'a> ApplicationHandler for WinitApp<'a> {
 	}
 }
 
+#[cfg(windows)]
+fn load_icon(path: &str) -> Option<Icon> {
+	use winit::platform::windows::IconExtWindows;
+	match Icon::from_path(path, None) {
+		Ok(ico) => Some(ico),
+		Err(e) => {
+			println!("Failed to open icon: {:?}", e);
+			None
+		}
+	}
+}
+
+// .ico loading is only implemented for windows
+#[cfg(not(windows))]
+fn load_icon(path: &str) -> Option<Icon> {
+	println!("Window icons are not supported on this platform, ignoring {}", path);
+	None
+}
+
 pub fn launch(config: WinitConfig, scenes: Vec<Box<dyn SceneBase>>) {
-	let event_loop = EventLoop::new().unwrap();
-	match config.max_fps {
-		Some(_) => event_loop.set_control_flow(ControlFlow::WaitUntil(Instant::now())),
-		None => event_loop.set_control_flow(ControlFlow::Poll)
+	let event_loop = match EventLoop::new() {
+		Ok(e) => e,
+		Err(e) => {
+			println!("Failed to create event loop: {}", e);
+			return;
+		}
 	};
+	event_loop.set_control_flow(ControlFlow::WaitUntil(Instant::now()));
   let mut winit_app = WinitApp::new(config, scenes);
   match event_loop.run_app(&mut winit_app) {
 		Ok(_) => (),